@@ -1,11 +1,18 @@
-use mongodb::{options::Credential, Client, Database};
+use actix_web::rt::time::sleep;
+use mongodb::{
+    bson::{doc, Document},
+    options::{ClientOptions, Credential},
+    Client, Database, IndexModel,
+};
+use std::time::Duration;
 
+static mut CLIENT: Option<Client> = None;
 static mut DB: Option<Database> = None;
 
-pub async fn connect(uri: String) {
-    let mut client = Client::with_uri_str(uri)
+async fn try_connect(uri: &str) -> Result<(Client, Database), String> {
+    let mut options = ClientOptions::parse(uri)
         .await
-        .expect("Failed to connect to database");
+        .map_err(|error| error.to_string())?;
 
     if let (Ok(username), Ok(password)) = (
         std::env::var("DATABASE_USERNAME"),
@@ -17,15 +24,97 @@ pub async fn connect(uri: String) {
             .source("admin".to_string())
             .build();
 
-        let options = mongodb::options::ClientOptions::builder()
-            .credential(credential)
-            .build();
+        options.credential = Some(credential);
+    }
+    if let Some(size) = std::env::var("DB_MAX_POOL_SIZE")
+        .ok()
+        .and_then(|size| size.parse::<u32>().ok())
+    {
+        options.max_pool_size = Some(size);
+    }
+    if let Some(size) = std::env::var("DB_MIN_POOL_SIZE")
+        .ok()
+        .and_then(|size| size.parse::<u32>().ok())
+    {
+        options.min_pool_size = Some(size);
+    }
+    if let Some(timeout) = std::env::var("DB_CONNECT_TIMEOUT_MS")
+        .ok()
+        .and_then(|timeout| timeout.parse::<u64>().ok())
+    {
+        options.connect_timeout = Some(Duration::from_millis(timeout));
+    }
+
+    let client = Client::with_options(options).map_err(|error| error.to_string())?;
 
-        client = Client::with_options(options).expect("Failed to connect to database");
+    let database = client.database("pms");
+    database
+        .run_command(doc! { "ping": 1 }, None)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    Ok((client, database))
+}
+
+async fn create_indexes(database: &Database) -> Result<(), String> {
+    database
+        .collection::<Document>("projects")
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "last_activity": -1 })
+                .build(),
+            None,
+        )
+        .await
+        .map_err(|error| error.to_string())?;
+
+    Ok(())
+}
+
+pub async fn connect(uri: String) {
+    let attempts = std::env::var("DB_CONNECT_ATTEMPTS")
+        .unwrap()
+        .parse::<u32>()
+        .expect("INVALID_DB_CONNECT_ATTEMPTS")
+        .max(1);
+    let mut interval = std::env::var("DB_CONNECT_INTERVAL_MS")
+        .unwrap()
+        .parse::<u64>()
+        .expect("INVALID_DB_CONNECT_INTERVAL_MS");
+
+    for attempt in 1..=attempts {
+        match try_connect(&uri).await {
+            Ok((client, database)) => {
+                println!("Connected to database (attempt {}/{})", attempt, attempts);
+                if let Err(error) = create_indexes(&database).await {
+                    println!("Failed to create database indexes: {}", error);
+                }
+                unsafe {
+                    CLIENT = Some(client);
+                    DB = Some(database);
+                }
+                return;
+            }
+            Err(error) => {
+                println!(
+                    "Failed to connect to database (attempt {}/{}): {}",
+                    attempt, attempts, error
+                );
+                if attempt < attempts {
+                    sleep(Duration::from_millis(interval)).await;
+                    interval = interval.saturating_mul(2);
+                }
+            }
+        }
     }
 
+    panic!("Failed to connect to database");
+}
+
+pub fn get_client() -> Client {
     unsafe {
-        DB = Some(client.database("pms"));
+        let client = &*std::ptr::addr_of!(CLIENT);
+        client.clone().expect("Database is not available yet!")
     }
 }
 
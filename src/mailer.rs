@@ -0,0 +1,43 @@
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+pub fn is_enabled() -> bool {
+    std::env::var("SMTP_URL").is_ok() && std::env::var("SMTP_FROM").is_ok()
+}
+
+pub async fn send(to: &[String], subject: &str, body: &str) -> Result<(), String> {
+    let (url, from) = match (std::env::var("SMTP_URL"), std::env::var("SMTP_FROM")) {
+        (Ok(url), Ok(from)) => (url, from),
+        _ => return Ok(()),
+    };
+
+    let from = from
+        .parse::<Mailbox>()
+        .map_err(|_| "INVALID_SMTP_FROM".to_string())?;
+    let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(&url)
+        .map_err(|_| "INVALID_SMTP_URL".to_string())?
+        .build();
+
+    for address in to {
+        let to = match address.parse::<Mailbox>() {
+            Ok(to) => to,
+            Err(_) => continue,
+        };
+        let message = Message::builder()
+            .from(from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body.to_string())
+            .map_err(|_| "BUILDING_FAILED".to_string())?;
+
+        transport
+            .send(message)
+            .await
+            .map_err(|_| "SENDING_FAILED".to_string())?;
+    }
+
+    Ok(())
+}
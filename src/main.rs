@@ -4,8 +4,12 @@ use actix_web::{web, App, HttpServer};
 use std::{fs::read_to_string, io};
 
 mod database;
+mod mailer;
+mod middleware;
+mod migration;
 mod models;
 mod routes;
+mod scheduler;
 
 fn load_env() {
     if let Ok(env) = read_to_string(".env") {
@@ -40,6 +44,66 @@ fn load_env() {
     if std::env::var("PORT").is_err() {
         std::env::set_var("PORT", "8000");
     }
+    if std::env::var("DB_CONNECT_ATTEMPTS").is_err() {
+        std::env::set_var("DB_CONNECT_ATTEMPTS", "5");
+    }
+    if std::env::var("DB_CONNECT_INTERVAL_MS").is_err() {
+        std::env::set_var("DB_CONNECT_INTERVAL_MS", "1000");
+    }
+    if std::env::var("MAX_PAGE_SIZE").is_err() {
+        std::env::set_var("MAX_PAGE_SIZE", "100");
+    }
+    if std::env::var("BASE_CURRENCY").is_err() {
+        std::env::set_var("BASE_CURRENCY", "IDR");
+    }
+    if std::env::var("ENFORCE_CHECKLIST").is_err() {
+        std::env::set_var("ENFORCE_CHECKLIST", "true");
+    }
+    if std::env::var("DIGEST_TIME").is_err() {
+        std::env::set_var("DIGEST_TIME", "08:00");
+    }
+    if std::env::var("REQUEST_TIMEOUT").is_err() {
+        std::env::set_var("REQUEST_TIMEOUT", "60");
+    }
+    if std::env::var("PROJECT_CODE_TEMPLATE").is_err() {
+        std::env::set_var("PROJECT_CODE_TEMPLATE", "{customer_prefix}-{year}-{seq}");
+    }
+    if std::env::var("MAX_JSON_BYTES").is_err() {
+        std::env::set_var("MAX_JSON_BYTES", "8388608");
+    }
+    if std::env::var("MAX_MULTIPART_BYTES").is_err() {
+        std::env::set_var("MAX_MULTIPART_BYTES", "52428800");
+    }
+    if std::env::var("VALUE_PRECISION").is_err() {
+        std::env::set_var("VALUE_PRECISION", "3");
+    }
+    if std::env::var("PROJECT_DEFAULT_SORT").is_err() {
+        std::env::set_var("PROJECT_DEFAULT_SORT", "latest");
+    }
+    if std::env::var("JWT_ISSUER").is_err() {
+        std::env::set_var("JWT_ISSUER", "Redian");
+    }
+    if std::env::var("JWT_AUDIENCE").is_err() {
+        std::env::set_var("JWT_AUDIENCE", std::env::var("BASE_URL").unwrap());
+    }
+    if std::env::var("JWT_KID").is_err() {
+        std::env::set_var("JWT_KID", "default");
+    }
+    if std::env::var("OVERVIEW_INCIDENT_LIMIT").is_err() {
+        std::env::set_var("OVERVIEW_INCIDENT_LIMIT", "5");
+    }
+    if std::env::var("TRUSTED_PROXY").is_err() {
+        std::env::set_var("TRUSTED_PROXY", "");
+    }
+    if std::env::var("MAINTENANCE").is_err() {
+        std::env::set_var("MAINTENANCE", "false");
+    }
+    if std::env::var("FILES_ROOT").is_err() {
+        std::env::set_var("FILES_ROOT", "./files");
+    }
+    if std::env::var("FILE_RATE_LIMIT").is_err() {
+        std::env::set_var("FILE_RATE_LIMIT", "120");
+    }
 }
 
 #[actix_web::main]
@@ -52,7 +116,12 @@ async fn main() -> io::Result<()> {
         .expect("INVALID_PORT");
 
     database::connect(std::env::var("DATABASE_URI").unwrap()).await;
+    if let Err(error) = migration::run().await {
+        panic!("Failed to migrate database: {}", error);
+    }
     models::user::load_keys();
+    middleware::set_maintenance(std::env::var("MAINTENANCE").unwrap() == "true");
+    scheduler::start();
 
     println!("Running on: http://localhost:{:#?}", port);
 
@@ -63,19 +132,36 @@ async fn main() -> io::Result<()> {
             .allow_any_method()
             .supports_credentials();
         App::new()
+            .wrap(middleware::TimeoutMiddlewareFactory::from_env())
             .wrap(models::user::UserAuthenticationMiddlewareFactory)
+            .wrap(middleware::MaintenanceMiddlewareFactory)
             .wrap(cors)
+            .app_data(routes::json_config())
+            .app_data(routes::multipart_config())
             .service(
                 web::scope(&std::env::var("BASE_PATH").unwrap())
+                    .service(routes::get_health)
                     .service(routes::get_file)
                     .service(routes::get_overview)
+                    .service(routes::get_overview_csv)
+                    .service(routes::get_overview_incidents)
+                    .service(routes::get_admin_files_verify)
+                    .service(routes::fix_admin_files)
+                    .service(routes::update_maintenance)
                     .service(routes::company::get_company)
                     .service(routes::company::create_company)
                     .service(routes::company::update_company)
                     .service(routes::company::update_company_image)
+                    .service(routes::company::create_company_images)
+                    .service(routes::company::update_company_images)
+                    .service(routes::company::delete_company_image)
                     .service(routes::user::get_users)
+                    .service(routes::user::get_user_notifications)
                     .service(routes::user::get_user)
                     .service(routes::user::create_user)
+                    .service(routes::user::create_user_role_assignment)
+                    .service(routes::user::update_user_notification_read)
+                    .service(routes::user::update_user_preference)
                     .service(routes::user::update_user)
                     .service(routes::user::update_user_image)
                     .service(routes::user::login)
@@ -90,33 +176,86 @@ async fn main() -> io::Result<()> {
                     .service(routes::customer::create_customer)
                     .service(routes::customer::update_customer)
                     .service(routes::customer::update_customer_image)
+                    .service(routes::customer::create_customer_images)
+                    .service(routes::customer::update_customer_images)
+                    .service(routes::customer::delete_customer_image)
+                    .service(routes::customer::create_customer_person)
+                    .service(routes::customer::update_customer_person)
+                    .service(routes::customer::delete_customer_person)
                     .service(routes::customer::delete_customer)
                     .service(routes::project::get_projects)
+                    .service(routes::project::get_projects_count)
+                    .service(routes::project::get_projects_recent)
+                    .service(routes::project::get_projects_ending_soon)
+                    .service(routes::project::get_projects_summary)
                     .service(routes::project::get_project)
+                    .service(routes::project::get_project_areas_empty)
                     .service(routes::project::get_project_areas)
+                    .service(routes::project::get_project_units)
                     .service(routes::project::get_project_tasks)
+                    .service(routes::project::get_project_tasks_count)
+                    .service(routes::project::get_project_tasks_not_started)
+                    .service(routes::project::get_project_tasks_tree)
+                    .service(routes::project::get_project_variance_histogram)
+                    .service(routes::project::get_project_support_tasks)
                     .service(routes::project::get_project_task)
+                    .service(routes::project::get_project_task_breadcrumb)
+                    .service(routes::project::get_project_task_comments)
+                    .service(routes::project::get_project_cost)
+                    .service(routes::project::get_project_budget)
+                    .service(routes::project::get_project_evm)
+                    .service(routes::project::get_project_readiness)
+                    .service(routes::project::get_project_progress_delta)
+                    .service(routes::project::get_project_progress_png)
                     .service(routes::project::get_project_progress)
                     .service(routes::project::get_project_members)
+                    .service(routes::project::get_project_member_activity)
+                    .service(routes::project::get_project_changes)
                     .service(routes::project::get_project_reports)
+                    .service(routes::project::get_project_reports_count)
+                    .service(routes::project::get_project_reports_list)
+                    .service(routes::project::get_project_reports_weekly)
+                    .service(routes::project::get_project_reports_plan_coverage)
+                    .service(routes::project::get_project_report_documentation_zip)
                     .service(routes::project::get_project_report)
+                    .service(routes::project::create_project_full)
                     .service(routes::project::create_project)
                     .service(routes::project::create_project_role)
                     .service(routes::project::create_project_task)
+                    .service(routes::project::create_project_member_reassign)
+                    .service(routes::project::create_project_member_bulk)
+                    .service(routes::project::create_project_area_merge)
+                    .service(routes::project::create_project_baseline)
+                    .service(routes::project::create_project_task_bulk_preview)
                     .service(routes::project::create_project_task_bulk)
+                    .service(routes::project::create_project_task_shift)
                     .service(routes::project::create_project_task_sub)
+                    .service(routes::project::create_project_task_attachment)
+                    .service(routes::project::create_project_task_checklist)
+                    .service(routes::project::create_project_task_duplicate)
+                    .service(routes::project::create_project_task_comment)
                     .service(routes::project::create_project_report)
                     .service(routes::project::create_project_incident)
                     .service(routes::project::update_project_status)
                     .service(routes::project::update_project_task)
                     .service(routes::project::update_project_task_period)
                     .service(routes::project::update_project_task_status)
+                    .service(routes::project::update_project_task_checklist)
                     .service(routes::project::update_project_report)
+                    .service(routes::project::update_project_report_cover)
+                    .service(routes::project::update_project_report_documentation)
                     .service(routes::project::update_project_role)
+                    .service(routes::project::update_project_budget)
                     .service(routes::project::add_project_member)
                     .service(routes::project::add_project_area)
+                    .service(routes::project::update_project_area)
+                    .service(routes::project::delete_project_areas_empty)
                     .service(routes::project::delete_project_area)
-                    .service(routes::project::delete_project_task),
+                    .service(routes::project::delete_project_tasks)
+                    .service(routes::project::delete_project_task)
+                    .service(routes::project::delete_project_task_attachment)
+                    .service(routes::project::delete_project_task_checklist)
+                    .service(routes::project::delete_project_task_comment),
             )
     })
     .bind(("127.0.0.1", port))?
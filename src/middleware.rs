@@ -0,0 +1,204 @@
+use actix_service::{self, Transform};
+use actix_web::{
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse},
+    rt::time::timeout,
+    Error, HttpRequest, HttpResponse,
+};
+use futures::{
+    future::{ready, LocalBoxFuture, Ready},
+    FutureExt,
+};
+use std::{
+    collections::HashMap,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+static MAINTENANCE: AtomicBool = AtomicBool::new(false);
+static FILE_BUCKETS: OnceLock<Mutex<HashMap<String, (f64, Instant)>>> = OnceLock::new();
+
+pub struct TimeoutMiddleware<S> {
+    service: Rc<S>,
+    duration: Duration,
+}
+pub struct TimeoutMiddlewareFactory {
+    duration: Duration,
+}
+pub struct MaintenanceMiddleware<S> {
+    service: Rc<S>,
+}
+pub struct MaintenanceMiddlewareFactory;
+
+pub fn is_maintenance() -> bool {
+    MAINTENANCE.load(Ordering::Relaxed)
+}
+pub fn set_maintenance(enabled: bool) {
+    MAINTENANCE.store(enabled, Ordering::Relaxed);
+}
+pub fn client_address(req: &HttpRequest) -> String {
+    let peer = match req.peer_addr() {
+        Some(peer) => peer.ip().to_string(),
+        None => return "unknown".to_string(),
+    };
+    // Forwarded headers are client controlled unless the request came through our own proxy
+    let trusted = std::env::var("TRUSTED_PROXY")
+        .unwrap_or_default()
+        .split(',')
+        .any(|a| a.trim() == peer);
+    if trusted {
+        if let Some(address) = req.connection_info().realip_remote_addr() {
+            return address.to_string();
+        }
+    }
+    peer
+}
+pub fn take_file_token(key: &str) -> bool {
+    let capacity = std::env::var("FILE_RATE_LIMIT")
+        .ok()
+        .and_then(|a| a.parse::<f64>().ok())
+        .unwrap_or(120.0);
+    let refill = capacity / 60.0;
+    let now = Instant::now();
+
+    let mut buckets = match FILE_BUCKETS.get_or_init(Default::default).lock() {
+        Ok(buckets) => buckets,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if buckets.len() > 10000 {
+        buckets.retain(|_, (tokens, time)| {
+            *tokens + now.duration_since(*time).as_secs_f64() * refill < capacity
+        });
+    }
+
+    let (tokens, time) = buckets.entry(key.to_string()).or_insert((capacity, now));
+    *tokens = (*tokens + now.duration_since(*time).as_secs_f64() * refill).min(capacity);
+    *time = now;
+    if *tokens >= 1.0 {
+        *tokens -= 1.0;
+        true
+    } else {
+        false
+    }
+}
+
+impl TimeoutMiddlewareFactory {
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+    pub fn from_env() -> Self {
+        let seconds = std::env::var("REQUEST_TIMEOUT")
+            .ok()
+            .and_then(|a| a.parse::<u64>().ok())
+            .unwrap_or(60);
+        Self::new(Duration::from_secs(seconds))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for TimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let srv: Rc<S> = self.service.clone();
+        let duration = self.duration;
+        let request = req.request().clone();
+
+        async move {
+            match timeout(duration, srv.call(req)).await {
+                Ok(res) => res.map(ServiceResponse::map_into_left_body),
+                Err(_) => Ok(ServiceResponse::new(
+                    request,
+                    HttpResponse::GatewayTimeout()
+                        .body("REQUEST_TIMEOUT".to_string())
+                        .map_into_right_body(),
+                )),
+            }
+        }
+        .boxed_local()
+    }
+}
+impl<S, B> Transform<S, ServiceRequest> for TimeoutMiddlewareFactory
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = TimeoutMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TimeoutMiddleware {
+            service: Rc::new(service),
+            duration: self.duration,
+        }))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let srv: Rc<S> = self.service.clone();
+
+        async move {
+            let base_path = std::env::var("BASE_PATH").unwrap_or_default();
+            let path = req.path().strip_prefix(&base_path).unwrap_or(req.path());
+
+            // Login and refresh stay open so an admin can still sign in to end maintenance
+            if is_maintenance()
+                && !matches!(
+                    path,
+                    "/health" | "/maintenance" | "/users/login" | "/users/refresh"
+                )
+            {
+                return Ok(req.into_response(
+                    HttpResponse::ServiceUnavailable()
+                        .body("MAINTENANCE".to_string())
+                        .map_into_right_body(),
+                ));
+            }
+
+            srv.call(req).await.map(ServiceResponse::map_into_left_body)
+        }
+        .boxed_local()
+    }
+}
+impl<S, B> Transform<S, ServiceRequest> for MaintenanceMiddlewareFactory
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = MaintenanceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
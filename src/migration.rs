@@ -0,0 +1,206 @@
+use crate::{database::get_db, models::normalize_text};
+
+use futures::stream::StreamExt;
+
+use mongodb::{
+    bson::{doc, Bson, Document},
+    options::{IndexOptions, UpdateOptions},
+    Database, IndexModel,
+};
+
+const LATEST_VERSION: i64 = 8;
+
+async fn find_version(database: &Database) -> Result<i64, String> {
+    let meta = database
+        .collection::<Document>("meta")
+        .find_one(doc! { "_id": "schema" }, None)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    Ok(meta
+        .and_then(|meta| meta.get_i64("version").ok())
+        .unwrap_or(0))
+}
+
+async fn update_version(database: &Database, version: i64) -> Result<(), String> {
+    database
+        .collection::<Document>("meta")
+        .update_one(
+            doc! { "_id": "schema" },
+            doc! { "$set": { "version": version } },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await
+        .map_err(|error| error.to_string())
+        .map(|_| ())
+}
+
+async fn backfill(
+    database: &Database,
+    collection: &str,
+    field: &str,
+    value: Bson,
+) -> Result<(), String> {
+    database
+        .collection::<Document>(collection)
+        .update_many(
+            doc! { field: { "$exists": false } },
+            doc! { "$set": { field: value } },
+            None,
+        )
+        .await
+        .map_err(|error| error.to_string())
+        .map(|_| ())
+}
+
+async fn normalize_names(database: &Database, collection: &str) -> Result<(), String> {
+    let collection = database.collection::<Document>(collection);
+    let mut cursor = collection
+        .find(doc! { "name_normalized": { "$exists": false } }, None)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    while let Some(Ok(document)) = cursor.next().await {
+        if let (Ok(_id), Ok(name)) = (document.get_object_id("_id"), document.get_str("name")) {
+            collection
+                .update_one(
+                    doc! { "_id": _id },
+                    doc! { "$set": { "name_normalized": normalize_text(name) } },
+                    None,
+                )
+                .await
+                .map_err(|error| error.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn grant_permission(
+    database: &Database,
+    permission: &[&str],
+    holder: &[&str],
+) -> Result<(), String> {
+    let filter = if holder.is_empty() {
+        doc! {}
+    } else {
+        doc! { "permission": { "$in": holder } }
+    };
+
+    database
+        .collection::<Document>("project-roles")
+        .update_many(
+            filter,
+            doc! { "$addToSet": { "permission": { "$each": permission } } },
+            None,
+        )
+        .await
+        .map_err(|error| error.to_string())
+        .map(|_| ())
+}
+
+async fn unique_project_code(database: &Database) -> Result<(), String> {
+    let collection = database.collection::<Document>("projects");
+    let mut cursor = collection
+        .aggregate(
+            vec![
+                doc! {
+                    "$group": {
+                        "_id": "$code",
+                        "count": { "$sum": 1 }
+                    }
+                },
+                doc! {
+                    "$match": {
+                        "count": { "$gt": 1 }
+                    }
+                },
+            ],
+            None,
+        )
+        .await
+        .map_err(|error| error.to_string())?;
+
+    let mut duplicate = Vec::<String>::new();
+    while let Some(Ok(document)) = cursor.next().await {
+        duplicate.push(document.get_str("_id").unwrap_or_default().to_string());
+    }
+    if !duplicate.is_empty() {
+        return Err(format!("DUPLICATE_PROJECT_CODE: {}", duplicate.join(", ")));
+    }
+
+    collection
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "code": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            None,
+        )
+        .await
+        .map_err(|error| error.to_string())
+        .map(|_| ())
+}
+
+async fn apply(database: &Database, version: i64) -> Result<(), String> {
+    match version {
+        1 => {
+            backfill(database, "projects", "baseline", Bson::Null).await?;
+            backfill(database, "projects", "import_hash", Bson::Null).await?;
+            backfill(database, "project-tasks", "cost", Bson::Null).await?;
+            backfill(database, "project-tasks", "checklist", Bson::Null).await?;
+            Ok(())
+        }
+        2 => {
+            normalize_names(database, "projects").await?;
+            normalize_names(database, "customers").await?;
+            Ok(())
+        }
+        3 => {
+            backfill(database, "projects", "budget", Bson::Null).await?;
+            backfill(database, "projects", "currency", Bson::Null).await?;
+            backfill(database, "projects", "spent", Bson::Null).await?;
+            Ok(())
+        }
+        4 => {
+            backfill(database, "project-tasks", "update_date", Bson::Null).await?;
+            Ok(())
+        }
+        5 => {
+            backfill(database, "project-reports", "update_date", Bson::Null).await?;
+            Ok(())
+        }
+        6 => {
+            grant_permission(
+                database,
+                &["get_report"],
+                &["get_task", "get_tasks", "create_report"],
+            )
+            .await?;
+            Ok(())
+        }
+        7 => {
+            grant_permission(database, &["get_project", "get_members"], &[]).await?;
+            Ok(())
+        }
+        8 => {
+            unique_project_code(database).await?;
+            Ok(())
+        }
+        _ => Err(format!("UNKNOWN_MIGRATION_{}", version)),
+    }
+}
+
+pub async fn run() -> Result<i64, String> {
+    let database = get_db();
+    let mut version = find_version(&database).await?;
+
+    while version < LATEST_VERSION {
+        apply(&database, version + 1).await?;
+        version += 1;
+        update_version(&database, version).await?;
+        println!("Applied database migration {}", version);
+    }
+
+    Ok(version)
+}
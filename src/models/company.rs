@@ -15,6 +15,7 @@ pub struct Company {
     pub field: String,
     pub contact: CompanyContact,
     pub image: Option<CompanyImage>,
+    pub images: Option<Vec<CompanyImage>>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CompanyContact {
@@ -43,6 +44,15 @@ pub struct CompanyImageMultipartRequest {
     #[multipart(rename = "file")]
     pub file: TempFile,
 }
+#[derive(Debug, MultipartForm)]
+pub struct CompanyImagesMultipartRequest {
+    #[multipart(rename = "file")]
+    pub files: Vec<TempFile>,
+}
+#[derive(Debug, Deserialize)]
+pub struct CompanyImageOrderRequest {
+    pub image_id: Vec<ObjectId>,
+}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CompanyResponse {
     pub _id: String,
@@ -50,6 +60,7 @@ pub struct CompanyResponse {
     pub field: String,
     pub contact: CompanyContactResponse,
     pub image: Option<CompanyImageResponse>,
+    pub images: Vec<CompanyImageResponse>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CompanyContactResponse {
@@ -123,6 +134,19 @@ impl Company {
                 to_bson::<Option<CompanyImageResponse>>(&None).unwrap()
               ]
             },
+            "images": {
+              "$map": {
+                "input": {
+                  "$ifNull": ["$images", []]
+                },
+                "in": {
+                  "_id": {
+                    "$toString": "$$this._id"
+                  },
+                  "extension": "$$this.extension"
+                }
+              }
+            },
           }
         }];
 
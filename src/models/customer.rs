@@ -7,15 +7,20 @@ use mongodb::{
 };
 use serde::{Deserialize, Serialize};
 
+use super::{is_valid_email, normalize_phone, normalize_text};
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Customer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub _id: Option<ObjectId>,
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_normalized: Option<String>,
     pub field: String,
     pub contact: CustomerContact,
     pub person: Vec<CustomerPerson>,
     pub image: Option<CustomerImage>,
+    pub images: Option<Vec<CustomerImage>>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CustomerContact {
@@ -33,6 +38,14 @@ pub struct CustomerPerson {
     pub role: String,
 }
 #[derive(Debug, Deserialize, Serialize)]
+pub struct CustomerPersonRequest {
+    pub name: String,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub role: String,
+}
+#[derive(Debug, Deserialize, Serialize)]
 pub struct CustomerImage {
     pub _id: ObjectId,
     pub extension: String,
@@ -60,6 +73,15 @@ pub struct CustomerImageMultipartRequest {
     #[multipart(rename = "file")]
     pub file: TempFile,
 }
+#[derive(Debug, MultipartForm)]
+pub struct CustomerImagesMultipartRequest {
+    #[multipart(rename = "file")]
+    pub files: Vec<TempFile>,
+}
+#[derive(Debug, Deserialize)]
+pub struct CustomerImageOrderRequest {
+    pub image_id: Vec<ObjectId>,
+}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CustomerResponse {
     pub _id: String,
@@ -68,6 +90,7 @@ pub struct CustomerResponse {
     pub contact: CustomerContact,
     pub person: Vec<CustomerPersonResponse>,
     pub image: Option<CustomerImageResponse>,
+    pub images: Vec<CustomerImageResponse>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CustomerPersonResponse {
@@ -84,12 +107,50 @@ pub struct CustomerImageResponse {
     pub extension: String,
 }
 
+impl CustomerContact {
+    pub fn validate(&mut self) -> Result<(), String> {
+        if let Some(email) = self.email.as_ref() {
+            if !is_valid_email(email) {
+                return Err("INVALID_EMAIL".to_string());
+            }
+            self.email = Some(email.trim().to_string());
+        }
+        if let Some(phone) = self.phone.as_ref() {
+            self.phone = Some(normalize_phone(phone).ok_or_else(|| "INVALID_PHONE".to_string())?);
+        }
+        Ok(())
+    }
+}
+
+impl CustomerPerson {
+    pub fn validate(&mut self) -> Result<(), String> {
+        if let Some(email) = self.email.as_ref() {
+            if !is_valid_email(email) {
+                return Err("INVALID_EMAIL".to_string());
+            }
+            self.email = Some(email.trim().to_string());
+        }
+        if let Some(phone) = self.phone.as_ref() {
+            self.phone = Some(normalize_phone(phone).ok_or_else(|| "INVALID_PHONE".to_string())?);
+        }
+        Ok(())
+    }
+}
+
 impl Customer {
+    pub fn validate(&mut self) -> Result<(), String> {
+        self.contact.validate()?;
+        for person in self.person.iter_mut() {
+            person.validate()?;
+        }
+        Ok(())
+    }
     pub async fn save(&mut self) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<Customer> = db.collection::<Customer>("customers");
 
         self._id = Some(ObjectId::new());
+        self.name_normalized = Some(normalize_text(&self.name));
 
         for person in self.person.iter_mut() {
             person._id = Some(ObjectId::new());
@@ -101,10 +162,12 @@ impl Customer {
             .map_err(|_| "INSERTING_FAILED".to_string())
             .map(|result| result.inserted_id.as_object_id().unwrap())
     }
-    pub async fn update(&self) -> Result<ObjectId, String> {
+    pub async fn update(&mut self) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<Customer> = db.collection::<Customer>("customers");
 
+        self.name_normalized = Some(normalize_text(&self.name));
+
         collection
             .update_one(
                 doc! { "_id": self._id.unwrap() },
@@ -132,6 +195,20 @@ impl Customer {
         let mut pipeline: Vec<mongodb::bson::Document> = Vec::new();
         let mut customers: Vec<CustomerResponse> = Vec::new();
 
+        if let Some(name) = &query.name {
+            pipeline.push(doc! {
+                "$match": {
+                    "$expr": {
+                        "$regexMatch": {
+                            "input": { "$ifNull": ["$name_normalized", "$name"] },
+                            "options": "i",
+                            "regex": normalize_text(name)
+                        }
+                    }
+                }
+            });
+        }
+
         if let Some(limit) = query.limit {
             pipeline.push(doc! {
               "$limit": to_bson::<usize>(&limit).unwrap()
@@ -173,6 +250,19 @@ impl Customer {
                     to_bson::<Option<CustomerImageResponse>>(&None).unwrap()
                 ]
             },
+            "images": {
+                "$map": {
+                    "input": {
+                        "$ifNull": ["$images", []]
+                    },
+                    "in": {
+                        "_id": {
+                            "$toString": "$$this._id"
+                        },
+                        "extension": "$$this.extension"
+                    }
+                }
+            },
           }
         });
 
@@ -1,9 +1,207 @@
 pub mod company;
 pub mod customer;
+pub mod notification;
 pub mod project;
 pub mod project_incident_report;
 pub mod project_progress_report;
 pub mod project_role;
 pub mod project_task;
+pub mod project_task_comment;
 pub mod role;
+pub mod tombstone;
 pub mod user;
+
+use chrono::{Local, Utc};
+use futures::stream::StreamExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    Collection,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, fs::canonicalize, path::PathBuf};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+const SYNC_SAFETY_WINDOW_MS: i64 = 5000;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    ProjectDocumentation,
+    CompanyImage,
+    CompanyGallery,
+    CustomerImage,
+    CustomerGallery,
+    UserImage,
+    TaskAttachment,
+}
+#[derive(Serialize)]
+pub struct PageResponse<T> {
+    pub limit: usize,
+    pub skip: usize,
+    pub data: Vec<T>,
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: u64,
+}
+
+#[derive(Serialize)]
+pub struct CursorPageResponse<T> {
+    pub limit: usize,
+    pub next_cursor: Option<String>,
+    pub data: Vec<T>,
+}
+
+impl FileKind {
+    pub fn root() -> String {
+        std::env::var("FILES_ROOT").unwrap()
+    }
+    pub fn dir(&self) -> &'static str {
+        match self {
+            FileKind::ProjectDocumentation => "reports/documentation",
+            FileKind::CompanyImage => "companies",
+            FileKind::CompanyGallery => "galleries/companies",
+            FileKind::CustomerImage => "customers",
+            FileKind::CustomerGallery => "galleries/customers",
+            FileKind::UserImage => "users",
+            FileKind::TaskAttachment => "tasks",
+        }
+    }
+    pub fn path(&self, name: impl Display) -> String {
+        format!(
+            "{}/{}/{}",
+            Self::root().trim_end_matches('/'),
+            self.dir(),
+            name
+        )
+    }
+    pub fn resolve(&self, name: &str) -> Option<PathBuf> {
+        let segments: Vec<&str> = name.split('/').collect();
+        if segments.len() != 2
+            || segments
+                .iter()
+                .any(|a| a.is_empty() || *a == "." || *a == ".." || a.contains('\\'))
+        {
+            return None;
+        }
+
+        let base = canonicalize(self.path("")).ok()?;
+        let path = canonicalize(self.path(name)).ok()?;
+        if path.starts_with(&base) {
+            Some(path)
+        } else {
+            None
+        }
+    }
+    pub fn is_public(&self) -> bool {
+        matches!(
+            self,
+            FileKind::CompanyImage | FileKind::CustomerImage | FileKind::UserImage
+        )
+    }
+}
+
+pub fn sync_timestamp() -> i64 {
+    // update_date is stamped by the app before the write lands, so a write stamped just before
+    // now may still commit after the read. Going back a few seconds returns it again next time.
+    Utc::now().timestamp_millis() - SYNC_SAFETY_WINDOW_MS
+}
+
+pub fn page_limit(limit: Option<usize>) -> usize {
+    let max = std::env::var("MAX_PAGE_SIZE")
+        .ok()
+        .and_then(|max| max.parse::<usize>().ok())
+        .unwrap_or(100)
+        .max(1);
+
+    limit.map_or(max, |limit| limit.clamp(1, max))
+}
+
+pub async fn count_matches<T>(
+    collection: &Collection<T>,
+    queries: Vec<Document>,
+) -> Result<u64, String> {
+    let pipeline: Vec<Document> = vec![
+        doc! {
+            "$match": {
+                "$expr": {
+                    "$and": queries
+                }
+            }
+        },
+        doc! {
+            "$count": "count"
+        },
+    ];
+
+    match collection.aggregate(pipeline, None).await {
+        Ok(mut cursor) => match cursor.next().await {
+            Some(Ok(doc)) => Ok(doc
+                .get_i32("count")
+                .map(|count| count as u64)
+                .or_else(|_| doc.get_i64("count").map(|count| count as u64))
+                .unwrap_or(0)),
+            _ => Ok(0),
+        },
+        Err(_) => Err("COUNT_FAILED".to_string()),
+    }
+}
+
+pub fn is_valid_email(email: &str) -> bool {
+    let re = Regex::new(r"^[^\s@]+@[^\s@]+\.[^\s@]+$").unwrap();
+    re.is_match(email.trim())
+}
+
+pub fn normalize_phone(phone: &str) -> Option<String> {
+    let phone: String = phone
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+    let digits = phone.strip_prefix('+').unwrap_or(&phone);
+
+    if (7..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit()) {
+        Some(phone)
+    } else {
+        None
+    }
+}
+
+pub fn normalize_text(text: &str) -> String {
+    text.trim()
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+pub fn status_to_string(status: &str) -> Document {
+    doc! {
+        "$map": {
+            "input": status,
+            "in": {
+                "kind": "$$this.kind",
+                "message": "$$this.message",
+                "time": date_to_string("$$this.time")
+            }
+        }
+    }
+}
+pub fn date_to_string(date: impl Into<Bson>) -> Document {
+    // Dates are rendered in the server offset, the same one progress is bucketed by
+    let offset = Local::now().format("%:z").to_string();
+    doc! {
+        "$concat": [
+            {
+                "$dateToString": {
+                    "date": date.into(),
+                    "format": "%Y-%m-%dT%H:%M:%S.%L",
+                    "timezone": &offset
+                }
+            },
+            &offset
+        ]
+    }
+}
@@ -0,0 +1,213 @@
+use crate::database::get_db;
+
+use futures::stream::StreamExt;
+use mongodb::{
+    bson::{doc, from_document, oid::ObjectId, to_bson, DateTime, Document},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+
+use super::date_to_string;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Mention,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Notification {
+    pub _id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub kind: NotificationKind,
+    pub project_id: ObjectId,
+    pub task_id: Option<ObjectId>,
+    pub comment_id: Option<ObjectId>,
+    pub read: bool,
+    pub create_date: DateTime,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotificationResponse {
+    pub _id: String,
+    pub kind: NotificationKind,
+    pub project: Option<NotificationProjectResponse>,
+    pub task: Option<NotificationTaskResponse>,
+    pub comment_id: Option<String>,
+    pub read: bool,
+    pub create_date: String,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotificationProjectResponse {
+    pub _id: String,
+    pub name: String,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotificationTaskResponse {
+    pub _id: String,
+    pub name: String,
+}
+#[derive(Debug)]
+pub struct NotificationQuery {
+    pub user_id: ObjectId,
+    pub read: Option<bool>,
+    pub limit: Option<usize>,
+}
+
+impl Notification {
+    pub async fn save_many(notifications: &mut [Notification]) -> Result<Vec<ObjectId>, String> {
+        let db: Database = get_db();
+        let collection: Collection<Notification> = db.collection::<Notification>("notifications");
+
+        if notifications.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        for notification in notifications.iter_mut() {
+            notification._id = Some(ObjectId::new());
+        }
+
+        collection
+            .insert_many(notifications.iter(), None)
+            .await
+            .map_err(|_| "INSERTING_FAILED".to_string())
+            .map(|_| notifications.iter().map(|a| a._id.unwrap()).collect())
+    }
+    pub async fn update_read(_id: &ObjectId, user_id: &ObjectId) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<Notification> = db.collection::<Notification>("notifications");
+
+        collection
+            .update_one(
+                doc! { "_id": _id, "user_id": user_id },
+                doc! { "$set": { "read": true } },
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())
+            .map(|result| result.matched_count)
+    }
+    pub async fn find_many(query: &NotificationQuery) -> Result<Vec<NotificationResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<Notification> = db.collection::<Notification>("notifications");
+
+        let mut queries: Vec<Document> = vec![doc! {
+            "$eq": ["$user_id", to_bson::<ObjectId>(&query.user_id).unwrap()]
+        }];
+
+        if let Some(read) = query.read {
+            queries.push(doc! {
+                "$eq": ["$read", read]
+            });
+        }
+
+        let mut pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$and": queries
+                    }
+                }
+            },
+            doc! {
+                "$sort": {
+                    "create_date": -1
+                }
+            },
+        ];
+
+        if let Some(limit) = query.limit {
+            pipeline.push(doc! {
+                "$limit": to_bson::<usize>(&limit).unwrap()
+            });
+        }
+
+        pipeline.push(doc! {
+            "$lookup": {
+                "from": "projects",
+                "as": "project",
+                "let": {
+                    "project_id": "$project_id"
+                },
+                "pipeline": [
+                    {
+                        "$match": {
+                            "$expr": {
+                                "$eq": ["$_id", "$$project_id"]
+                            }
+                        }
+                    },
+                    {
+                        "$project": {
+                            "_id": {
+                                "$toString": "$_id"
+                            },
+                            "name": "$name"
+                        }
+                    }
+                ]
+            }
+        });
+        pipeline.push(doc! {
+            "$lookup": {
+                "from": "project-tasks",
+                "as": "task",
+                "let": {
+                    "task_id": "$task_id"
+                },
+                "pipeline": [
+                    {
+                        "$match": {
+                            "$expr": {
+                                "$eq": ["$_id", "$$task_id"]
+                            }
+                        }
+                    },
+                    {
+                        "$project": {
+                            "_id": {
+                                "$toString": "$_id"
+                            },
+                            "name": "$name"
+                        }
+                    }
+                ]
+            }
+        });
+        pipeline.push(doc! {
+            "$project": {
+                "_id": {
+                    "$toString": "$_id"
+                },
+                "kind": "$kind",
+                "project": {
+                    "$first": "$project"
+                },
+                "task": {
+                    "$first": "$task"
+                },
+                "comment_id": {
+                    "$cond": [
+                        "$comment_id",
+                        {
+                            "$toString": "$comment_id"
+                        },
+                        to_bson::<Option<String>>(&None).unwrap()
+                    ]
+                },
+                "read": "$read",
+                "create_date": date_to_string("$create_date")
+            }
+        });
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut notifications = Vec::<NotificationResponse>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    notifications.push(from_document::<NotificationResponse>(doc).unwrap());
+                }
+                Ok(notifications)
+            }
+            Err(_) => Err("NOTIFICATION_NOT_FOUND".to_string()),
+        }
+    }
+}
@@ -1,24 +1,38 @@
-use crate::database::get_db;
+use crate::database::{get_client, get_db};
 
-use chrono::{FixedOffset, Local, NaiveDateTime, Utc};
-use futures::stream::StreamExt;
+use actix_multipart::form::{tempfile::TempFile, MultipartForm};
+use chrono::{Datelike, FixedOffset, Local, NaiveDateTime, Utc};
+use futures::stream::{self, StreamExt};
 use mongodb::{
-    bson::{doc, from_document, oid::ObjectId, to_bson, DateTime},
+    bson::{doc, from_bson, from_document, oid::ObjectId, to_bson, Bson, DateTime, Document},
+    error::{ErrorKind, WriteFailure},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
 
 use super::{
+    count_matches,
     customer::Customer,
-    project_incident_report::ProjectIncidentReportResponse,
+    date_to_string, normalize_text, page_limit,
+    project_incident_report::{ProjectIncidentReport, ProjectIncidentReportResponse},
     project_progress_report::{
-        ProjectProgressReport, ProjectProgressReportMinResponse, ProjectProgressReportQuery,
+        ProjectProgressReport, ProjectProgressReportActual, ProjectProgressReportMinResponse,
+        ProjectProgressReportQuery, ProjectProgressReportResponse,
     },
-    project_role::ProjectRoleResponse,
-    project_task::{ProjectTask, ProjectTaskMinResponse, ProjectTaskQuery, ProjectTaskQueryKind},
+    project_role::{ProjectRole, ProjectRolePermission, ProjectRoleResponse},
+    project_task::{
+        ProjectTask, ProjectTaskCost, ProjectTaskCostRequest, ProjectTaskMinResponse,
+        ProjectTaskPeriod, ProjectTaskPeriodRequest, ProjectTaskQuery, ProjectTaskQueryKind,
+        ProjectTaskTimelineQuery, ProjectTaskVolume,
+    },
+    status_to_string, sync_timestamp,
+    tombstone::{Tombstone, TombstoneKind},
     user::{User, UserImage},
 };
 
+const PROGRESS_CONCURRENCY: usize = 8;
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ProjectMemberKind {
@@ -59,6 +73,7 @@ pub enum ProjectQuerySortKind {
     Oldest,
     AZ,
     ZA,
+    Activity,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -67,13 +82,25 @@ pub struct Project {
     pub customer_id: ObjectId,
     pub user_id: ObjectId,
     pub name: String,
+    pub name_normalized: Option<String>,
     pub code: String,
     pub period: ProjectPeriod,
     pub status: Vec<ProjectStatus>,
     pub area: Option<Vec<ProjectArea>>,
     pub member: Option<Vec<ProjectMember>>,
     pub leave: Option<Vec<DateTime>>,
+    pub baseline: Option<Vec<ProjectBaseline>>,
+    pub import_hash: Option<String>,
+    pub budget: Option<f64>,
+    pub currency: Option<String>,
+    pub spent: Option<f64>,
     pub create_date: DateTime,
+    pub last_activity: Option<DateTime>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProjectBaseline {
+    pub task_id: ObjectId,
+    pub period: ProjectTaskPeriod,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectStatus {
@@ -88,6 +115,15 @@ pub struct ProjectMember {
     pub kind: ProjectMemberKind,
     pub role_id: Vec<ObjectId>,
 }
+#[derive(Debug, Deserialize)]
+pub struct ProjectMemberReassignRequest {
+    pub to_member_id: ObjectId,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectAreaMergeRequest {
+    pub source_area_id: ObjectId,
+    pub target_area_id: ObjectId,
+}
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectPeriod {
     pub start: DateTime,
@@ -97,6 +133,8 @@ pub struct ProjectPeriod {
 pub struct ProjectArea {
     pub _id: ObjectId,
     pub name: String,
+    pub description: Option<String>,
+    pub update_date: Option<DateTime>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -118,19 +156,153 @@ pub struct ProjectMinResponse {
     pub name: String,
     pub code: String,
     pub period: ProjectPeriodResponse,
-    pub status: Vec<ProjectStatus>,
+    pub status: Vec<ProjectStatusResponse>,
     pub progress: Option<ProjectProgressResponse>,
+    pub last_activity: Option<String>,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectSummaryResponse {
+    pub _id: String,
+    pub customer: ProjectCustomerResponse,
+    pub name: String,
+    pub code: String,
 }
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ProjectProgressResponse {
     pub plan: f64,
     pub actual: f64,
+    #[serde(default)]
+    pub has_schedule: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectCostResponse {
+    pub currency: String,
+    pub planned: f64,
+    pub earned: f64,
+    pub count: usize,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectBudgetResponse {
+    pub currency: String,
+    pub budget: Option<f64>,
+    pub earned: Option<f64>,
+    pub spent: Option<f64>,
+    pub progress: f64,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectEvmResponse {
+    pub currency: String,
+    pub budget: f64,
+    pub planned_value: f64,
+    pub earned_value: f64,
+    pub actual_cost: Option<f64>,
+    pub spi: Option<f64>,
+    pub cpi: Option<f64>,
+    pub skipped: usize,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectReadinessResponse {
+    pub ready: bool,
+    pub root_value_sum: f64,
+    pub tasks_without_period: Vec<ProjectReadinessItemResponse>,
+    pub areas_without_tasks: Vec<ProjectReadinessItemResponse>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectReadinessItemResponse {
+    pub _id: String,
+    pub name: String,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectValueSummaryResponse {
+    pub code: String,
+    pub root_sum: f64,
+    pub deficit: f64,
+    pub branch: Vec<ProjectValueBranchResponse>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectValueBranchResponse {
+    pub _id: String,
+    pub name: String,
+    pub sum: f64,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectProgressDeltaResponse {
+    pub from: ProjectProgressResponse,
+    pub to: ProjectProgressResponse,
+    pub plan: f64,
+    pub actual: f64,
+    pub task: Vec<ProjectProgressDeltaTaskResponse>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectProgressDeltaTaskResponse {
+    pub _id: String,
+    pub name: String,
+    pub value: f64,
 }
 #[derive(Debug, Serialize)]
 pub struct ProjectProgressGraphResponse {
     pub x: i64,
     pub y: Vec<f64>,
 }
+#[derive(Debug, Serialize)]
+pub struct ProjectMemberActivityResponse {
+    pub reports_authored: u64,
+    pub days_present: u64,
+    pub tasks_assigned: u64,
+    pub tasks_completed: u64,
+    pub incidents_involved: u64,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectChangeResponse {
+    pub timestamp: i64,
+    pub task: Vec<ProjectTaskMinResponse>,
+    pub report: Vec<ProjectProgressReportResponse>,
+    pub area: Vec<ProjectAreaResponse>,
+    pub deleted: Vec<ProjectChangeDeletedResponse>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectChangeDeletedResponse {
+    pub _id: String,
+    pub kind: TombstoneKind,
+    pub delete_date: i64,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectMemberBulkResponse {
+    pub added: Vec<String>,
+    pub error: Vec<ProjectMemberBulkErrorResponse>,
+}
+#[derive(Debug)]
+pub struct ProjectMemberBulkRow {
+    pub row: usize,
+    pub email: String,
+    pub role: Vec<String>,
+    pub kind: ProjectMemberKind,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectMemberBulkErrorResponse {
+    pub row: usize,
+    pub value: String,
+    pub error: String,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectPlanCoverageResponse {
+    pub date: String,
+    pub planned: usize,
+    pub reported: usize,
+    pub covered: usize,
+    pub coverage: Option<f64>,
+    pub missed: Vec<String>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectProgressWeeklyResponse {
+    pub year: i32,
+    pub week: u32,
+    pub start: i64,
+    pub progress: f64,
+    pub manpower: usize,
+    pub report: usize,
+    pub incident: usize,
+}
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ProjectCustomerResponse {
     pub _id: String,
@@ -146,6 +318,7 @@ pub struct ProjectCustomerImageResponse {
 pub struct ProjectAreaResponse {
     pub _id: String,
     pub name: String,
+    pub description: Option<String>,
     pub task: Option<Vec<ProjectTaskMinResponse>>,
 }
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -168,11 +341,31 @@ pub struct ProjectUserResponse {
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectReportResponse {
+    #[serde(default, skip_serializing)]
+    pub cursor_date: i64,
+    #[serde(default, skip_serializing)]
+    pub cursor_id: String,
     pub date: String,
     pub kind: ProjectReportKind,
     pub progress: Option<ProjectProgressReportMinResponse>,
     pub incident: Option<ProjectIncidentReportResponse>,
 }
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectReportListResponse {
+    pub _id: String,
+    pub kind: ProjectReportKind,
+    pub date: String,
+    pub author_name: Option<String>,
+    pub progress: Option<f64>,
+    #[serde(default, skip_serializing)]
+    pub actual: Vec<ProjectProgressReportActual>,
+}
+#[derive(Debug)]
+pub struct ProjectReportQuery {
+    pub area_id: Option<ObjectId>,
+    pub cursor: Option<String>,
+    pub limit: usize,
+}
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectStatusResponse {
     pub kind: ProjectStatusKind,
@@ -185,13 +378,48 @@ pub struct ProjectRequest {
     pub customer_id: ObjectId,
     pub user_id: Option<ObjectId>,
     pub name: String,
-    pub code: String,
+    pub code: Option<String>,
     pub period: ProjectPeriodRequest,
     pub leave: Option<Vec<DateTime>>,
+    pub status: Option<ProjectStatusKind>,
+    pub budget: Option<f64>,
+    pub currency: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectBudgetRequest {
+    pub budget: f64,
+    pub currency: Option<String>,
+    pub spent: Option<f64>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectFullRequest {
+    pub project: ProjectRequest,
+    pub area: Vec<ProjectFullAreaRequest>,
+    pub task: Vec<ProjectFullTaskRequest>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectFullAreaRequest {
+    pub key: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectFullTaskRequest {
+    pub key: String,
+    pub parent_key: Option<String>,
+    pub area_key: Option<String>,
+    pub user_id: Option<Vec<ObjectId>>,
+    pub name: String,
+    pub description: Option<String>,
+    pub volume: Option<ProjectTaskVolume>,
+    pub value: f64,
+    pub cost: Option<ProjectTaskCostRequest>,
+    pub period: Option<ProjectTaskPeriodRequest>,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectAreaRequest {
     pub name: String,
+    pub description: Option<String>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectMemberRequest {
@@ -200,6 +428,11 @@ pub struct ProjectMemberRequest {
     pub kind: ProjectMemberKind,
     pub role_id: Vec<ObjectId>,
 }
+#[derive(Debug, MultipartForm)]
+pub struct ProjectMemberMultipartRequest {
+    #[multipart(rename = "file")]
+    pub file: TempFile,
+}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectPeriodRequest {
     pub start: i64,
@@ -210,28 +443,277 @@ pub struct ProjectQuery {
     pub status: Option<ProjectQueryStatusKind>,
     pub sort: Option<ProjectQuerySortKind>,
     pub text: Option<String>,
+    pub end_before: Option<DateTime>,
     pub limit: Option<usize>,
     pub skip: Option<usize>,
 }
+pub struct ProjectMemberQuery {
+    pub kind: Option<ProjectMemberKind>,
+    pub role_id: Option<ObjectId>,
+}
+
+impl ProjectStatusKind {
+    pub fn can_transition_to(&self, next: &ProjectStatusKind) -> bool {
+        matches!(
+            (self, next),
+            (ProjectStatusKind::Pending, ProjectStatusKind::Running)
+                | (ProjectStatusKind::Paused, ProjectStatusKind::Running)
+                | (ProjectStatusKind::Breakdown, ProjectStatusKind::Running)
+                | (ProjectStatusKind::Running, ProjectStatusKind::Breakdown)
+                | (ProjectStatusKind::Running, ProjectStatusKind::Finished)
+        )
+    }
+}
+
+impl ProjectQuerySortKind {
+    pub fn default_sort() -> Self {
+        std::env::var("PROJECT_DEFAULT_SORT")
+            .ok()
+            .and_then(|sort| from_bson::<Self>(Bson::String(sort)).ok())
+            .unwrap_or(ProjectQuerySortKind::Latest)
+    }
+    pub fn stages(&self) -> Vec<Document> {
+        match self {
+            ProjectQuerySortKind::AZ | ProjectQuerySortKind::ZA => vec![
+                doc! {
+                    "$set": {
+                        "name_sort": {
+                            "$toLower": { "$ifNull": ["$name_normalized", "$name"] }
+                        }
+                    }
+                },
+                doc! {
+                    "$sort": {
+                        "name_sort": if self == &ProjectQuerySortKind::AZ { 1 } else { -1 },
+                        "_id": 1
+                    }
+                },
+            ],
+            ProjectQuerySortKind::Latest => vec![doc! {
+                "$sort": {
+                    "create_date": -1
+                }
+            }],
+            ProjectQuerySortKind::Oldest => vec![doc! {
+                "$sort": {
+                    "create_date": 1
+                }
+            }],
+            ProjectQuerySortKind::Activity => vec![doc! {
+                "$sort": {
+                    "last_activity": -1,
+                    "create_date": -1
+                }
+            }],
+        }
+    }
+}
+
+impl ProjectReportResponse {
+    pub fn cursor(&self) -> String {
+        format!("{}_{}", self.cursor_date, self.cursor_id)
+    }
+}
 
 impl Project {
+    pub fn current_status(&self) -> Result<&ProjectStatus, String> {
+        self.status
+            .first()
+            .ok_or_else(|| "MISSING_STATUS".to_string())
+    }
+    pub async fn generate_code(customer: &Customer) -> Result<String, String> {
+        let db: Database = get_db();
+        let collection: Collection<Document> = db.collection::<Document>("counters");
+
+        let mut prefix: String = customer
+            .name
+            .split_whitespace()
+            .filter_map(|a| a.chars().find(|b| b.is_ascii_alphanumeric()))
+            .take(4)
+            .collect::<String>()
+            .to_uppercase();
+        if prefix.is_empty() {
+            prefix = "PRJ".to_string();
+        }
+
+        let key = std::env::var("PROJECT_CODE_TEMPLATE")
+            .unwrap()
+            .replace("{customer_prefix}", &prefix)
+            .replace("{year}", &Local::now().year().to_string());
+
+        let counter = collection
+            .find_one_and_update(
+                doc! { "_id": &key },
+                doc! { "$inc": { "seq": 1_i64 } },
+                FindOneAndUpdateOptions::builder()
+                    .upsert(true)
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await
+            .map_err(|_| "PROJECT_CODE_GENERATION_FAILED".to_string())?
+            .ok_or_else(|| "PROJECT_CODE_GENERATION_FAILED".to_string())?;
+        let seq = counter
+            .get_i64("seq")
+            .map_err(|_| "PROJECT_CODE_GENERATION_FAILED".to_string())?;
+
+        Ok(key.replace("{seq}", &format!("{:03}", seq)))
+    }
     pub async fn save(&mut self) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
 
         self._id = Some(ObjectId::new());
+        self.code = self.code.trim().to_string();
+        self.name_normalized = Some(normalize_text(&self.name));
+
+        if let Ok(Some(customer)) = Customer::find_by_id(&self.customer_id).await {
+            if self.code.is_empty() {
+                self.code = Self::generate_code(&customer).await?;
+            }
+            if let Ok(Some(_)) = collection.find_one(doc! { "code": &self.code }, None).await {
+                return Err("PROJECT_CODE_EXISTS".to_string());
+            }
 
-        if let Ok(Some(_)) = Customer::find_by_id(&self.customer_id).await {
             collection
                 .insert_one(self, None)
                 .await
-                .map_err(|_| "INSERTING_FAILED".to_string())
+                .map_err(|error| match *error.kind {
+                    ErrorKind::Write(WriteFailure::WriteError(error)) if error.code == 11000 => {
+                        "PROJECT_CODE_EXISTS".to_string()
+                    }
+                    _ => "INSERTING_FAILED".to_string(),
+                })
                 .map(|result| result.inserted_id.as_object_id().unwrap())
         } else {
             Err("CUSTOMER_NOT_FOUND".to_string())
         }
     }
-    pub async fn calculate_progress(_id: &ObjectId) -> Result<ProjectProgressResponse, String> {
+    pub async fn save_full(
+        &mut self,
+        user_id: &ObjectId,
+        areas: Vec<ProjectArea>,
+        mut tasks: Vec<ProjectTask>,
+        running: bool,
+    ) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+        let role_collection: Collection<ProjectRole> =
+            db.collection::<ProjectRole>("project-roles");
+        let task_collection: Collection<ProjectTask> =
+            db.collection::<ProjectTask>("project-tasks");
+
+        let project_id = ObjectId::new();
+        let now = DateTime::from_millis(Utc::now().timestamp_millis());
+
+        self._id = Some(project_id);
+        self.code = self.code.trim().to_string();
+        self.name_normalized = Some(normalize_text(&self.name));
+
+        let customer = Customer::find_by_id(&self.customer_id)
+            .await?
+            .ok_or_else(|| "CUSTOMER_NOT_FOUND".to_string())?;
+        if self.code.is_empty() {
+            self.code = Self::generate_code(&customer).await?;
+        }
+        if let Ok(Some(_)) = collection.find_one(doc! { "code": &self.code }, None).await {
+            return Err("PROJECT_CODE_EXISTS".to_string());
+        }
+
+        let role = ProjectRole {
+            _id: Some(ObjectId::new()),
+            project_id,
+            name: "Owner".to_string(),
+            permission: vec![ProjectRolePermission::Owner],
+        };
+        self.member = Some(vec![ProjectMember {
+            _id: *user_id,
+            name: None,
+            kind: ProjectMemberKind::Indirect,
+            role_id: vec![role._id.unwrap()],
+        }]);
+        self.area = Some(
+            areas
+                .into_iter()
+                .map(|mut area| {
+                    area.update_date = Some(now);
+                    area
+                })
+                .collect(),
+        );
+        if running {
+            self.status.insert(
+                0,
+                ProjectStatus {
+                    kind: ProjectStatusKind::Running,
+                    time: now,
+                    message: None,
+                },
+            );
+        }
+        for task in tasks.iter_mut() {
+            task.project_id = project_id;
+            task.update_date = Some(now);
+        }
+
+        let mut session = get_client()
+            .start_session(None)
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+
+        if let Err(error) = collection
+            .insert_one_with_session(&*self, None, &mut session)
+            .await
+        {
+            let _ = session.abort_transaction().await;
+            return Err(match *error.kind {
+                ErrorKind::Write(WriteFailure::WriteError(error)) if error.code == 11000 => {
+                    "PROJECT_CODE_EXISTS".to_string()
+                }
+                _ => "INSERTING_FAILED".to_string(),
+            });
+        }
+        if role_collection
+            .insert_one_with_session(&role, None, &mut session)
+            .await
+            .is_err()
+        {
+            let _ = session.abort_transaction().await;
+            return Err("INSERTING_FAILED".to_string());
+        }
+        if !tasks.is_empty()
+            && task_collection
+                .insert_many_with_session(tasks, None, &mut session)
+                .await
+                .is_err()
+        {
+            let _ = session.abort_transaction().await;
+            return Err("INSERTING_FAILED".to_string());
+        }
+
+        session
+            .commit_transaction()
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+        ProjectTask::invalidate_dependencies(Some(&project_id));
+
+        Ok(project_id)
+    }
+    pub async fn calculate_progress_many(ids: &[ObjectId]) -> Vec<Option<ProjectProgressResponse>> {
+        stream::iter(ids)
+            .map(|_id| async move { Self::calculate_progress(_id, None).await.ok() })
+            .buffered(PROGRESS_CONCURRENCY)
+            .collect()
+            .await
+    }
+    pub async fn calculate_progress(
+        _id: &ObjectId,
+        as_of: Option<i64>,
+    ) -> Result<ProjectProgressResponse, String> {
         let mut bases: Vec<ProjectTask> = Vec::new();
         let mut dependencies: Vec<ProjectTask> = Vec::new();
         let mut progresses: Vec<ProjectProgressReport> = Vec::new();
@@ -248,16 +730,7 @@ impl Project {
         {
             bases = tasks;
         }
-        if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
-            _id: None,
-            project_id: Some(*_id),
-            task_id: None,
-            area_id: None,
-            limit: None,
-            kind: Some(ProjectTaskQueryKind::Dependency),
-        })
-        .await
-        {
+        if let Ok(tasks) = ProjectTask::find_dependencies(_id).await {
             dependencies = tasks;
         }
         if let Ok(Some(reports)) = ProjectProgressReport::find_many(ProjectProgressReportQuery {
@@ -271,26 +744,13 @@ impl Project {
 
         if !bases.is_empty() && !dependencies.is_empty() {
             for task in bases.iter_mut() {
-                let mut _id = task.task_id;
-                let mut found = true;
-                while found {
-                    if let Some(task_id) = _id {
-                        if let Some(index) =
-                            dependencies.iter().position(|a| a._id.unwrap() == task_id)
-                        {
-                            task.value *= dependencies[index].value / 100.0;
-                            _id = dependencies[index].task_id;
-                        }
-                    } else {
-                        found = false;
-                    }
-                }
+                task.value = ProjectTask::effective_value(task.value, task.task_id, &dependencies);
             }
         }
 
         let mut start_base = false;
         let mut start = 0;
-        let end = Utc::now().timestamp_millis();
+        let end = as_of.unwrap_or_else(|| Utc::now().timestamp_millis());
 
         if let Some(date) = bases
             .iter()
@@ -307,9 +767,14 @@ impl Project {
             }
         }
 
+        // Without scheduled base tasks the plan curve is flat at 0, so a 0/0 result
+        // means "not scheduled yet" rather than "on track at 0%".
+        let has_schedule = bases.iter().any(|a| a.period.is_some());
+
         let mut progress = ProjectProgressResponse {
             plan: 0.0,
             actual: 0.0,
+            has_schedule,
         };
         if start != 0 {
             let diff = (end - start) / 86400000 + 1;
@@ -330,10 +795,7 @@ impl Project {
                         }
                     })
                     .fold(prev_plan, |a, b| {
-                        let period = b.period.as_ref().unwrap();
-                        let start = period.start.timestamp_millis();
-                        let end = period.end.timestamp_millis();
-                        let diff = (end - start) / 86400000 + 1;
+                        let diff = b.period.as_ref().unwrap().duration_days();
                         a + (b.value / (diff as f64))
                     });
                 let mut actual = progresses
@@ -374,93 +836,407 @@ impl Project {
                 if actual >= 99.99 {
                     actual = 100.0
                 }
+                progress = ProjectProgressResponse {
+                    plan,
+                    actual,
+                    has_schedule,
+                };
+
                 if plan == 100.0 && actual == 100.0 {
                     break;
                 }
-
-                progress = ProjectProgressResponse { plan, actual };
             }
         }
 
         Ok(progress)
     }
-    pub async fn find_many(
-        query: &ProjectQuery,
-    ) -> Result<Option<Vec<ProjectMinResponse>>, String> {
-        let db: Database = get_db();
-        let collection: Collection<Project> = db.collection::<Project>("projects");
+    pub async fn calculate_progress_delta(
+        _id: &ObjectId,
+        from: i64,
+        to: i64,
+    ) -> Result<ProjectProgressDeltaResponse, String> {
+        let progress_from = Self::calculate_progress(_id, Some(from)).await?;
+        let progress_to = Self::calculate_progress(_id, Some(to)).await?;
 
-        let mut pipeline = Vec::<mongodb::bson::Document>::new();
-        let mut queries = Vec::<mongodb::bson::Document>::new();
-        let mut projects = Vec::<ProjectMinResponse>::new();
+        let mut task = Vec::<ProjectProgressDeltaTaskResponse>::new();
+        if let Ok(Some(reports)) = ProjectProgressReport::find_many(ProjectProgressReportQuery {
+            project_id: *_id,
+            area_id: None,
+        })
+        .await
+        {
+            let bases = ProjectTask::find_many(&ProjectTaskQuery {
+                _id: None,
+                project_id: Some(*_id),
+                task_id: None,
+                area_id: None,
+                limit: None,
+                kind: Some(ProjectTaskQueryKind::Base),
+            })
+            .await?
+            .unwrap_or_default();
 
-        if let Some(status) = &query.status {
-            if status == &ProjectQueryStatusKind::Paused {
-                queries.push(doc! {
-                    "$or": [
-                        {
-                            "$eq": [
-                                {
-                                    "$first": "$status.kind"
-                                },
-                                "pending"
-                            ]
-                        },
-                        {
-                            "$eq": [
-                                {
-                                    "$first": "$status.kind"
-                                },
-                                "paused"
-                            ]
-                        },
-                        {
-                            "$eq": [
-                                {
-                                    "$first": "$status.kind"
-                                },
-                                "breakdown"
-                            ]
-                        },
-                    ]
-                });
-            } else if status == &ProjectQueryStatusKind::Finished {
-                queries.push(doc! {
-                    "$eq": [
-                        {
-                            "$first": "$status.kind"
-                        },
-                        "finished"
-                    ]
-                });
-            } else if status == &ProjectQueryStatusKind::Cancelled {
-                queries.push(doc! {
-                    "$eq": [
-                        {
-                            "$first": "$status.kind"
-                        },
-                        "cancelled"
-                    ]
-                });
-            } else {
-                queries.push(doc! {
-                    "$eq": [
-                        {
-                            "$first": "$status.kind"
-                        },
-                        "running"
-                    ]
-                });
+            for actual in reports
+                .iter()
+                .filter(|a| a.date.timestamp_millis() > from && a.date.timestamp_millis() <= to)
+                .filter_map(|a| a.actual.as_ref())
+                .flatten()
+            {
+                let task_id = actual.task_id.to_string();
+                if let Some(task) = task.iter_mut().find(|a| a._id == task_id) {
+                    task.value += actual.value;
+                } else if let Some(base) = bases.iter().find(|a| a._id == Some(actual.task_id)) {
+                    task.push(ProjectProgressDeltaTaskResponse {
+                        _id: task_id,
+                        name: base.name.clone(),
+                        value: actual.value,
+                    });
+                }
             }
         }
-        if let Some(text) = &query.text {
-            queries.push(doc! {
-                "$or": [
-                    {
-                        "$regexMatch": {
-                            "input": "$name",
+        task.retain(|a| a.value > 0.0);
+
+        Ok(ProjectProgressDeltaResponse {
+            plan: progress_to.plan - progress_from.plan,
+            actual: progress_to.actual - progress_from.actual,
+            from: progress_from,
+            to: progress_to,
+            task,
+        })
+    }
+    pub async fn calculate_cost(_id: &ObjectId) -> Result<Vec<ProjectCostResponse>, String> {
+        let mut tasks: Vec<ProjectTask> = Vec::new();
+        let mut progresses: Vec<ProjectProgressReport> = Vec::new();
+
+        if let Ok(Some(result)) = ProjectTask::find_many(&ProjectTaskQuery {
+            _id: None,
+            project_id: Some(*_id),
+            task_id: None,
+            area_id: None,
+            limit: None,
+            kind: None,
+        })
+        .await
+        {
+            tasks = result;
+        }
+        if let Ok(Some(reports)) = ProjectProgressReport::find_many(ProjectProgressReportQuery {
+            project_id: *_id,
+            area_id: None,
+        })
+        .await
+        {
+            progresses = reports;
+        }
+
+        let ancestors = |task: &ProjectTask| {
+            let mut ancestors = Vec::<ObjectId>::new();
+            let mut task_id = task.task_id;
+            while let Some(_id) = task_id {
+                if ancestors.contains(&_id) {
+                    break;
+                }
+                ancestors.push(_id);
+                task_id = tasks
+                    .iter()
+                    .find(|a| a._id == Some(_id))
+                    .and_then(|a| a.task_id);
+            }
+            ancestors
+        };
+        let bases: Vec<(&ProjectTask, f64, f64)> = tasks
+            .iter()
+            .filter(|a| !tasks.iter().any(|b| b.task_id == a._id))
+            .map(|a| {
+                let progress = progresses
+                    .iter()
+                    .filter_map(|b| b.actual.as_ref())
+                    .flatten()
+                    .filter(|b| Some(b.task_id) == a._id)
+                    .fold(0.0, |c, d| c + d.value)
+                    .min(100.0);
+                (
+                    a,
+                    ProjectTask::effective_value(a.value, a.task_id, &tasks),
+                    progress,
+                )
+            })
+            .collect();
+
+        let mut costs = vec![ProjectCostResponse {
+            currency: ProjectTaskCost::base_currency(),
+            planned: 0.0,
+            earned: 0.0,
+            count: 0,
+        }];
+        for task in tasks.iter() {
+            let cost = match &task.cost {
+                Some(cost) => cost,
+                None => continue,
+            };
+            // A cost on a parent task covers its whole subtree, so nested costs are skipped.
+            if ancestors(task)
+                .iter()
+                .any(|a| tasks.iter().any(|b| b._id == Some(*a) && b.cost.is_some()))
+            {
+                continue;
+            }
+
+            let value = ProjectTask::effective_value(task.value, task.task_id, &tasks);
+            let progress = if value > 0.0 {
+                bases
+                    .iter()
+                    .filter(|(a, _, _)| {
+                        a._id == task._id || ancestors(a).contains(&task._id.unwrap())
+                    })
+                    .fold(0.0, |a, (_, b, c)| a + c * b / value)
+            } else {
+                0.0
+            };
+
+            let index = match costs.iter().position(|a| a.currency == cost.currency) {
+                Some(index) => index,
+                None => {
+                    costs.push(ProjectCostResponse {
+                        currency: cost.currency.clone(),
+                        planned: 0.0,
+                        earned: 0.0,
+                        count: 0,
+                    });
+                    costs.len() - 1
+                }
+            };
+            costs[index].planned += cost.amount;
+            costs[index].earned += cost.amount * progress.min(100.0) / 100.0;
+            costs[index].count += 1;
+        }
+
+        Ok(costs)
+    }
+    pub async fn calculate_evm(
+        _id: &ObjectId,
+        actual_cost: Option<f64>,
+    ) -> Result<ProjectEvmResponse, String> {
+        let progress = Self::calculate_progress(_id, None).await?;
+        let cost = Self::calculate_cost(_id).await?;
+        let currency = ProjectTaskCost::base_currency();
+        let budget = cost
+            .iter()
+            .find(|a| a.currency == currency)
+            .map_or(0.0, |a| a.planned);
+        // Costs in other currencies are not converted, so they are left out of the budget
+        let skipped = cost
+            .iter()
+            .filter(|a| a.currency != currency)
+            .fold(0, |a, b| a + b.count);
+
+        let planned_value = budget * progress.plan / 100.0;
+        let earned_value = budget * progress.actual / 100.0;
+
+        Ok(ProjectEvmResponse {
+            currency,
+            budget,
+            planned_value,
+            earned_value,
+            actual_cost,
+            spi: if planned_value > 0.0 {
+                Some(earned_value / planned_value)
+            } else {
+                None
+            },
+            cpi: match actual_cost {
+                Some(actual_cost) if actual_cost > 0.0 => Some(earned_value / actual_cost),
+                _ => None,
+            },
+            skipped,
+        })
+    }
+    pub async fn calculate_budget(&self) -> Result<ProjectBudgetResponse, String> {
+        let progress = Self::calculate_progress(&self._id.unwrap(), None).await?;
+
+        Ok(ProjectBudgetResponse {
+            currency: self
+                .currency
+                .clone()
+                .unwrap_or_else(ProjectTaskCost::base_currency),
+            budget: self.budget,
+            earned: self.budget.map(|budget| budget * progress.actual / 100.0),
+            spent: self.spent,
+            progress: progress.actual,
+        })
+    }
+    pub async fn calculate_readiness(&self) -> Result<ProjectReadinessResponse, String> {
+        let tasks = ProjectTask::find_many(&ProjectTaskQuery {
+            _id: None,
+            project_id: self._id,
+            task_id: None,
+            area_id: None,
+            limit: None,
+            kind: None,
+        })
+        .await?
+        .unwrap_or_default();
+
+        let root_value_sum = Self::root_value_sum(&tasks);
+        let tasks_without_period = tasks
+            .iter()
+            .filter(|a| a.period.is_none() && !tasks.iter().any(|b| b.task_id == a._id))
+            .map(|a| ProjectReadinessItemResponse {
+                _id: a._id.unwrap().to_string(),
+                name: a.name.clone(),
+            })
+            .collect::<Vec<ProjectReadinessItemResponse>>();
+        let areas_without_tasks = self
+            .area
+            .iter()
+            .flatten()
+            .filter(|a| !tasks.iter().any(|b| b.area_id == a._id))
+            .map(|a| ProjectReadinessItemResponse {
+                _id: a._id.to_string(),
+                name: a.name.clone(),
+            })
+            .collect::<Vec<ProjectReadinessItemResponse>>();
+
+        // Mirrors what update_status enforces when the project is moved to running
+        let ready = self
+            .current_status()?
+            .kind
+            .can_transition_to(&ProjectStatusKind::Running)
+            && ProjectTask::is_value_complete(root_value_sum);
+
+        Ok(ProjectReadinessResponse {
+            ready,
+            root_value_sum,
+            tasks_without_period,
+            areas_without_tasks,
+        })
+    }
+    pub async fn calculate_value_summary(&self) -> Result<ProjectValueSummaryResponse, String> {
+        let tasks = ProjectTask::find_many(&ProjectTaskQuery {
+            _id: None,
+            project_id: self._id,
+            task_id: None,
+            area_id: None,
+            limit: None,
+            kind: None,
+        })
+        .await?
+        .unwrap_or_default();
+
+        let root_sum = Self::root_value_sum(&tasks);
+        let branch = tasks
+            .iter()
+            .filter_map(|a| {
+                let children = tasks.iter().filter(|b| b.task_id == a._id);
+                let count = children.clone().count();
+                let sum: f64 = children.map(|b| b.value).sum();
+                if count > 0 && !ProjectTask::is_value_complete(sum) {
+                    Some(ProjectValueBranchResponse {
+                        _id: a._id.unwrap().to_string(),
+                        name: a.name.clone(),
+                        sum,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<ProjectValueBranchResponse>>();
+
+        Ok(ProjectValueSummaryResponse {
+            code: "PROJECT_TASK_VALUE_SUM_MUST_BE_100".to_string(),
+            root_sum,
+            deficit: 100.0 - root_sum,
+            branch,
+        })
+    }
+    pub async fn find_empty_areas(&self) -> Result<Vec<ProjectArea>, String> {
+        let area_id = ProjectTask::find_many_area_id(&self._id.unwrap()).await?;
+
+        Ok(self
+            .area
+            .iter()
+            .flatten()
+            .filter(|a| !area_id.contains(&a._id))
+            .cloned()
+            .collect())
+    }
+    pub fn root_value_sum(tasks: &[ProjectTask]) -> f64 {
+        tasks
+            .iter()
+            .filter(|a| a.task_id.is_none())
+            .map(|a| a.value)
+            .sum()
+    }
+    fn find_many_queries(query: &ProjectQuery) -> Vec<Document> {
+        let mut queries = Vec::<Document>::new();
+
+        if let Some(status) = &query.status {
+            if status == &ProjectQueryStatusKind::Paused {
+                queries.push(doc! {
+                    "$or": [
+                        {
+                            "$eq": [
+                                {
+                                    "$first": "$status.kind"
+                                },
+                                "pending"
+                            ]
+                        },
+                        {
+                            "$eq": [
+                                {
+                                    "$first": "$status.kind"
+                                },
+                                "paused"
+                            ]
+                        },
+                        {
+                            "$eq": [
+                                {
+                                    "$first": "$status.kind"
+                                },
+                                "breakdown"
+                            ]
+                        },
+                    ]
+                });
+            } else if status == &ProjectQueryStatusKind::Finished {
+                queries.push(doc! {
+                    "$eq": [
+                        {
+                            "$first": "$status.kind"
+                        },
+                        "finished"
+                    ]
+                });
+            } else if status == &ProjectQueryStatusKind::Cancelled {
+                queries.push(doc! {
+                    "$eq": [
+                        {
+                            "$first": "$status.kind"
+                        },
+                        "cancelled"
+                    ]
+                });
+            } else {
+                queries.push(doc! {
+                    "$eq": [
+                        {
+                            "$first": "$status.kind"
+                        },
+                        "running"
+                    ]
+                });
+            }
+        }
+        if let Some(text) = &query.text {
+            queries.push(doc! {
+                "$or": [
+                    {
+                        "$regexMatch": {
+                            "input": { "$ifNull": ["$name_normalized", "$name"] },
                             "options": "i",
-                            "regex": to_bson::<String>(text).unwrap()
+                            "regex": normalize_text(text)
                         }
                     },
                     {
@@ -473,6 +1249,202 @@ impl Project {
                 ]
             });
         }
+        if let Some(end_before) = query.end_before {
+            queries.push(doc! {
+                "$not": [
+                    {
+                        "$in": [
+                            {
+                                "$first": "$status.kind"
+                            },
+                            ["finished", "cancelled"]
+                        ]
+                    }
+                ]
+            });
+            queries.push(doc! {
+                "$gte": ["$period.end", DateTime::now()]
+            });
+            queries.push(doc! {
+                "$lte": ["$period.end", end_before]
+            });
+        }
+
+        queries
+    }
+    pub async fn count(query: &ProjectQuery) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        count_matches(&collection, Self::find_many_queries(query)).await
+    }
+    pub async fn find_changes(&self, since: &DateTime) -> Result<ProjectChangeResponse, String> {
+        // Taken before querying so nothing written in between is missed on the next sync
+        let timestamp = sync_timestamp();
+        let project_id = self._id.unwrap();
+
+        let task = ProjectTask::find_many_timeline(&ProjectTaskTimelineQuery {
+            project_id,
+            area_id: None,
+            task_id: None,
+            status: None,
+            relative: true,
+            subtask: false,
+            kind: None,
+            user_id: None,
+            since: Some(*since),
+        })
+        .await?
+        .unwrap_or_default();
+        let report = ProjectProgressReport::find_many_updated(&project_id, since).await?;
+        let area = self
+            .area
+            .iter()
+            .flatten()
+            .filter(|a| a.update_date.is_some_and(|date| date > *since))
+            .map(|a| ProjectAreaResponse {
+                _id: a._id.to_string(),
+                name: a.name.clone(),
+                description: a.description.clone(),
+                task: None,
+            })
+            .collect();
+        let deleted = Tombstone::find_many(&project_id, None, since)
+            .await?
+            .into_iter()
+            .map(|a| ProjectChangeDeletedResponse {
+                _id: a.entity_id.to_string(),
+                kind: a.kind,
+                delete_date: a.delete_date.timestamp_millis(),
+            })
+            .collect();
+
+        Ok(ProjectChangeResponse {
+            timestamp,
+            task,
+            report,
+            area,
+            deleted,
+        })
+    }
+    pub async fn calculate_member_activity(
+        &self,
+        member_id: &ObjectId,
+    ) -> Result<ProjectMemberActivityResponse, String> {
+        let db: Database = get_db();
+        let reports = db.collection::<ProjectProgressReport>("project-reports");
+        let tasks = db.collection::<ProjectTask>("project-tasks");
+        let incidents = db.collection::<ProjectIncidentReport>("project-incidents");
+
+        let project_id = doc! {
+            "$eq": ["$project_id", to_bson::<Option<ObjectId>>(&self._id).unwrap()]
+        };
+        let member_of = |field: &str| {
+            doc! {
+                "$in": [member_id, { "$ifNull": [field, []] }]
+            }
+        };
+
+        let reports_authored = count_matches(
+            &reports,
+            vec![project_id.clone(), doc! { "$eq": ["$user_id", member_id] }],
+        )
+        .await?;
+        let tasks_assigned =
+            count_matches(&tasks, vec![project_id.clone(), member_of("$user_id")]).await?;
+        let tasks_completed = count_matches(
+            &tasks,
+            vec![
+                project_id.clone(),
+                member_of("$user_id"),
+                doc! { "$eq": [{ "$first": "$status.kind" }, "finished"] },
+            ],
+        )
+        .await?;
+        let incidents_involved = count_matches(
+            &incidents,
+            vec![
+                project_id.clone(),
+                doc! {
+                    "$or": [
+                        { "$eq": ["$user_id", member_id] },
+                        member_of("$member_id")
+                    ]
+                },
+            ],
+        )
+        .await?;
+
+        let offset = Local::now().format("%:z").to_string();
+        let pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$and": [project_id, member_of("$member_id")]
+                    }
+                }
+            },
+            doc! {
+                "$group": {
+                    "_id": {
+                        "$dateToString": {
+                            "date": "$date",
+                            "format": "%Y-%m-%d",
+                            "timezone": &offset
+                        }
+                    }
+                }
+            },
+            doc! {
+                "$count": "count"
+            },
+        ];
+        let days_present = match reports.aggregate(pipeline, None).await {
+            Ok(mut cursor) => match cursor.next().await {
+                Some(Ok(doc)) => doc.get_i32("count").map_or(0, |count| count as u64),
+                _ => 0,
+            },
+            Err(_) => return Err("PROJECT_REPORT_NOT_FOUND".to_string()),
+        };
+
+        Ok(ProjectMemberActivityResponse {
+            reports_authored,
+            days_present,
+            tasks_assigned,
+            tasks_completed,
+            incidents_involved,
+        })
+    }
+    pub async fn count_reports(_id: &ObjectId) -> Result<u64, String> {
+        let db: Database = get_db();
+        let queries = || {
+            vec![doc! {
+                "$eq": ["$project_id", to_bson::<ObjectId>(_id).unwrap()]
+            }]
+        };
+
+        let progress = count_matches(
+            &db.collection::<ProjectProgressReport>("project-reports"),
+            queries(),
+        )
+        .await?;
+        let incident = count_matches(
+            &db.collection::<ProjectIncidentReport>("project-incidents"),
+            queries(),
+        )
+        .await?;
+
+        Ok(progress + incident)
+    }
+    pub async fn find_many(
+        query: &ProjectQuery,
+    ) -> Result<Option<Vec<ProjectMinResponse>>, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        let mut pipeline = Vec::<mongodb::bson::Document>::new();
+        let queries = Self::find_many_queries(query);
+        let mut projects = Vec::<ProjectMinResponse>::new();
 
         pipeline.push(doc! {
             "$match": {
@@ -482,32 +1454,20 @@ impl Project {
             }
         });
 
-        if let Some(sort) = &query.sort {
-            if sort == &ProjectQuerySortKind::AZ {
-                pipeline.push(doc! {
-                    "$sort": {
-                        "name": 1
-                    }
-                });
-            } else if sort == &ProjectQuerySortKind::ZA {
-                pipeline.push(doc! {
-                    "$sort": {
-                        "name": -1
-                    }
-                });
-            } else if sort == &ProjectQuerySortKind::Latest {
-                pipeline.push(doc! {
-                    "$sort": {
-                        "create_date": -1
-                    }
-                });
-            } else if sort == &ProjectQuerySortKind::Oldest {
-                pipeline.push(doc! {
-                    "$sort": {
-                        "create_date": 1
-                    }
-                });
-            }
+        if query.sort.is_none() && query.end_before.is_some() {
+            pipeline.push(doc! {
+                "$sort": {
+                    "period.end": 1
+                }
+            });
+        }
+        let sort = match (&query.sort, query.end_before) {
+            (Some(sort), _) => Some(sort.clone()),
+            (None, None) => Some(ProjectQuerySortKind::default_sort()),
+            (None, Some(_)) => None,
+        };
+        if let Some(sort) = &sort {
+            pipeline.extend(sort.stages());
         }
 
         pipeline.push(doc! {
@@ -569,12 +1529,19 @@ impl Project {
                 },
                 "name": "$name",
                 "code": "$code",
-                "status": "$status",
+                "status": status_to_string("$status"),
                 "period": {
-                    "start": { "$toString": "$period.start" },
-                    "end": { "$toString": "$period.end" },
+                    "start": date_to_string("$period.start"),
+                    "end": date_to_string("$period.end"),
                 },
-                "progress": to_bson::<Option<ProjectProgressResponse>>(&None).unwrap()
+                "progress": to_bson::<Option<ProjectProgressResponse>>(&None).unwrap(),
+                "last_activity": {
+                    "$cond": [
+                        "$last_activity",
+                        date_to_string("$last_activity"),
+                        to_bson::<Option<String>>(&None).unwrap()
+                    ]
+                }
             }
         });
 
@@ -583,42 +1550,54 @@ impl Project {
                 "$skip": to_bson::<usize>(&skip).unwrap()
             });
         }
-        if let Some(limit) = query.limit {
-            pipeline.push(doc! {
-                "$limit": to_bson::<usize>(&limit).unwrap()
-            });
-        }
+        pipeline.push(doc! {
+            "$limit": to_bson::<usize>(&page_limit(query.limit)).unwrap()
+        });
 
         if let Ok(mut cursor) = collection.aggregate(pipeline, None).await {
+            let mut candidates = Vec::<ProjectMinResponse>::new();
             while let Some(Ok(doc)) = cursor.next().await {
-                let mut project: ProjectMinResponse =
-                    from_document::<ProjectMinResponse>(doc).unwrap();
+                candidates.push(from_document::<ProjectMinResponse>(doc).unwrap());
+            }
 
-                if project.status.first().unwrap().kind == ProjectStatusKind::Pending {
+            let running = candidates
+                .iter()
+                .filter(|project| {
+                    !matches!(
+                        project.status.first().map(|a| &a.kind),
+                        Some(ProjectStatusKind::Pending) | Some(ProjectStatusKind::Finished)
+                    )
+                })
+                .map(|project| project._id.parse::<ObjectId>().unwrap())
+                .collect::<Vec<ObjectId>>();
+            let mut progresses = Self::calculate_progress_many(&running).await.into_iter();
+
+            for mut project in candidates {
+                let status = project.status.first().map(|a| a.kind.clone());
+                if status == Some(ProjectStatusKind::Pending) {
                     project.progress = Some(ProjectProgressResponse {
                         plan: 0.0,
                         actual: 0.0,
+                        has_schedule: false,
                     });
-                } else if project.status.first().unwrap().kind == ProjectStatusKind::Finished {
+                } else if status == Some(ProjectStatusKind::Finished) {
                     project.progress = Some(ProjectProgressResponse {
                         plan: 100.0,
                         actual: 100.0,
+                        has_schedule: true,
                     });
                 } else {
-                    project.progress =
-                        Self::calculate_progress(&project._id.parse::<ObjectId>().unwrap())
-                            .await
-                            .map_or_else(|_| None, Some);
+                    project.progress = progresses.next().flatten();
                 }
 
                 if let Some(progress) = &project.progress {
                     if let Some(status) = &query.status {
                         if status == &ProjectQueryStatusKind::Ahead {
-                            if progress.actual >= progress.plan {
+                            if progress.has_schedule && progress.actual >= progress.plan {
                                 projects.push(project);
                             }
                         } else if status == &ProjectQueryStatusKind::Behind {
-                            if progress.actual < progress.plan {
+                            if progress.has_schedule && progress.actual < progress.plan {
                                 projects.push(project);
                             }
                         } else {
@@ -638,6 +1617,65 @@ impl Project {
             Ok(None)
         }
     }
+    pub async fn find_many_summary() -> Result<Vec<ProjectSummaryResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        let pipeline: Vec<mongodb::bson::Document> = vec![
+            doc! {
+                "$sort": {
+                    "name": 1
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "customers",
+                    "let": {
+                        "customer_id": "$customer_id"
+                    },
+                    "as": "customer",
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$eq": ["$_id", "$$customer_id"]
+                                }
+                            }
+                        }
+                    ]
+                }
+            },
+            doc! {
+                "$project": {
+                    "_id": {
+                        "$toString": "$_id"
+                    },
+                    "customer": {
+                        "_id": {
+                            "$toString": "$customer_id"
+                        },
+                        "name": {
+                            "$first": "$customer.name"
+                        },
+                        "image": to_bson::<Option<ProjectCustomerImageResponse>>(&None).unwrap()
+                    },
+                    "name": "$name",
+                    "code": "$code"
+                }
+            },
+        ];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut projects = Vec::<ProjectSummaryResponse>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    projects.push(from_document::<ProjectSummaryResponse>(doc).unwrap());
+                }
+                Ok(projects)
+            }
+            Err(_) => Err("PROJECT_NOT_FOUND".to_string()),
+        }
+    }
     pub async fn find_by_id(_id: &ObjectId) -> Result<Option<Project>, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
@@ -647,6 +1685,51 @@ impl Project {
             .await
             .map_err(|_| "PROJECT_NOT_FOUND".to_string())
     }
+    pub async fn find_many_by_status(kind: &ProjectStatusKind) -> Result<Vec<Project>, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        let pipeline = vec![doc! {
+            "$match": {
+                "$expr": {
+                    "$eq": [{ "$first": "$status.kind" }, to_bson::<ProjectStatusKind>(kind).unwrap()]
+                }
+            }
+        }];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut projects = Vec::<Project>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    projects.push(from_document::<Project>(doc).unwrap());
+                }
+                Ok(projects)
+            }
+            Err(_) => Err("PROJECT_NOT_FOUND".to_string()),
+        }
+    }
+    pub async fn find_owners(&self) -> Vec<User> {
+        let mut owners = Vec::<User>::new();
+
+        for member in self.member.iter().flatten() {
+            let mut owner = false;
+            for id in &member.role_id {
+                if let Ok(Some(role)) = ProjectRole::find_by_id(id).await {
+                    if role.permission.contains(&ProjectRolePermission::Owner) {
+                        owner = true;
+                        break;
+                    }
+                }
+            }
+            if owner {
+                if let Ok(Some(user)) = User::find_by_id(&member._id).await {
+                    owners.push(user);
+                }
+            }
+        }
+
+        owners
+    }
     pub async fn find_detail_by_id(_id: &ObjectId) -> Result<Option<ProjectResponse>, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
@@ -763,19 +1846,10 @@ impl Project {
                     "name": "$name",
                     "code": "$code",
                     "period": {
-                        "start": { "$toString": "$period.start" },
-                        "end": { "$toString": "$period.end" },
-                    },
-                    "status": {
-                        "$map": {
-                            "input": "$status",
-                            "in": {
-                                "kind": "$$this.kind",
-                                "message": "$$this.message",
-                                "time": { "$toString": "$$this.time" }
-                            }
-                        }
+                        "start": date_to_string("$period.start"),
+                        "end": date_to_string("$period.end"),
                     },
+                    "status": status_to_string("$status"),
                     "area": {
                         "$map": {
                             "input": "$area",
@@ -783,7 +1857,8 @@ impl Project {
                                 "_id": {
                                     "$toString": "$$this._id"
                                 },
-                                "name": "$$this.name"
+                                "name": "$$this.name",
+                                "description": "$$this.description"
                             }
                         }
                     },
@@ -804,10 +1879,25 @@ impl Project {
             Err(_) => Err("PROJECT_NOT_FOUND".to_string()),
         }
     }
-    pub async fn find_users(_id: &ObjectId) -> Result<Option<ProjectUserResponse>, String> {
+    pub async fn find_users(
+        _id: &ObjectId,
+        query: &ProjectMemberQuery,
+    ) -> Result<Option<ProjectUserResponse>, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
 
+        let mut queries: Vec<Document> = Vec::<Document>::new();
+        if let Some(kind) = &query.kind {
+            queries.push(doc! {
+                "$eq": ["$$this.kind", to_bson::<ProjectMemberKind>(kind).unwrap()]
+            });
+        }
+        if let Some(role_id) = &query.role_id {
+            queries.push(doc! {
+                "$in": [to_bson::<ObjectId>(role_id).unwrap(), "$$this.role_id"]
+            });
+        }
+
         let pipeline: Vec<mongodb::bson::Document> = vec![
             doc! {
                 "$match": {
@@ -816,6 +1906,18 @@ impl Project {
                     }
                 }
             },
+            doc! {
+                "$set": {
+                    "member": {
+                        "$filter": {
+                            "input": { "$ifNull": ["$member", []] },
+                            "cond": {
+                                "$and": queries
+                            }
+                        }
+                    }
+                }
+            },
             doc! {
                 "$lookup": {
                     "from": "users",
@@ -1022,6 +2124,7 @@ impl Project {
     }
     pub async fn find_reports(
         _id: &ObjectId,
+        query: &ProjectReportQuery,
     ) -> Result<Option<Vec<ProjectReportResponse>>, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
@@ -1030,16 +2133,40 @@ impl Project {
         let mut reports = Vec::<ProjectReportResponse>::new();
         let mut dependencies: Vec<ProjectTask> = Vec::new();
 
-        if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
-            _id: None,
-            project_id: Some(*_id),
-            task_id: None,
-            area_id: None,
-            limit: None,
-            kind: Some(ProjectTaskQueryKind::Dependency),
-        })
-        .await
-        {
+        let mut report_queries = vec![doc! {
+            "$eq": ["$project_id", "$$project._id"]
+        }];
+        if let Some(cursor) = &query.cursor {
+            let (date, report_id) = cursor
+                .split_once('_')
+                .and_then(|(date, report_id)| {
+                    Some((
+                        date.parse::<i64>().ok()?,
+                        report_id.parse::<ObjectId>().ok()?,
+                    ))
+                })
+                .ok_or_else(|| "INVALID_CURSOR".to_string())?;
+            report_queries.push(doc! {
+                "$or": [
+                    {
+                        "$lt": ["$date", DateTime::from_millis(date)]
+                    },
+                    {
+                        "$and": [
+                            {
+                                "$eq": ["$date", DateTime::from_millis(date)]
+                            },
+                            {
+                                "$lt": ["$_id", report_id]
+                            }
+                        ]
+                    }
+                ]
+            });
+        }
+        let limit = to_bson::<usize>(&(query.limit + 1)).unwrap();
+
+        if let Ok(tasks) = ProjectTask::find_dependencies(_id).await {
             dependencies = tasks;
         }
 
@@ -1065,10 +2192,19 @@ impl Project {
                     {
                         "$match": {
                             "$expr": {
-                                "$eq": ["$project_id", "$$project._id"]
+                                "$and": report_queries.clone()
                             }
                         }
                     },
+                    {
+                        "$sort": {
+                            "date": -1,
+                            "_id": -1
+                        }
+                    },
+                    {
+                        "$limit": limit.clone()
+                    },
                     {
                         "$lookup": {
                             "from": "users",
@@ -1184,7 +2320,8 @@ impl Project {
                                 "_id": { "$toString": "$$project._id" },
                                 "name": "$$project.name"
                             },
-                            "date": { "$toString": "$date" },
+                            "date": date_to_string("$date"),
+                            "timestamp": { "$toLong": "$date" },
                             "time": "$time",
                             "member": {
                                 "$concatArrays": [
@@ -1288,6 +2425,7 @@ impl Project {
                             "user": "$user",
                             "project": "$project",
                             "date": "$date",
+                            "timestamp": "$timestamp",
                             "time": "$time",
                             "member": {
                                 "$map": {
@@ -1357,10 +2495,19 @@ impl Project {
                     {
                         "$match": {
                             "$expr": {
-                                "$eq": ["$project_id", "$$project._id"]
+                                "$and": report_queries.clone()
                             }
                         }
                     },
+                    {
+                        "$sort": {
+                            "date": -1,
+                            "_id": -1
+                        }
+                    },
+                    {
+                        "$limit": limit.clone()
+                    },
                     {
                         "$lookup": {
                             "from": "users",
@@ -1476,7 +2623,8 @@ impl Project {
                                 "_id": { "$toString": "$$project._id" },
                                 "name": "$$project.name"
                             },
-                            "date": { "$toString": "$date" },
+                            "date": date_to_string("$date"),
+                            "timestamp": { "$toLong": "$date" },
                             "kind": "$kind",
                             "member": {
                                 "$concatArrays": [
@@ -1575,6 +2723,7 @@ impl Project {
                             "user": "$user",
                             "project": "$project",
                             "date": "$date",
+                            "timestamp": "$timestamp",
                             "kind": "$kind",
                             "member": {
                                 "$map": {
@@ -1637,6 +2786,10 @@ impl Project {
         });
         pipeline.push(doc! {
             "$project": {
+                "cursor_date": "$report.timestamp",
+                "cursor_id": {
+                    "$toString": "$report._id"
+                },
                 "date": "$report.date",
                 "kind": {
                     "$cond": [
@@ -1731,59 +2884,176 @@ impl Project {
         });
         pipeline.push(doc! {
             "$sort": {
-                "date": -1
+                "cursor_date": -1,
+                "cursor_id": -1
             }
         });
+        pipeline.push(doc! {
+            "$limit": limit
+        });
+
+        if let Ok(mut cursor) = collection.aggregate(pipeline, None).await {
+            while let Some(Ok(doc)) = cursor.next().await {
+                let report = from_document::<ProjectReportResponse>(doc).unwrap();
+                reports.push(report);
+            }
+            if !reports.is_empty() {
+                for report in reports
+                    .iter_mut()
+                    .filter(|a| a.kind == ProjectReportKind::Progress)
+                {
+                    if let Some(progress) = report.progress.as_mut() {
+                        if let Some(tasks) = progress.actual.take() {
+                            let mut actual = Vec::with_capacity(tasks.len());
+                            for task in tasks.into_iter() {
+                                let _id = task.task_id.parse::<ObjectId>().unwrap();
+                                if let Ok(Some(base)) = ProjectTask::find_by_id(&_id).await {
+                                    if query.area_id.is_some_and(|a| a != base.area_id) {
+                                        continue;
+                                    }
+                                    progress.progress += ProjectTask::effective_value(
+                                        task.value * base.value / 100.0,
+                                        base.task_id,
+                                        &dependencies,
+                                    );
+                                }
+                                actual.push(task);
+                            }
+                            progress.actual = Some(actual);
+                        }
+                    }
+                }
+                Ok(Some(reports))
+            } else {
+                Ok(None)
+            }
+        } else {
+            Ok(None)
+        }
+    }
+    pub async fn find_reports_list(
+        _id: &ObjectId,
+    ) -> Result<Vec<ProjectReportListResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectProgressReport> =
+            db.collection::<ProjectProgressReport>("project-reports");
+
+        let mut reports = Vec::<ProjectReportListResponse>::new();
+        let tasks = ProjectTask::find_many(&ProjectTaskQuery {
+            _id: None,
+            project_id: Some(*_id),
+            task_id: None,
+            area_id: None,
+            limit: None,
+            kind: None,
+        })
+        .await?
+        .unwrap_or_default();
+
+        let pipeline = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$eq": ["$project_id", to_bson::<ObjectId>(_id).unwrap()]
+                    }
+                }
+            },
+            doc! {
+                "$project": {
+                    "user_id": "$user_id",
+                    "date": "$date",
+                    "kind": "progress",
+                    "actual": { "$ifNull": ["$actual", []] }
+                }
+            },
+            doc! {
+                "$unionWith": {
+                    "coll": "project-incidents",
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$eq": ["$project_id", to_bson::<ObjectId>(_id).unwrap()]
+                                }
+                            }
+                        },
+                        {
+                            "$project": {
+                                "user_id": "$user_id",
+                                "date": "$date",
+                                "kind": "incident",
+                                "actual": []
+                            }
+                        }
+                    ]
+                }
+            },
+            doc! {
+                "$sort": {
+                    "date": -1,
+                    "_id": -1
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "users",
+                    "as": "user",
+                    "let": {
+                        "user_id": "$user_id"
+                    },
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$eq": ["$_id", "$$user_id"]
+                                }
+                            }
+                        },
+                        {
+                            "$project": {
+                                "name": "$name"
+                            }
+                        }
+                    ]
+                }
+            },
+            doc! {
+                "$project": {
+                    "_id": {
+                        "$toString": "$_id"
+                    },
+                    "kind": "$kind",
+                    "date": date_to_string("$date"),
+                    "author_name": {
+                        "$first": "$user.name"
+                    },
+                    "progress": to_bson::<Option<f64>>(&None).unwrap(),
+                    "actual": "$actual"
+                }
+            },
+        ];
 
         if let Ok(mut cursor) = collection.aggregate(pipeline, None).await {
             while let Some(Ok(doc)) = cursor.next().await {
-                let report = from_document::<ProjectReportResponse>(doc).unwrap();
-                reports.push(report);
-            }
-            if !reports.is_empty() {
-                for report in reports
-                    .iter_mut()
-                    .filter(|a| a.kind == ProjectReportKind::Progress)
-                {
-                    if let Some(progress) = report.progress.as_mut() {
-                        if let Some(tasks) = &progress.actual {
-                            for task in tasks.iter() {
-                                let mut _id =
-                                    Some(task.task_id.clone().parse::<ObjectId>().unwrap());
-                                if let Ok(Some(base)) = ProjectTask::find_by_id(&_id.unwrap()).await
-                                {
-                                    let mut found = true;
-                                    let mut count = task.value * base.value / 100.0;
-                                    _id = base.task_id;
-
-                                    while found {
-                                        if let Some(task_id) = _id {
-                                            if let Some(index) = dependencies
-                                                .iter()
-                                                .position(|a| a._id.unwrap() == task_id)
-                                            {
-                                                count *= dependencies[index].value / 100.0;
-                                                _id = dependencies[index].task_id;
-                                            } else {
-                                                found = false;
-                                            }
-                                        } else {
-                                            found = false;
-                                        }
-                                    }
-
-                                    progress.progress += count;
-                                }
-                            }
+                let mut report = from_document::<ProjectReportListResponse>(doc).unwrap();
+                if report.kind == ProjectReportKind::Progress {
+                    let mut progress = 0.0;
+                    for actual in report.actual.iter() {
+                        if let Some(base) = tasks.iter().find(|a| a._id == Some(actual.task_id)) {
+                            progress += ProjectTask::effective_value(
+                                actual.value * base.value / 100.0,
+                                base.task_id,
+                                &tasks,
+                            );
                         }
                     }
+                    report.progress = Some(progress);
                 }
-                Ok(Some(reports))
-            } else {
-                Ok(None)
+                reports.push(report);
             }
+            Ok(reports)
         } else {
-            Ok(None)
+            Err("PROJECT_REPORT_NOT_FOUND".to_string())
         }
     }
     pub async fn delete_by_id(_id: &ObjectId) -> Result<u64, String> {
@@ -1812,9 +3082,9 @@ impl Project {
                 message,
             },
         );
+        self.last_activity = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
 
         if status == ProjectStatusKind::Running {
-            let mut total: f64 = 0.0;
             let tasks = ProjectTask::find_many(&ProjectTaskQuery {
                 _id: None,
                 project_id: self._id,
@@ -1826,11 +3096,7 @@ impl Project {
             .await?
             .ok_or_else(|| "PROJECT_TASK_NOT_FOUND".to_string())?;
 
-            for i in &tasks {
-                total += i.value;
-            }
-
-            if total != 100.0 {
+            if !ProjectTask::is_value_complete(Self::root_value_sum(&tasks)) {
                 return Err("PROJECT_TASK_VALUE_SUM_MUST_BE_100".to_string());
             }
         }
@@ -1845,6 +3111,24 @@ impl Project {
             .map_err(|_| "UPDATE_FAILED".to_string())
             .map(|_| self._id.unwrap())
     }
+    pub async fn update_activity(_id: &ObjectId) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        collection
+            .update_one(
+                doc! { "_id": _id },
+                doc! {
+                    "$set": {
+                        "last_activity": DateTime::from_millis(Utc::now().timestamp_millis())
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())
+            .map(|_| *_id)
+    }
     pub async fn add_member(
         &mut self,
         members: &[ProjectMemberRequest],
@@ -1896,6 +3180,172 @@ impl Project {
             .map_err(|_| "UPDATE_FAILED".to_string())
             .map(|_| self._id.unwrap())
     }
+    pub async fn add_member_bulk(
+        &mut self,
+        rows: Vec<ProjectMemberBulkRow>,
+        error: Vec<ProjectMemberBulkErrorResponse>,
+    ) -> Result<ProjectMemberBulkResponse, String> {
+        let roles = ProjectRole::find_many_by_project_id(&self._id.unwrap()).await?;
+
+        let mut members: Vec<ProjectMemberRequest> = Vec::<ProjectMemberRequest>::new();
+        let mut response = ProjectMemberBulkResponse {
+            added: Vec::new(),
+            error,
+        };
+
+        for row in rows {
+            let mut error = |value: &str, error: &str| {
+                response.error.push(ProjectMemberBulkErrorResponse {
+                    row: row.row,
+                    value: value.to_string(),
+                    error: error.to_string(),
+                })
+            };
+
+            let mut role_id: Vec<ObjectId> = Vec::<ObjectId>::new();
+            let mut valid = true;
+            for value in row.role.iter() {
+                match roles.iter().find(|a| {
+                    a._id.is_some_and(|_id| _id.to_string() == *value)
+                        || a.name.to_lowercase() == value.to_lowercase()
+                }) {
+                    Some(role) => role_id.push(role._id.unwrap()),
+                    None => {
+                        error(value, "PROJECT_ROLE_NOT_FOUND");
+                        valid = false;
+                    }
+                }
+            }
+
+            let user = match User::find_by_email(&row.email).await? {
+                Some(user) => user,
+                None => {
+                    error(&row.email, "USER_NOT_FOUND");
+                    continue;
+                }
+            };
+            if self
+                .member
+                .iter()
+                .flatten()
+                .any(|a| Some(a._id) == user._id)
+                || members.iter().any(|a| a._id == user._id)
+            {
+                error(&row.email, "PROJECT_MEMBER_EXISTS");
+                continue;
+            }
+
+            if valid {
+                members.push(ProjectMemberRequest {
+                    _id: user._id,
+                    name: None,
+                    kind: row.kind,
+                    role_id,
+                });
+            }
+        }
+        response.error.sort_by_key(|a| a.row);
+
+        if !members.is_empty() {
+            self.add_member(&members).await?;
+        }
+        response.added = members
+            .iter()
+            .filter_map(|a| a._id.map(|_id| _id.to_string()))
+            .collect();
+
+        Ok(response)
+    }
+    pub async fn reassign_member(&mut self, from: &ObjectId, to: &ObjectId) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+        let task_collection: Collection<ProjectTask> =
+            db.collection::<ProjectTask>("project-tasks");
+
+        let mut member: Vec<ProjectMember> = self.member.clone().unwrap_or_default();
+        let source = match member
+            .iter()
+            .find(|a| a._id == *from && !matches!(a.kind, ProjectMemberKind::Support))
+        {
+            Some(source) => source.clone(),
+            None => return Err("PROJECT_MEMBER_NOT_FOUND".to_string()),
+        };
+        if !member.iter().any(|a| a._id == *to) {
+            match User::find_by_id(to).await {
+                Ok(Some(_)) => member.push(ProjectMember {
+                    _id: *to,
+                    name: None,
+                    kind: source.kind,
+                    role_id: source.role_id,
+                }),
+                _ => return Err("USER_NOT_FOUND".to_string()),
+            }
+        }
+        self.member = Some(member);
+
+        let mut session = get_client()
+            .start_session(None)
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+
+        let modified = match task_collection
+            .update_many_with_session(
+                doc! { "project_id": self._id.unwrap(), "user_id": from },
+                vec![doc! {
+                    "$set": {
+                        "user_id": {
+                            "$setUnion": [
+                                {
+                                    "$filter": {
+                                        "input": "$user_id",
+                                        "cond": {
+                                            "$ne": ["$$this", to_bson::<ObjectId>(from).unwrap()]
+                                        }
+                                    }
+                                },
+                                [to_bson::<ObjectId>(to).unwrap()]
+                            ]
+                        },
+                        "update_date": "$$NOW"
+                    }
+                }],
+                None,
+                &mut session,
+            )
+            .await
+        {
+            Ok(result) => result.modified_count,
+            Err(_) => {
+                let _ = session.abort_transaction().await;
+                return Err("PROJECT_TASK_UPDATE_FAILED".to_string());
+            }
+        };
+
+        if collection
+            .update_one_with_session(
+                doc! { "_id": self._id.unwrap() },
+                doc! { "$set": to_bson::<Project>(self).unwrap()},
+                None,
+                &mut session,
+            )
+            .await
+            .is_err()
+        {
+            let _ = session.abort_transaction().await;
+            return Err("UPDATE_FAILED".to_string());
+        }
+
+        session
+            .commit_transaction()
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())
+            .map(|_| modified)
+            .inspect(|_| ProjectTask::invalidate_dependencies(self._id.as_ref()))
+    }
     pub async fn add_area(&mut self, areas: &[ProjectAreaRequest]) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
@@ -1909,11 +3359,42 @@ impl Project {
             let new_area = ProjectArea {
                 _id: ObjectId::new(),
                 name: i.name.clone(),
+                description: i.description.clone(),
+                update_date: Some(DateTime::from_millis(Utc::now().timestamp_millis())),
             };
             area.push(new_area);
         }
 
         self.area = Some(area);
+        self.import_hash = None;
+
+        collection
+            .update_one(
+                doc! { "_id": self._id.unwrap() },
+                doc! { "$set": to_bson::<Project>(self).unwrap()},
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())
+            .map(|_| self._id.unwrap())
+    }
+    pub async fn update_area(
+        &mut self,
+        area_id: &ObjectId,
+        request: ProjectAreaRequest,
+    ) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        match self.area.iter_mut().flatten().find(|a| a._id == *area_id) {
+            Some(area) => {
+                area.name = request.name;
+                area.description = request.description;
+                area.update_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
+            }
+            None => return Err("PROJECT_AREA_NOT_FOUND".to_string()),
+        }
+        self.import_hash = None;
 
         collection
             .update_one(
@@ -1925,11 +3406,43 @@ impl Project {
             .map_err(|_| "UPDATE_FAILED".to_string())
             .map(|_| self._id.unwrap())
     }
-    pub async fn replace_areas(&mut self, areas: Vec<ProjectArea>) -> Result<ObjectId, String> {
+    pub async fn replace_areas(&mut self, mut areas: Vec<ProjectArea>) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
 
+        let removed: Vec<ObjectId> = self
+            .area
+            .iter()
+            .flatten()
+            .filter(|a| !areas.iter().any(|b| b._id == a._id))
+            .map(|a| a._id)
+            .collect();
+        for area in areas.iter_mut().filter(|a| a.update_date.is_none()) {
+            area.update_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
+        }
         self.area = Some(areas);
+        self.import_hash = None;
+
+        collection
+            .update_one(
+                doc! { "_id": self._id.unwrap() },
+                doc! { "$set": to_bson::<Project>(self).unwrap()},
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())?;
+        let _ = Tombstone::save_many(TombstoneKind::Area, &self._id.unwrap(), &removed).await;
+
+        Ok(self._id.unwrap())
+    }
+    pub async fn update_baseline(
+        &mut self,
+        baseline: Vec<ProjectBaseline>,
+    ) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        self.baseline = Some(baseline);
 
         collection
             .update_one(
@@ -1941,6 +3454,161 @@ impl Project {
             .map_err(|_| "UPDATE_FAILED".to_string())
             .map(|_| self._id.unwrap())
     }
+    pub fn validate_budget(
+        budget: Option<f64>,
+        currency: Option<String>,
+    ) -> Result<(Option<f64>, Option<String>), String> {
+        if budget.is_some_and(|budget| !budget.is_finite() || budget < 0.0) {
+            return Err("INVALID_BUDGET".to_string());
+        }
+        let currency = match currency {
+            Some(currency) if !currency.trim().is_empty() => Some(currency.trim().to_uppercase()),
+            _ => budget.map(|_| ProjectTaskCost::base_currency()),
+        };
+        Ok((budget, currency))
+    }
+    pub async fn update_budget(
+        &mut self,
+        request: ProjectBudgetRequest,
+    ) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        if request
+            .spent
+            .is_some_and(|spent| !spent.is_finite() || spent < 0.0)
+        {
+            return Err("INVALID_SPENT".to_string());
+        }
+        let (budget, currency) = Self::validate_budget(Some(request.budget), request.currency)?;
+
+        self.budget = budget;
+        self.currency = currency;
+        self.spent = request.spent;
+
+        collection
+            .update_one(
+                doc! { "_id": self._id.unwrap() },
+                doc! {
+                    "$set": {
+                        "budget": to_bson::<Option<f64>>(&self.budget).unwrap(),
+                        "currency": to_bson::<Option<String>>(&self.currency).unwrap(),
+                        "spent": to_bson::<Option<f64>>(&self.spent).unwrap(),
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())
+            .map(|_| self._id.unwrap())
+    }
+    pub async fn clear_import_hash(_id: &ObjectId) -> Result<(), String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        collection
+            .update_one(
+                doc! { "_id": _id, "import_hash": { "$ne": Bson::Null } },
+                doc! { "$set": { "import_hash": Bson::Null } },
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())
+            .map(|_| ())
+    }
+    pub async fn update_import_hash(&mut self, hash: String) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        self.import_hash = Some(hash);
+
+        collection
+            .update_one(
+                doc! { "_id": self._id.unwrap() },
+                doc! { "$set": { "import_hash": to_bson::<Option<String>>(&self.import_hash).unwrap() } },
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())
+            .map(|_| self._id.unwrap())
+    }
+    pub async fn merge_area(
+        &mut self,
+        source: &ObjectId,
+        target: &ObjectId,
+    ) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+        let task_collection: Collection<ProjectTask> =
+            db.collection::<ProjectTask>("project-tasks");
+
+        if source == target {
+            return Err("INVALID_TARGET_AREA".to_string());
+        }
+        let mut area: Vec<ProjectArea> = self.area.clone().unwrap_or_default();
+        if !area.iter().any(|a| a._id == *target) {
+            return Err("PROJECT_AREA_NOT_FOUND".to_string());
+        }
+        match area.iter().position(|a| a._id == *source) {
+            Some(index) => area.remove(index),
+            None => return Err("PROJECT_AREA_NOT_FOUND".to_string()),
+        };
+        self.area = Some(area);
+        self.import_hash = None;
+
+        let mut session = get_client()
+            .start_session(None)
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+
+        let modified = match task_collection
+            .update_many_with_session(
+                doc! { "project_id": self._id.unwrap(), "area_id": source },
+                doc! {
+                    "$set": {
+                        "area_id": target,
+                        "update_date": DateTime::from_millis(Utc::now().timestamp_millis())
+                    }
+                },
+                None,
+                &mut session,
+            )
+            .await
+        {
+            Ok(result) => result.modified_count,
+            Err(_) => {
+                let _ = session.abort_transaction().await;
+                return Err("PROJECT_TASK_UPDATE_FAILED".to_string());
+            }
+        };
+
+        if collection
+            .update_one_with_session(
+                doc! { "_id": self._id.unwrap() },
+                doc! { "$set": to_bson::<Project>(self).unwrap()},
+                None,
+                &mut session,
+            )
+            .await
+            .is_err()
+        {
+            let _ = session.abort_transaction().await;
+            return Err("UPDATE_FAILED".to_string());
+        }
+
+        session
+            .commit_transaction()
+            .await
+            .map_err(|_| "TRANSACTION_FAILED".to_string())?;
+        ProjectTask::invalidate_dependencies(self._id.as_ref());
+        let _ = Tombstone::save_many(TombstoneKind::Area, &self._id.unwrap(), &[*source]).await;
+
+        Ok(modified)
+    }
     pub async fn remove_area(&mut self, area_id: &ObjectId) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<Project> = db.collection::<Project>("projects");
@@ -1950,6 +3618,7 @@ impl Project {
                 area.remove(index);
             }
         }
+        self.import_hash = None;
 
         collection
             .update_one(
@@ -1958,7 +3627,90 @@ impl Project {
                 None,
             )
             .await
-            .map_err(|_| "UPDATE_FAILED".to_string())
-            .map(|_| self._id.unwrap())
+            .map_err(|_| "UPDATE_FAILED".to_string())?;
+        let _ = Tombstone::save_many(TombstoneKind::Area, &self._id.unwrap(), &[*area_id]).await;
+
+        Ok(self._id.unwrap())
+    }
+    pub async fn remove_many_area(&mut self, area_id: &[ObjectId]) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<Project> = db.collection::<Project>("projects");
+
+        collection
+            .update_one(
+                doc! { "_id": self._id.unwrap() },
+                doc! {
+                    "$pull": { "area": { "_id": { "$in": area_id } } },
+                    "$set": { "import_hash": Bson::Null }
+                },
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())?;
+        if let Some(area) = self.area.as_mut() {
+            area.retain(|a| !area_id.contains(&a._id));
+        }
+        self.import_hash = None;
+        let _ = Tombstone::save_many(TombstoneKind::Area, &self._id.unwrap(), area_id).await;
+
+        Ok(self._id.unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProjectQuerySortKind, ProjectStatusKind};
+
+    #[test]
+    fn az_sort_is_ascending_by_name() {
+        let stages = ProjectQuerySortKind::AZ.stages();
+        let sort = stages[1].get_document("$sort").unwrap();
+        assert_eq!(sort.get_i32("name_sort"), Ok(1));
+
+        let stages = ProjectQuerySortKind::ZA.stages();
+        let sort = stages[1].get_document("$sort").unwrap();
+        assert_eq!(sort.get_i32("name_sort"), Ok(-1));
+    }
+
+    #[test]
+    fn default_sort_reads_env() {
+        std::env::set_var("PROJECT_DEFAULT_SORT", "a_z");
+        assert_eq!(
+            ProjectQuerySortKind::default_sort(),
+            ProjectQuerySortKind::AZ
+        );
+        std::env::set_var("PROJECT_DEFAULT_SORT", "oldest");
+        assert_eq!(
+            ProjectQuerySortKind::default_sort(),
+            ProjectQuerySortKind::Oldest
+        );
+        std::env::set_var("PROJECT_DEFAULT_SORT", "garbage");
+        assert_eq!(
+            ProjectQuerySortKind::default_sort(),
+            ProjectQuerySortKind::Latest
+        );
+        std::env::remove_var("PROJECT_DEFAULT_SORT");
+        assert_eq!(
+            ProjectQuerySortKind::default_sort(),
+            ProjectQuerySortKind::Latest
+        );
+    }
+
+    #[test]
+    fn only_stopped_projects_can_start_running() {
+        for status in [
+            ProjectStatusKind::Pending,
+            ProjectStatusKind::Paused,
+            ProjectStatusKind::Breakdown,
+        ] {
+            assert!(status.can_transition_to(&ProjectStatusKind::Running));
+        }
+        for status in [
+            ProjectStatusKind::Running,
+            ProjectStatusKind::Finished,
+            ProjectStatusKind::Cancelled,
+        ] {
+            assert!(!status.can_transition_to(&ProjectStatusKind::Running));
+        }
     }
 }
@@ -1,12 +1,17 @@
 use crate::database::get_db;
 
+use futures::stream::StreamExt;
 use mongodb::{
-    bson::{doc, oid::ObjectId, DateTime},
+    bson::{doc, from_document, oid::ObjectId, to_bson, DateTime, Document},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
 
-use super::project::{Project, ProjectMemberResponse, ProjectStatusKind};
+use super::{
+    date_to_string,
+    project::{Project, ProjectMemberResponse, ProjectStatusKind},
+    project_task::ProjectTask,
+};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -24,12 +29,14 @@ pub struct ProjectIncidentReport {
     pub _id: Option<ObjectId>,
     pub project_id: ObjectId,
     pub user_id: ObjectId,
+    pub task_id: Option<ObjectId>,
     pub member_id: Option<Vec<ObjectId>>,
     pub date: DateTime,
     pub kind: ProjectIncidentReportKind,
 }
 #[derive(Debug, Deserialize)]
 pub struct ProjectIncidentReportRequest {
+    pub task_id: Option<ObjectId>,
     pub member_id: Option<Vec<ObjectId>>,
     pub kind: ProjectIncidentReportKind,
 }
@@ -52,6 +59,25 @@ pub struct ProjectIncidentReportProjectResponse {
     pub _id: String,
     pub name: String,
 }
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectIncidentReportMinResponse {
+    pub _id: String,
+    pub user: Option<ProjectIncidentReportUserResponse>,
+    pub date: String,
+    pub kind: ProjectIncidentReportKind,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectIncidentReportOverviewResponse {
+    pub project: ProjectIncidentReportProjectResponse,
+    pub count: usize,
+    pub incident: Vec<ProjectIncidentReportMinResponse>,
+}
+#[derive(Debug)]
+pub struct ProjectIncidentReportQuery {
+    pub project_id: Option<ObjectId>,
+    pub from: Option<DateTime>,
+    pub to: Option<DateTime>,
+}
 
 impl ProjectIncidentReport {
     pub async fn save(&mut self, breakdown: bool) -> Result<ObjectId, String> {
@@ -61,7 +87,15 @@ impl ProjectIncidentReport {
 
         self._id = Some(ObjectId::new());
 
+        if let Some(task_id) = &self.task_id {
+            match ProjectTask::find_by_id(task_id).await? {
+                Some(task) if task.project_id == self.project_id => (),
+                _ => return Err("PROJECT_TASK_NOT_FOUND".to_string()),
+            }
+        }
+
         if let Ok(Some(mut project)) = Project::find_by_id(&self.project_id).await {
+            let project_id = self.project_id;
             let result = collection
                 .insert_one(self, None)
                 .await
@@ -73,10 +107,208 @@ impl ProjectIncidentReport {
                     .update_status(ProjectStatusKind::Breakdown, None)
                     .await
                     .map_err(|_| "PROJECT_STATUS_UPDATE_FAILED".to_string())?;
+            } else {
+                Project::update_activity(&project_id).await?;
             }
             Ok(result)
         } else {
             Err("PROJECT_NOT_FOUND".to_string())
         }
     }
+    pub async fn find_many(
+        query: &ProjectIncidentReportQuery,
+    ) -> Result<Option<Vec<ProjectIncidentReport>>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectIncidentReport> =
+            db.collection::<ProjectIncidentReport>("project-incidents");
+
+        let mut filter = Document::new();
+
+        if let Some(_id) = &query.project_id {
+            filter.insert("project_id", _id);
+        }
+        if query.from.is_some() || query.to.is_some() {
+            let mut date = Document::new();
+            if let Some(from) = query.from {
+                date.insert("$gte", from);
+            }
+            if let Some(to) = query.to {
+                date.insert("$lte", to);
+            }
+            filter.insert("date", date);
+        }
+
+        match collection.find(filter, None).await {
+            Ok(mut cursor) => {
+                let mut incidents = Vec::<ProjectIncidentReport>::new();
+                while let Some(Ok(incident)) = cursor.next().await {
+                    incidents.push(incident);
+                }
+                if !incidents.is_empty() {
+                    Ok(Some(incidents))
+                } else {
+                    Ok(None)
+                }
+            }
+            Err(_) => Err("PROJECT_INCIDENT_NOT_FOUND".to_string()),
+        }
+    }
+    pub async fn find_many_overview(
+        query: &ProjectIncidentReportQuery,
+    ) -> Result<Vec<ProjectIncidentReportOverviewResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectIncidentReport> =
+            db.collection::<ProjectIncidentReport>("project-incidents");
+
+        let mut queries: Vec<Document> = Vec::<Document>::new();
+
+        if let Some(_id) = &query.project_id {
+            queries.push(doc! {
+                "$eq": [ "$project_id", to_bson::<ObjectId>(_id).unwrap() ]
+            });
+        }
+        if let Some(from) = &query.from {
+            queries.push(doc! {
+                "$gte": [ "$date", to_bson::<DateTime>(from).unwrap() ]
+            });
+        }
+        if let Some(to) = &query.to {
+            queries.push(doc! {
+                "$lte": [ "$date", to_bson::<DateTime>(to).unwrap() ]
+            });
+        }
+
+        let limit = std::env::var("OVERVIEW_INCIDENT_LIMIT")
+            .ok()
+            .and_then(|limit| limit.parse::<i64>().ok())
+            .unwrap_or(5)
+            .max(1);
+
+        let pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$and": queries
+                    }
+                }
+            },
+            // $push keeps this order, so the sliced incidents are the most recent ones
+            doc! {
+                "$sort": {
+                    "date": -1
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "users",
+                    "as": "user",
+                    "let": {
+                        "user_id": "$user_id"
+                    },
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$eq": ["$_id", "$$user_id"]
+                                }
+                            }
+                        },
+                        {
+                            "$project": {
+                                "_id": {
+                                    "$toString": "$_id"
+                                },
+                                "name": "$name"
+                            }
+                        }
+                    ]
+                }
+            },
+            doc! {
+                "$group": {
+                    "_id": "$project_id",
+                    "count": {
+                        "$sum": 1
+                    },
+                    "date": {
+                        "$max": "$date"
+                    },
+                    "incident": {
+                        "$push": {
+                            "_id": {
+                                "$toString": "$_id"
+                            },
+                            "user": {
+                                "$first": "$user"
+                            },
+                            "date": date_to_string("$date"),
+                            "kind": "$kind"
+                        }
+                    }
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "projects",
+                    "as": "project",
+                    "let": {
+                        "project_id": "$_id"
+                    },
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$eq": ["$_id", "$$project_id"]
+                                }
+                            }
+                        },
+                        {
+                            "$project": {
+                                "_id": {
+                                    "$toString": "$_id"
+                                },
+                                "name": "$name"
+                            }
+                        }
+                    ]
+                }
+            },
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$gt": [{ "$size": "$project" }, 0]
+                    }
+                }
+            },
+            doc! {
+                "$sort": {
+                    "date": -1
+                }
+            },
+            doc! {
+                "$project": {
+                    "_id": 0,
+                    "project": {
+                        "$first": "$project"
+                    },
+                    "count": "$count",
+                    "incident": {
+                        "$slice": ["$incident", limit]
+                    }
+                }
+            },
+        ];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut incidents = Vec::<ProjectIncidentReportOverviewResponse>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    incidents
+                        .push(from_document::<ProjectIncidentReportOverviewResponse>(doc).unwrap());
+                }
+                Ok(incidents)
+            }
+            Err(_) => Err("PROJECT_INCIDENT_NOT_FOUND".to_string()),
+        }
+    }
 }
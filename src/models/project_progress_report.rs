@@ -1,17 +1,26 @@
-use crate::database::get_db;
+use crate::{database::get_db, mailer};
 
 use actix_multipart::form::{tempfile::TempFile, MultipartForm};
+use chrono::Utc;
 use futures::stream::StreamExt;
 use mongodb::{
-    bson::{doc, from_document, oid::ObjectId, to_bson, DateTime, Document},
+    bson::{doc, from_document, oid::ObjectId, to_bson, to_document, DateTime, Document},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::{
+    collections::BTreeMap,
+    fs::{read_dir, remove_dir, remove_file},
+    path::Path,
+    str::FromStr,
+};
 
 use super::{
+    date_to_string,
     project::{Project, ProjectMemberResponse, ProjectStatusKind},
-    project_task::{ProjectTask, ProjectTaskQuery, ProjectTaskQueryKind, ProjectTaskStatusKind},
+    project_task::{ProjectTask, ProjectTaskStatusKind},
+    user::User,
+    FileKind,
 };
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -35,6 +44,7 @@ pub struct ProjectProgressReport {
     pub plan: Option<Vec<ProjectProgressReportPlan>>,
     pub documentation: Option<Vec<ProjectProgressReportDocumentation>>,
     pub weather: Option<Vec<ProjectProgressReportWeather>>,
+    pub update_date: Option<DateTime>,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectProgressReportActual {
@@ -50,6 +60,18 @@ pub struct ProjectProgressReportDocumentation {
     pub _id: ObjectId,
     pub description: Option<String>,
     pub extension: String,
+    pub cover: Option<bool>,
+    pub upload_date: Option<DateTime>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectProgressReportCreateResponse {
+    pub _id: String,
+    pub slot: Vec<ProjectProgressReportSlotResponse>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectProgressReportSlotResponse {
+    pub _id: String,
+    pub url: String,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectProgressReportWeather {
@@ -60,6 +82,11 @@ pub struct ProjectProgressReportWeather {
 pub struct ProjectProgressReportDocumentationRequest {
     pub description: Option<String>,
     pub extension: String,
+    pub cover: Option<bool>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectProgressReportCoverRequest {
+    pub documentation_id: ObjectId,
 }
 
 pub struct ProjectProgressReportQuery {
@@ -150,6 +177,45 @@ pub struct ProjectProgressReportDocumentationResponse {
     pub _id: String,
     pub description: Option<String>,
     pub extension: String,
+    pub cover: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectProgressReportFileVerifyResponse {
+    pub orphan_file: Vec<String>,
+    pub missing_file: Vec<ProjectProgressReportMissingFileResponse>,
+    pub fixed: bool,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectProgressReportMissingFileResponse {
+    pub report_id: String,
+    pub documentation_id: String,
+}
+
+impl ProjectProgressReportDocumentation {
+    pub fn is_uploaded(&self, report_id: &ObjectId) -> bool {
+        Path::new(
+            &FileKind::ProjectDocumentation
+                .path(format!("{}/{}.{}", report_id, self._id, self.extension)),
+        )
+        .is_file()
+    }
+}
+
+impl ProjectProgressReportWeather {
+    // time is an inclusive [start, end] range of hour buckets, e.g. [8, 11] covers 08:00-11:59
+    pub fn normalize(weather: &mut [Self]) -> Result<(), String> {
+        if weather
+            .iter()
+            .any(|a| a.time[0] > 23 || a.time[1] > 23 || a.time[0] > a.time[1])
+        {
+            return Err("INVALID_WEATHER_TIME".to_string());
+        }
+        weather.sort_by_key(|a| a.time);
+        if weather.windows(2).any(|a| a[1].time[0] <= a[0].time[1]) {
+            return Err("INVALID_WEATHER_TIME".to_string());
+        }
+        Ok(())
+    }
 }
 
 impl ProjectProgressReport {
@@ -174,12 +240,14 @@ impl ProjectProgressReport {
                 return Err("PROJECT_REPORT_TIME_INVALID".to_string());
             }
         }
+        if let Some(weather) = self.weather.as_mut() {
+            ProjectProgressReportWeather::normalize(weather)?;
+        }
 
         if let Some(actual) = self.actual.as_mut() {
             let mut invalid_task_index = Vec::<usize>::new();
-            if project.status.get(0).unwrap().kind == ProjectStatusKind::Pending
-                || project.status.get(0).unwrap().kind == ProjectStatusKind::Paused
-            {
+            let status = project.current_status()?.kind.clone();
+            if status == ProjectStatusKind::Pending || status == ProjectStatusKind::Paused {
                 project
                     .update_status(ProjectStatusKind::Running, None)
                     .await
@@ -198,15 +266,32 @@ impl ProjectProgressReport {
                             .await
                             .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())?
                             .ok_or_else(|| "PROJECT_TASK_NOT_FOUND".to_string())?;
-                        task.update_status(ProjectTaskStatusKind::Finished, None)
-                            .await
-                            .map_err(|_| "PROJECT_TASK_UPDATE_FAILED".to_string())?;
+                        let kind = task.current_status()?.kind.clone();
+                        if kind != ProjectTaskStatusKind::Finished {
+                            if kind.can_transition_to(&ProjectTaskStatusKind::Running) {
+                                task.update_status(ProjectTaskStatusKind::Running, None)
+                                    .await
+                                    .map_err(|_| "PROJECT_TASK_UPDATE_FAILED".to_string())?;
+                            }
+                            // Open checklist items keep the task running at full progress
+                            match task
+                                .update_status(ProjectTaskStatusKind::Finished, None)
+                                .await
+                            {
+                                Ok(_) => (),
+                                Err(error) if error == "CHECKLIST_INCOMPLETE" => (),
+                                Err(_) => return Err("PROJECT_TASK_UPDATE_FAILED".to_string()),
+                            }
+                        }
                     } else {
                         let status = task
                             .status
                             .first()
                             .ok_or_else(|| "PROJECT_TASK_STATUS_NOT_FOUND".to_string())?;
-                        if status.kind != ProjectTaskStatusKind::Running {
+                        if status
+                            .kind
+                            .can_transition_to(&ProjectTaskStatusKind::Running)
+                        {
                             let mut task = ProjectTask::find_by_id(&actual_task.task_id)
                                 .await
                                 .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())?
@@ -225,27 +310,180 @@ impl ProjectProgressReport {
             }
         }
 
-        collection
+        let project_id = self.project_id;
+        self.update_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
+        let result = collection
             .insert_one(self, None)
             .await
             .map_err(|_| "INSERTING_FAILED".to_string())
-            .map(|result| result.inserted_id.as_object_id().unwrap())
+            .map(|result| result.inserted_id.as_object_id().unwrap())?;
+
+        Project::update_activity(&project_id).await?;
+
+        Ok(result)
     }
     pub async fn update(&self) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectProgressReport> =
             db.collection::<ProjectProgressReport>("project-reports");
 
+        let mut report: Document = to_document::<ProjectProgressReport>(self).unwrap();
+        report.insert(
+            "update_date",
+            DateTime::from_millis(Utc::now().timestamp_millis()),
+        );
+
         collection
             .update_one(
                 doc! { "_id": self._id.unwrap() },
-                doc! { "$set": to_bson::<ProjectProgressReport>(self).unwrap()},
+                doc! { "$set": report },
                 None,
             )
             .await
             .map_err(|_| "UPDATE_FAILED".to_string())
             .map(|_| self._id.unwrap())
     }
+    pub async fn verify_documentation(
+        fix: bool,
+    ) -> Result<ProjectProgressReportFileVerifyResponse, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectProgressReport> =
+            db.collection::<ProjectProgressReport>("project-reports");
+
+        let mut reports: BTreeMap<ObjectId, ProjectProgressReport> = BTreeMap::new();
+        let mut cursor = collection
+            .find(doc! {}, None)
+            .await
+            .map_err(|_| "PROJECT_REPORT_NOT_FOUND".to_string())?;
+        while let Some(Ok(report)) = cursor.next().await {
+            reports.insert(report._id.unwrap(), report);
+        }
+
+        let mut response = ProjectProgressReportFileVerifyResponse {
+            orphan_file: Vec::new(),
+            missing_file: Vec::new(),
+            fixed: fix,
+        };
+
+        let is_known = |report: Option<&ProjectProgressReport>, name: &str| {
+            report.is_some_and(|report| {
+                report.documentation.iter().flatten().any(|documentation| {
+                    name == format!("{}.{}", documentation._id, documentation.extension)
+                })
+            })
+        };
+
+        // Files on disk without a documentation entry
+        if let Ok(dirs) = read_dir(FileKind::ProjectDocumentation.path("")) {
+            for dir in dirs.flatten() {
+                let report_id = dir
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.parse::<ObjectId>().ok());
+                let report = report_id.and_then(|_id| reports.get(&_id));
+                let mut fresh: Option<Option<ProjectProgressReport>> = None;
+                let files = match read_dir(dir.path()) {
+                    Ok(files) => files,
+                    Err(_) => continue,
+                };
+                for file in files.flatten() {
+                    let name = file.file_name().to_string_lossy().to_string();
+                    if is_known(report, &name) {
+                        continue;
+                    }
+                    // The scan snapshot may predate the upload, so check the stored report again
+                    if fresh.is_none() {
+                        fresh = Some(match &report_id {
+                            Some(_id) => Self::find_by_id(_id).await?,
+                            None => None,
+                        });
+                    }
+                    if is_known(fresh.as_ref().and_then(|a| a.as_ref()), &name) {
+                        continue;
+                    }
+                    if fix {
+                        let _ = remove_file(file.path());
+                    }
+                    response.orphan_file.push(format!(
+                        "{}/{}",
+                        dir.file_name().to_string_lossy(),
+                        name
+                    ));
+                }
+                if fix && report.is_none() && fresh.is_some_and(|a| a.is_none()) {
+                    let _ = remove_dir(dir.path());
+                }
+            }
+        }
+
+        // Documentation entries whose uploaded file is gone, slots never uploaded are skipped
+        for (report_id, report) in reports.iter() {
+            for documentation in report.documentation.iter().flatten() {
+                if documentation.upload_date.is_none() || documentation.is_uploaded(report_id) {
+                    continue;
+                }
+                if fix {
+                    collection
+                        .update_one(
+                            doc! { "_id": report_id },
+                            doc! {
+                                "$pull": {
+                                    "documentation": { "_id": documentation._id }
+                                },
+                                "$set": {
+                                    "update_date": DateTime::from_millis(Utc::now().timestamp_millis())
+                                }
+                            },
+                            None,
+                        )
+                        .await
+                        .map_err(|_| "UPDATE_FAILED".to_string())?;
+                }
+                response
+                    .missing_file
+                    .push(ProjectProgressReportMissingFileResponse {
+                        report_id: report_id.to_string(),
+                        documentation_id: documentation._id.to_string(),
+                    });
+            }
+        }
+
+        Ok(response)
+    }
+    pub async fn delete_many_task_entry(task_id: &[ObjectId]) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectProgressReport> =
+            db.collection::<ProjectProgressReport>("project-reports");
+
+        let task_id = to_bson::<Vec<ObjectId>>(&task_id.to_vec()).unwrap();
+        let update_date = DateTime::from_millis(Utc::now().timestamp_millis());
+
+        // Each field is pulled on its own, $pull fails on reports where the other one is null
+        let mut modified = 0;
+        for field in ["actual", "plan"] {
+            modified += collection
+                .update_many(
+                    doc! {
+                        field: { "$type": "array" },
+                        format!("{}.task_id", field): { "$in": task_id.clone() }
+                    },
+                    doc! {
+                        "$pull": {
+                            field: { "task_id": { "$in": task_id.clone() } }
+                        },
+                        "$set": {
+                            "update_date": update_date
+                        }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|_| "UPDATE_FAILED".to_string())?
+                .modified_count;
+        }
+
+        Ok(modified)
+    }
     pub async fn find_by_id(_id: &ObjectId) -> Result<Option<ProjectProgressReport>, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectProgressReport> =
@@ -294,13 +532,53 @@ impl ProjectProgressReport {
             Err("PROJECT_TASK_NOT_FOUND".to_string())
         }
     }
+    pub async fn find_many_updated(
+        project_id: &ObjectId,
+        since: &DateTime,
+    ) -> Result<Vec<ProjectProgressReportResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectProgressReport> =
+            db.collection::<ProjectProgressReport>("project-reports");
+
+        let mut report_id: Vec<ObjectId> = Vec::<ObjectId>::new();
+        let mut cursor = collection
+            .find(
+                doc! { "project_id": project_id, "update_date": { "$gt": since } },
+                None,
+            )
+            .await
+            .map_err(|_| "PROJECT_REPORT_NOT_FOUND".to_string())?;
+        while let Some(Ok(report)) = cursor.next().await {
+            report_id.extend(report._id);
+        }
+
+        let mut reports: Vec<ProjectProgressReportResponse> =
+            Vec::<ProjectProgressReportResponse>::new();
+        for _id in report_id.iter() {
+            if let Some(report) = Self::find_detail_by_id(_id, None).await? {
+                reports.push(report);
+            }
+        }
+
+        Ok(reports)
+    }
     pub async fn find_detail_by_id(
         _id: &ObjectId,
+        area_id: Option<ObjectId>,
     ) -> Result<Option<ProjectProgressReportResponse>, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectProgressReport> =
             db.collection::<ProjectProgressReport>("project-reports");
 
+        let mut actual_queries = vec![doc! {
+            "$in": ["$_id", "$$actual.task_id"]
+        }];
+        if let Some(area_id) = area_id {
+            actual_queries.push(doc! {
+                "$eq": ["$area_id", to_bson::<ObjectId>(&area_id).unwrap()]
+            });
+        }
+
         let pipeline = vec![
             doc! {
                 "$match": {
@@ -451,7 +729,7 @@ impl ProjectProgressReport {
                             "$first": "$project.area"
                         }
                     },
-                    "date": { "$toString": "$date" },
+                    "date": date_to_string("$date"),
                     "time": "$time",
                     "member": {
                         "$concatArrays": [
@@ -562,7 +840,7 @@ impl ProjectProgressReport {
                         {
                             "$match": {
                                 "$expr": {
-                                    "$in": ["$_id", "$$actual.task_id"]
+                                    "$and": actual_queries
                                 }
                             }
                         },
@@ -666,6 +944,7 @@ impl ProjectProgressReport {
                                 "_id": { "$toString": "$$this._id" },
                                 "extension": "$$this.extension",
                                 "description": "$$this.description",
+                                "cover": { "$ifNull": ["$$this.cover", false] },
                             }
                         }
                     },
@@ -679,16 +958,7 @@ impl ProjectProgressReport {
         ];
         let mut dependencies: Vec<ProjectTask> = Vec::new();
 
-        if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
-            _id: None,
-            project_id: Some(*_id),
-            task_id: None,
-            area_id: None,
-            limit: None,
-            kind: Some(ProjectTaskQueryKind::Dependency),
-        })
-        .await
-        {
+        if let Ok(tasks) = ProjectTask::find_dependencies(_id).await {
             dependencies = tasks;
         }
 
@@ -700,26 +970,11 @@ impl ProjectProgressReport {
                         if let Ok(Some(base)) =
                             ProjectTask::find_by_id(&ObjectId::from_str(&task._id).unwrap()).await
                         {
-                            let mut _id = base.task_id;
-                            let mut found = true;
-                            let mut count = task.value * base.value / 100.0;
-
-                            while found {
-                                if let Some(task_id) = _id {
-                                    if let Some(index) =
-                                        dependencies.iter().position(|a| a._id.unwrap() == task_id)
-                                    {
-                                        count *= dependencies[index].value / 100.0;
-                                        _id = dependencies[index].task_id;
-                                    } else {
-                                        found = false;
-                                    }
-                                } else {
-                                    found = false;
-                                }
-                            }
-
-                            report.progress += count;
+                            report.progress += ProjectTask::effective_value(
+                                task.value * base.value / 100.0,
+                                base.task_id,
+                                &dependencies,
+                            );
                         }
                     }
                 }
@@ -731,15 +986,95 @@ impl ProjectProgressReport {
             Ok(None)
         }
     }
-    pub async fn delete_by_id(_id: &ObjectId) -> Result<u64, String> {
-        let db: Database = get_db();
-        let collection: Collection<ProjectProgressReport> =
-            db.collection::<ProjectProgressReport>("project-reports");
+    pub async fn notify_owners(&self) -> Result<usize, String> {
+        if !mailer::is_enabled() {
+            return Ok(0);
+        }
 
-        collection
-            .delete_one(doc! { "_id": _id }, None)
-            .await
-            .map_err(|_| "PROJECT_REPORT_NOT_FOUND".to_string())
-            .map(|result| result.deleted_count)
+        let project = Project::find_by_id(&self.project_id)
+            .await?
+            .ok_or_else(|| "PROJECT_NOT_FOUND".to_string())?;
+        let reporter = User::find_by_id(&self.user_id)
+            .await?
+            .map_or_else(|| "-".to_string(), |user| user.name);
+
+        let mut emails = Vec::<String>::new();
+        for user in project.find_owners().await {
+            if user._id == Some(self.user_id) {
+                continue;
+            }
+            if let Some(preference) = &user.preference {
+                if preference.report_email == Some(false) {
+                    continue;
+                }
+            }
+            emails.push(user.email);
+        }
+
+        if emails.is_empty() {
+            return Ok(0);
+        }
+
+        let subject = format!("[{}] New progress report", project.name);
+        let body = format!(
+            "{} submitted a progress report for {} on {}.\n\nMembers: {}\nTasks reported: {}\nTasks planned: {}\n\nTo stop receiving these emails, turn off report emails in your preferences.",
+            reporter,
+            project.name,
+            self.date.try_to_rfc3339_string().unwrap_or_default(),
+            self.member_id.as_ref().map_or(0, |a| a.len()),
+            self.actual.as_ref().map_or(0, |a| a.len()),
+            self.plan.as_ref().map_or(0, |a| a.len()),
+        );
+
+        mailer::send(&emails, &subject, &body).await?;
+
+        Ok(emails.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProjectProgressReportWeather, ProjectProgressReportWeatherKind};
+
+    fn weather(time: [usize; 2]) -> ProjectProgressReportWeather {
+        ProjectProgressReportWeather {
+            time,
+            kind: ProjectProgressReportWeatherKind::Sunny,
+        }
+    }
+
+    #[test]
+    fn normalize_sorts_valid_windows() {
+        let mut windows = vec![weather([12, 15]), weather([8, 11]), weather([16, 23])];
+        assert!(ProjectProgressReportWeather::normalize(&mut windows).is_ok());
+        let time: Vec<[usize; 2]> = windows.iter().map(|a| a.time).collect();
+        assert_eq!(time, vec![[8, 11], [12, 15], [16, 23]]);
+    }
+
+    #[test]
+    fn normalize_rejects_hour_out_of_range() {
+        let mut windows = vec![weather([20, 24])];
+        assert_eq!(
+            ProjectProgressReportWeather::normalize(&mut windows),
+            Err("INVALID_WEATHER_TIME".to_string())
+        );
+    }
+
+    #[test]
+    fn normalize_rejects_reversed_window() {
+        let mut windows = vec![weather([11, 8])];
+        assert_eq!(
+            ProjectProgressReportWeather::normalize(&mut windows),
+            Err("INVALID_WEATHER_TIME".to_string())
+        );
+    }
+
+    #[test]
+    fn normalize_rejects_overlapping_windows() {
+        let mut windows = vec![weather([8, 12]), weather([12, 15])];
+        assert_eq!(
+            ProjectProgressReportWeather::normalize(&mut windows),
+            Err("INVALID_WEATHER_TIME".to_string())
+        );
     }
 }
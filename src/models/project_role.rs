@@ -1,5 +1,6 @@
 use crate::database::get_db;
 
+use futures::stream::StreamExt;
 use mongodb::{
     bson::{doc, oid::ObjectId, to_bson},
     Collection, Database,
@@ -24,6 +25,9 @@ pub enum ProjectRolePermission {
     GetTask,
     CreateReport,
     CreateIncident,
+    GetReport,
+    GetProject,
+    GetMembers,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -98,6 +102,24 @@ impl ProjectRole {
             .await
             .map_err(|_| "PROJECT_ROLE_NOT_FOUND".to_string())
     }
+    pub async fn find_many_by_project_id(
+        project_id: &ObjectId,
+    ) -> Result<Vec<ProjectRole>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectRole> = db.collection::<ProjectRole>("project-roles");
+
+        let mut cursor = collection
+            .find(doc! { "project_id": project_id }, None)
+            .await
+            .map_err(|_| "PROJECT_ROLE_NOT_FOUND".to_string())?;
+
+        let mut roles: Vec<ProjectRole> = Vec::<ProjectRole>::new();
+        while let Some(Ok(role)) = cursor.next().await {
+            roles.push(role);
+        }
+
+        Ok(roles)
+    }
     pub async fn delete_by_id(_id: &ObjectId) -> Result<u64, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectRole> = db.collection::<ProjectRole>("project-roles");
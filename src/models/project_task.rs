@@ -4,17 +4,35 @@ use actix_multipart::form::{tempfile::TempFile, MultipartForm};
 use async_recursion::async_recursion;
 use chrono::Utc;
 use futures::stream::StreamExt;
+use moka::sync::Cache;
 use mongodb::{
-    bson::{doc, from_document, oid::ObjectId, to_bson, DateTime, Document},
+    bson::{doc, from_document, oid::ObjectId, to_bson, to_document, DateTime, Document},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
+use std::{fs::remove_dir_all, sync::OnceLock, time::Duration};
 
 use super::{
-    project::{Project, ProjectAreaResponse, ProjectStatusKind},
+    count_matches, date_to_string,
+    project::{Project, ProjectAreaResponse, ProjectMemberKind, ProjectStatusKind},
+    project_incident_report::ProjectIncidentReportMinResponse,
+    status_to_string,
+    tombstone::{Tombstone, TombstoneKind},
     user::UserImage,
+    FileKind,
 };
 
+static DEPENDENCY_CACHE: OnceLock<Cache<ObjectId, Vec<ProjectTask>>> = OnceLock::new();
+
+fn dependency_cache() -> &'static Cache<ObjectId, Vec<ProjectTask>> {
+    DEPENDENCY_CACHE.get_or_init(|| {
+        Cache::builder()
+            .max_capacity(256)
+            .time_to_live(Duration::from_secs(5))
+            .build()
+    })
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ProjectTaskStatusKind {
@@ -44,6 +62,10 @@ pub struct ProjectTask {
     pub status: Vec<ProjectTaskStatus>,
     pub volume: Option<ProjectTaskVolume>,
     pub value: f64,
+    pub cost: Option<ProjectTaskCost>,
+    pub attachment: Option<Vec<ProjectTaskAttachment>>,
+    pub checklist: Option<Vec<ProjectTaskChecklist>>,
+    pub update_date: Option<DateTime>,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectTaskPeriod {
@@ -52,16 +74,39 @@ pub struct ProjectTaskPeriod {
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectTaskVolume {
-    pub value: usize,
+    pub value: f64,
     pub unit: String,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProjectTaskCost {
+    pub amount: f64,
+    pub currency: String,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProjectTaskAttachment {
+    pub _id: ObjectId,
+    pub name: Option<String>,
+    pub extension: String,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProjectTaskChecklist {
+    pub _id: ObjectId,
+    pub label: String,
+    pub done: bool,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProjectTaskStatus {
     pub kind: ProjectTaskStatusKind,
     pub time: DateTime,
     pub message: Option<String>,
 }
 #[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskStatusResponse {
+    pub kind: ProjectTaskStatusKind,
+    pub time: String,
+    pub message: Option<String>,
+}
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectTaskStatusRequest {
     pub kind: ProjectTaskStatusKind,
     pub message: Option<String>,
@@ -76,10 +121,26 @@ pub struct ProjectTaskResponse {
     pub name: String,
     pub description: Option<String>,
     pub period: Option<ProjectTaskPeriodResponse>,
-    pub status: Vec<ProjectTaskStatus>,
+    pub status: Vec<ProjectTaskStatusResponse>,
     pub volume: Option<ProjectTaskVolume>,
     pub value: f64,
+    pub cost: Option<ProjectTaskCost>,
+    pub effective_value: f64,
     pub progress: f64,
+    pub duration_days: Option<i64>,
+    #[serde(default)]
+    pub paused_days: f64,
+    pub attachment: Option<Vec<ProjectTaskAttachmentResponse>>,
+    pub checklist: Option<Vec<ProjectTaskChecklistResponse>>,
+    pub checklist_completion: Option<f64>,
+    #[serde(default)]
+    pub incident: Vec<ProjectIncidentReportMinResponse>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectTaskDeltaResponse {
+    pub timestamp: i64,
+    pub task: Vec<ProjectTaskMinResponse>,
+    pub deleted: Vec<String>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectTaskMinResponse {
@@ -92,17 +153,59 @@ pub struct ProjectTaskMinResponse {
     pub description: Option<String>,
     pub period: Option<ProjectTaskPeriodResponse>,
     pub actual: Option<ProjectTaskPeriodResponse>,
-    pub status: Vec<ProjectTaskStatus>,
+    pub status: Vec<ProjectTaskStatusResponse>,
     pub volume: Option<ProjectTaskVolume>,
     pub value: f64,
+    pub cost: Option<ProjectTaskCost>,
     pub progress: f64,
+    pub duration_days: Option<i64>,
+    #[serde(default)]
+    pub paused_days: f64,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskSupportResponse {
+    pub _id: String,
+    pub task_id: Option<String>,
+    pub area_id: String,
+    pub member: Vec<ProjectTaskSupportMemberResponse>,
+    pub name: String,
+    pub period: Option<ProjectTaskPeriodResponse>,
+    pub status: Vec<ProjectTaskStatusResponse>,
+    pub value: f64,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskNotStartedResponse {
+    pub _id: String,
+    pub task_id: Option<String>,
+    pub area_id: String,
+    pub name: String,
+    pub period: ProjectTaskPeriodResponse,
+    pub value: f64,
+    pub days_late: i64,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectTaskVarianceHistogramResponse {
+    pub total: usize,
+    pub bucket: Vec<ProjectTaskVarianceBucketResponse>,
+}
+#[derive(Debug, Serialize)]
+pub struct ProjectTaskVarianceBucketResponse {
+    pub label: String,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub count: usize,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskSupportMemberResponse {
+    pub _id: String,
+    pub name: Option<String>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectTaskTaskResponse {
     pub _id: String,
     pub name: String,
     pub period: Option<ProjectTaskPeriodResponse>,
-    pub status: Vec<ProjectTaskStatus>,
+    pub status: Vec<ProjectTaskStatusResponse>,
     pub volume: Option<ProjectTaskVolume>,
 }
 #[derive(Debug, Deserialize, Serialize)]
@@ -120,6 +223,38 @@ pub struct ProjectTaskProjectResponse {
 pub struct ProjectTaskAreaResponse {
     pub _id: String,
     pub name: String,
+    pub description: Option<String>,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskAttachmentResponse {
+    pub _id: String,
+    pub name: Option<String>,
+    pub extension: String,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskChecklistResponse {
+    pub _id: String,
+    pub label: String,
+    pub done: bool,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskBreadcrumbResponse {
+    pub area: ProjectTaskAreaResponse,
+    pub task: Vec<ProjectTaskBreadcrumbTaskResponse>,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskBreadcrumbTaskResponse {
+    pub _id: String,
+    pub name: String,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskTreeResponse {
+    pub _id: String,
+    pub task_id: Option<String>,
+    pub depth: usize,
+    pub value: f64,
+    pub effective_value: f64,
+    pub name: String,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectTaskPeriodResponse {
@@ -142,6 +277,9 @@ pub struct ProjectTaskTimelineQuery {
     pub status: Option<ProjectTaskStatusKind>,
     pub relative: bool,
     pub subtask: bool,
+    pub kind: Option<ProjectTaskQueryKind>,
+    pub user_id: Option<ObjectId>,
+    pub since: Option<DateTime>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -152,6 +290,37 @@ pub struct ProjectTaskRequest {
     pub description: Option<String>,
     pub volume: Option<ProjectTaskVolume>,
     pub value: f64,
+    pub cost: Option<ProjectTaskCostRequest>,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskCostRequest {
+    pub amount: f64,
+    pub currency: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectTaskChecklistRequest {
+    pub label: String,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectTaskChecklistUpdateRequest {
+    pub label: Option<String>,
+    pub done: Option<bool>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectTaskDuplicateRequest {
+    pub include_subtasks: bool,
+    pub name_suffix: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectTaskDeleteRequest {
+    pub task_ids: Vec<ObjectId>,
+    pub cascade: bool,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectTaskShiftRequest {
+    pub days: i64,
+    pub from_date: Option<i64>,
+    pub area_id: Option<ObjectId>,
 }
 #[derive(Debug, Deserialize)]
 pub struct ProjectTaskPeriodRequest {
@@ -163,8 +332,179 @@ pub struct ProjectTaskMultipartRequest {
     #[multipart(rename = "file")]
     pub file: TempFile,
 }
+#[derive(Debug, MultipartForm)]
+pub struct ProjectTaskAttachmentMultipartRequest {
+    #[multipart(rename = "file")]
+    pub files: Vec<TempFile>,
+}
+
+impl ProjectTaskCost {
+    pub fn base_currency() -> String {
+        std::env::var("BASE_CURRENCY").unwrap()
+    }
+    pub fn from_request(request: ProjectTaskCostRequest) -> Result<Self, String> {
+        if !request.amount.is_finite() || request.amount < 0.0 {
+            return Err("INVALID_COST".to_string());
+        }
+        let currency = match request.currency {
+            Some(currency) if !currency.trim().is_empty() => currency.trim().to_uppercase(),
+            _ => Self::base_currency(),
+        };
+        Ok(Self {
+            amount: request.amount,
+            currency,
+        })
+    }
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split_whitespace();
+        let amount = parts
+            .next()?
+            .parse::<f64>()
+            .ok()
+            .filter(|amount| amount.is_finite() && *amount >= 0.0)?;
+        let currency = match parts.next() {
+            Some(currency) => currency.to_uppercase(),
+            None => Self::base_currency(),
+        };
+        Some(Self { amount, currency })
+    }
+}
+
+impl ProjectTaskStatusKind {
+    pub fn can_transition_to(&self, next: &ProjectTaskStatusKind) -> bool {
+        matches!(
+            (self, next),
+            (
+                ProjectTaskStatusKind::Pending,
+                ProjectTaskStatusKind::Running
+            ) | (
+                ProjectTaskStatusKind::Running,
+                ProjectTaskStatusKind::Paused
+            ) | (
+                ProjectTaskStatusKind::Running,
+                ProjectTaskStatusKind::Finished
+            ) | (
+                ProjectTaskStatusKind::Paused,
+                ProjectTaskStatusKind::Running
+            )
+        )
+    }
+}
+
+impl ProjectTaskStatusResponse {
+    pub fn timestamp_millis(&self) -> Option<i64> {
+        chrono::DateTime::parse_from_rfc3339(&self.time)
+            .ok()
+            .map(|a| a.timestamp_millis())
+    }
+    pub fn paused_days(status: &[ProjectTaskStatusResponse]) -> f64 {
+        let now = Utc::now().timestamp_millis();
+        let paused = status
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.kind == ProjectTaskStatusKind::Paused)
+            .fold(0, |a, (index, b)| {
+                let end = match index {
+                    0 => now,
+                    _ => status[index - 1].timestamp_millis().unwrap_or(now),
+                };
+                a + (end - b.timestamp_millis().unwrap_or(end)).max(0)
+            });
+
+        paused as f64 / 86400000.0
+    }
+}
+
+impl ProjectTaskChecklist {
+    pub fn is_enforced() -> bool {
+        std::env::var("ENFORCE_CHECKLIST").unwrap() == "true"
+    }
+}
+
+impl ProjectTaskPeriod {
+    pub fn duration_days(&self) -> i64 {
+        (self.end.timestamp_millis() - self.start.timestamp_millis()) / 86400000 + 1
+    }
+}
+
+fn duration_days_projection() -> Document {
+    doc! {
+        "$cond": [
+            "$period",
+            {
+                "$add": [
+                    {
+                        "$toLong": {
+                            "$divide": [
+                                {
+                                    "$subtract": ["$period.end", "$period.start"]
+                                },
+                                86400000
+                            ]
+                        }
+                    },
+                    1
+                ]
+            },
+            to_bson::<Option<i64>>(&None).unwrap()
+        ]
+    }
+}
 
 impl ProjectTask {
+    pub fn effective_value(
+        value: f64,
+        task_id: Option<ObjectId>,
+        dependencies: &[ProjectTask],
+    ) -> f64 {
+        let mut value = value;
+        let mut _id = task_id;
+
+        while let Some(task_id) = _id {
+            if let Some(task) = dependencies.iter().find(|a| a._id == Some(task_id)) {
+                value *= task.value / 100.0;
+                _id = task.task_id;
+            } else {
+                break;
+            }
+        }
+
+        value
+    }
+    pub fn value_epsilon() -> f64 {
+        let precision = std::env::var("VALUE_PRECISION")
+            .ok()
+            .and_then(|a| a.parse::<i32>().ok())
+            .unwrap_or(3)
+            .clamp(0, 9);
+        10_f64.powi(-precision)
+    }
+    pub fn is_value_complete(sum: f64) -> bool {
+        // Allow for binary drift on top of the configured precision, e.g. 3 x 33.333
+        (sum - 100.0).abs() <= Self::value_epsilon() + 1e-9
+    }
+    pub fn current_status(&self) -> Result<&ProjectTaskStatus, String> {
+        self.status
+            .first()
+            .ok_or_else(|| "MISSING_STATUS".to_string())
+    }
+    pub fn find_depth(&self, tasks: &[ProjectTask]) -> usize {
+        let mut depth = 0;
+        let mut _id = self.task_id;
+
+        while let Some(task_id) = _id {
+            if depth >= tasks.len() {
+                break;
+            }
+            depth += 1;
+            _id = tasks
+                .iter()
+                .find(|a| a._id == Some(task_id))
+                .and_then(|a| a.task_id);
+        }
+
+        depth
+    }
     pub async fn save(&mut self) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
@@ -200,19 +540,25 @@ impl ProjectTask {
         .await
         {
             let total = task.iter().fold(0.0, |a, b| a + b.value);
-            if (100.0 - (total + self.value)).abs() <= 0.0011 {
+            if Self::is_value_complete(total + self.value) {
                 self.value = 100.0 - total;
             }
         }
 
+        self.update_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
+
         if let Ok(Some(project)) = Project::find_by_id(&self.project_id).await {
             if project.area.is_some() && project.area.unwrap().iter().any(|a| a._id == self.area_id)
             {
-                collection
-                    .insert_one(self, None)
+                let _id = collection
+                    .insert_one(&*self, None)
                     .await
                     .map_err(|_| "INSERTING_FAILED".to_string())
                     .map(|result| result.inserted_id.as_object_id().unwrap())
+                    .inspect(|_| Self::invalidate_dependencies(Some(&self.project_id)))?;
+                let _ = Project::clear_import_hash(&self.project_id).await;
+
+                Ok(_id)
             } else {
                 Err("PROJECT_AREA_NOT_FOUND".to_string())
             }
@@ -220,11 +566,20 @@ impl ProjectTask {
             Err("PROJECT_NOT_FOUND".to_string())
         }
     }
-    pub async fn save_bulk(tasks: Vec<Self>) -> Result<Vec<ObjectId>, String> {
+    pub async fn save_bulk(mut tasks: Vec<Self>) -> Result<Vec<ObjectId>, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
 
-        collection
+        let update_date = DateTime::from_millis(Utc::now().timestamp_millis());
+        let mut project_id = Vec::<ObjectId>::new();
+        for task in tasks.iter_mut() {
+            task.update_date = Some(update_date);
+            if !project_id.contains(&task.project_id) {
+                project_id.push(task.project_id);
+            }
+        }
+
+        let task_id = collection
             .insert_many(tasks, None)
             .await
             .map_err(|_| "INSERTING_FAILED".to_string())
@@ -234,48 +589,126 @@ impl ProjectTask {
                     task_id.push(_id.as_object_id().unwrap());
                 }
                 task_id
+            })?;
+        for _id in project_id.iter() {
+            Self::invalidate_dependencies(Some(_id));
+            let _ = Project::clear_import_hash(_id).await;
+        }
+
+        Ok(task_id)
+    }
+    pub async fn duplicate(
+        &self,
+        include_subtasks: bool,
+        name_suffix: &str,
+    ) -> Result<Vec<ObjectId>, String> {
+        let status = vec![ProjectTaskStatus {
+            kind: ProjectTaskStatusKind::Pending,
+            time: DateTime::from_millis(Utc::now().timestamp_millis()),
+            message: None,
+        }];
+
+        let mut task = self.clone();
+        task._id = Some(ObjectId::new());
+        task.name.push_str(name_suffix);
+        task.status = status.clone();
+        task.attachment = None;
+        for item in task.checklist.iter_mut().flatten() {
+            item.done = false;
+        }
+
+        let mut queue = vec![(self._id.unwrap(), task._id.unwrap())];
+        let mut tasks = vec![task];
+
+        while include_subtasks && !queue.is_empty() {
+            let (task_id, new_task_id) = queue.pop().unwrap();
+            if let Some(sub_tasks) = Self::find_many(&ProjectTaskQuery {
+                _id: None,
+                project_id: Some(self.project_id),
+                task_id: Some(task_id),
+                area_id: None,
+                limit: None,
+                kind: None,
             })
+            .await?
+            {
+                for mut sub_task in sub_tasks {
+                    let _id = ObjectId::new();
+                    queue.push((sub_task._id.unwrap(), _id));
+                    sub_task._id = Some(_id);
+                    sub_task.task_id = Some(new_task_id);
+                    sub_task.status = status.clone();
+                    sub_task.attachment = None;
+                    for item in sub_task.checklist.iter_mut().flatten() {
+                        item.done = false;
+                    }
+                    tasks.push(sub_task);
+                }
+            }
+        }
+
+        Self::save_bulk(tasks).await
     }
     pub async fn update(&self) -> Result<ObjectId, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
 
+        let mut task: Document = to_document::<ProjectTask>(self).unwrap();
+        task.insert(
+            "update_date",
+            DateTime::from_millis(Utc::now().timestamp_millis()),
+        );
+
         collection
             .update_one(
                 doc! { "_id": self._id.unwrap() },
-                doc! { "$set": to_bson::<ProjectTask>(self).unwrap()},
+                doc! { "$set": task },
                 None,
             )
             .await
             .map_err(|_| "UPDATE_FAILED".to_string())
-            .map(|_| self._id.unwrap())
+            .inspect(|_| Self::invalidate_dependencies(Some(&self.project_id)))?;
+        let _ = Project::clear_import_hash(&self.project_id).await;
+
+        Ok(self._id.unwrap())
     }
-    pub async fn update_period(&mut self, period: ProjectTaskPeriod) -> Result<ObjectId, String> {
+    pub async fn update_many_area_id(
+        project_id: &ObjectId,
+        area_id: &ObjectId,
+        new_area_id: &ObjectId,
+    ) -> Result<u64, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
 
-        let tasks = Self::find_many(&ProjectTaskQuery {
-            _id: None,
-            project_id: Some(self.project_id),
-            task_id: None,
-            area_id: None,
-            limit: None,
-            kind: None,
-        })
-        .await
-        .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())?
-        .ok_or("PROJECT_TASK_NOT_FOUND")?;
-        let mut task_id: Vec<ObjectId> = Vec::new();
+        let modified = collection
+            .update_many(
+                doc! { "project_id": project_id, "area_id": area_id },
+                doc! {
+                    "$set": {
+                        "area_id": new_area_id,
+                        "update_date": DateTime::from_millis(Utc::now().timestamp_millis())
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|_| "UPDATE_FAILED".to_string())
+            .map(|result| result.modified_count)
+            .inspect(|_| Self::invalidate_dependencies(Some(project_id)))?;
+        let _ = Project::clear_import_hash(project_id).await;
 
-        for task in tasks.iter() {
-            if let Some(_id) = task.task_id {
-                if !task_id.contains(&_id) {
-                    task_id.push(_id);
-                }
-            }
-        }
+        Ok(modified)
+    }
+    pub async fn update_period(&mut self, period: ProjectTaskPeriod) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let sub_task_count = collection
+            .count_documents(doc! { "task_id": self._id.unwrap() }, None)
+            .await
+            .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())?;
 
-        if task_id.contains(&self._id.unwrap()) {
+        if sub_task_count > 0 {
             return Err("PROJECT_TASK_DEPENDENCY".to_string());
         }
 
@@ -284,6 +717,7 @@ impl ProjectTask {
         }
 
         self.period = Some(period);
+        self.update_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
 
         collection
             .update_one(
@@ -293,17 +727,38 @@ impl ProjectTask {
             )
             .await
             .map_err(|_| "UPDATE_FAILED".to_string())
-            .map(|_| self._id.unwrap())
+            .inspect(|_| Self::invalidate_dependencies(Some(&self.project_id)))?;
+        let _ = Project::clear_import_hash(&self.project_id).await;
+
+        Ok(self._id.unwrap())
     }
-    #[async_recursion]
     pub async fn update_status(
         &mut self,
         status: ProjectTaskStatusKind,
         message: Option<String>,
+    ) -> Result<ObjectId, String> {
+        if !self.current_status()?.kind.can_transition_to(&status) {
+            return Err("INVALID_TASK_TRANSITION".to_string());
+        }
+
+        self.apply_status(status, message).await
+    }
+    #[async_recursion]
+    async fn apply_status(
+        &mut self,
+        status: ProjectTaskStatusKind,
+        message: Option<String>,
     ) -> Result<ObjectId, String> {
         let db = get_db();
         let collection = db.collection::<ProjectTask>("project-tasks");
 
+        if status == ProjectTaskStatusKind::Finished
+            && ProjectTaskChecklist::is_enforced()
+            && self.checklist.iter().flatten().any(|a| !a.done)
+        {
+            return Err("CHECKLIST_INCOMPLETE".to_string());
+        }
+
         self.status.insert(
             0,
             ProjectTaskStatus {
@@ -312,6 +767,7 @@ impl ProjectTask {
                 message,
             },
         );
+        self.update_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
 
         let mut finished_parent_task = None;
         if status == ProjectTaskStatusKind::Finished {
@@ -329,7 +785,9 @@ impl ProjectTask {
 
                 if tasks.iter().all(|task| {
                     task._id == self._id
-                        || task.status.get(0).unwrap().kind == ProjectTaskStatusKind::Finished
+                        || task
+                            .current_status()
+                            .is_ok_and(|a| a.kind == ProjectTaskStatusKind::Finished)
                 }) {
                     finished_parent_task = self.task_id;
                 } else {
@@ -349,7 +807,9 @@ impl ProjectTask {
 
                 if tasks.iter().all(|task| {
                     task._id == self._id
-                        || task.status.first().unwrap().kind == ProjectTaskStatusKind::Finished
+                        || task
+                            .current_status()
+                            .is_ok_and(|a| a.kind == ProjectTaskStatusKind::Finished)
                 }) {
                     let mut project = Project::find_by_id(&self.project_id)
                         .await?
@@ -370,13 +830,19 @@ impl ProjectTask {
             )
             .await
             .map_err(|_| "UPDATE_FAILED".to_string())?;
+        Self::invalidate_dependencies(Some(&self.project_id));
 
         if let Some(finished_parent_task) = finished_parent_task {
-            Self::find_by_id(&finished_parent_task)
+            // A parent with open checklist items stays running until they are done
+            match Self::find_by_id(&finished_parent_task)
                 .await?
                 .ok_or_else(|| "PROJECT_TASK_NOT_FOUND".to_string())?
-                .update_status(ProjectTaskStatusKind::Finished, None)
+                .apply_status(ProjectTaskStatusKind::Finished, None)
                 .await
+            {
+                Err(error) if error == "CHECKLIST_INCOMPLETE" => Ok(self._id.unwrap()),
+                result => result,
+            }
         } else {
             Ok(self._id.unwrap())
         }
@@ -397,10 +863,21 @@ impl ProjectTask {
         .await?
         .map_or_else(Vec::<ProjectTask>::new, |val| val);
 
-        let mut deleted = match collection.delete_one(doc! { "_id": _id }, None).await {
-            Ok(result) => result.deleted_count,
+        let mut deleted = match collection
+            .find_one_and_delete(doc! { "_id": _id }, None)
+            .await
+        {
+            Ok(Some(task)) => {
+                let _ = Tombstone::save_many(TombstoneKind::Task, &task.project_id, &[*_id]).await;
+                let _ = Project::clear_import_hash(&task.project_id).await;
+                1
+            }
+            Ok(None) => 0,
             Err(_) => return Err("PROJECT_TASK_NOT_FOUND".to_string()),
         };
+        Self::invalidate_dependencies(None);
+
+        let _ = remove_dir_all(FileKind::TaskAttachment.path(format!("{}/", _id)));
 
         for task in tasks.iter() {
             deleted += Self::delete_by_id(&task._id.unwrap())
@@ -410,25 +887,107 @@ impl ProjectTask {
 
         Ok(deleted)
     }
-    pub async fn delete_many_by_project_id(_id: &ObjectId) -> Result<u64, String> {
+    pub async fn delete_tree(_id: &ObjectId) -> Result<Vec<ObjectId>, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
 
+        let project_id = Self::find_by_id(_id)
+            .await?
+            .ok_or_else(|| "PROJECT_TASK_NOT_FOUND".to_string())?
+            .project_id;
+        let mut task_id: Vec<ObjectId> = vec![*_id];
+        let mut queue: Vec<ObjectId> = vec![*_id];
+
+        while !queue.is_empty() {
+            let mut cursor = collection
+                .find(
+                    doc! { "task_id": { "$in": to_bson::<Vec<ObjectId>>(&queue).unwrap() } },
+                    None,
+                )
+                .await
+                .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())?;
+            queue.clear();
+            while let Some(Ok(task)) = cursor.next().await {
+                if let Some(_id) = task._id {
+                    if !task_id.contains(&_id) {
+                        task_id.push(_id);
+                        queue.push(_id);
+                    }
+                }
+            }
+        }
+
         collection
+            .delete_many(
+                doc! { "_id": { "$in": to_bson::<Vec<ObjectId>>(&task_id).unwrap() } },
+                None,
+            )
+            .await
+            .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())?;
+        Self::invalidate_dependencies(None);
+        let _ = Tombstone::save_many(TombstoneKind::Task, &project_id, &task_id).await;
+        let _ = Project::clear_import_hash(&project_id).await;
+
+        for _id in task_id.iter() {
+            let _ = remove_dir_all(FileKind::TaskAttachment.path(format!("{}/", _id)));
+        }
+
+        Ok(task_id)
+    }
+    pub async fn delete_many_by_project_id(_id: &ObjectId) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let mut task_id: Vec<ObjectId> = Vec::<ObjectId>::new();
+        if let Ok(mut cursor) = collection.find(doc! { "project_id": _id }, None).await {
+            while let Some(Ok(task)) = cursor.next().await {
+                task_id.extend(task._id);
+            }
+        }
+
+        let deleted = collection
             .delete_many(doc! { "project_id": _id }, None)
             .await
             .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())
             .map(|result| result.deleted_count)
+            .inspect(|_| Self::invalidate_dependencies(Some(_id)))?;
+        let _ = Tombstone::save_many(TombstoneKind::Task, _id, &task_id).await;
+
+        for _id in task_id.iter() {
+            let _ = remove_dir_all(FileKind::TaskAttachment.path(format!("{}/", _id)));
+        }
+
+        Ok(deleted)
     }
     pub async fn delete_many_by_area_id(_id: &ObjectId) -> Result<u64, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
 
-        collection
+        let mut project_id: Option<ObjectId> = None;
+        let mut task_id: Vec<ObjectId> = Vec::<ObjectId>::new();
+        if let Ok(mut cursor) = collection.find(doc! { "area_id": _id }, None).await {
+            while let Some(Ok(task)) = cursor.next().await {
+                project_id = Some(task.project_id);
+                task_id.extend(task._id);
+            }
+        }
+
+        let deleted = collection
             .delete_many(doc! { "area_id": _id }, None)
             .await
             .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())
             .map(|result| result.deleted_count)
+            .inspect(|_| Self::invalidate_dependencies(None))?;
+
+        if let Some(project_id) = project_id {
+            let _ = Tombstone::save_many(TombstoneKind::Task, &project_id, &task_id).await;
+            let _ = Project::clear_import_hash(&project_id).await;
+        }
+        for _id in task_id.iter() {
+            let _ = remove_dir_all(FileKind::TaskAttachment.path(format!("{}/", _id)));
+        }
+
+        Ok(deleted)
     }
     pub async fn delete_many_by_task_id(_id: &ObjectId) -> Result<u64, String> {
         let tasks = Self::find_many(&ProjectTaskQuery {
@@ -452,6 +1011,31 @@ impl ProjectTask {
 
         Ok(deleted)
     }
+    pub fn invalidate_dependencies(project_id: Option<&ObjectId>) {
+        match project_id {
+            Some(project_id) => dependency_cache().invalidate(project_id),
+            None => dependency_cache().invalidate_all(),
+        }
+    }
+    pub async fn find_dependencies(project_id: &ObjectId) -> Result<Vec<ProjectTask>, String> {
+        if let Some(tasks) = dependency_cache().get(project_id) {
+            return Ok(tasks);
+        }
+
+        let tasks = Self::find_many(&ProjectTaskQuery {
+            _id: None,
+            project_id: Some(*project_id),
+            task_id: None,
+            area_id: None,
+            limit: None,
+            kind: Some(ProjectTaskQueryKind::Dependency),
+        })
+        .await?
+        .unwrap_or_default();
+        dependency_cache().insert(*project_id, tasks.clone());
+
+        Ok(tasks)
+    }
     pub async fn find_many(query: &ProjectTaskQuery) -> Result<Option<Vec<ProjectTask>>, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
@@ -562,26 +1146,14 @@ impl ProjectTask {
             Err("PROJECT_TASK_NOT_FOUND".to_string())
         }
     }
-    pub async fn find_many_timeline(
+    async fn find_many_timeline_queries(
         query: &ProjectTaskTimelineQuery,
-    ) -> Result<Option<Vec<ProjectTaskMinResponse>>, String> {
-        let db: Database = get_db();
-        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
-
+    ) -> (Vec<Document>, Vec<ProjectTask>) {
         let mut dependencies: Vec<ProjectTask> = Vec::new();
         let mut task_id: Vec<ObjectId> = Vec::new();
 
-        if !query.relative {
-            if let Ok(Some(tasks)) = Self::find_many(&ProjectTaskQuery {
-                _id: None,
-                project_id: Some(query.project_id),
-                task_id: None,
-                area_id: None,
-                limit: None,
-                kind: Some(ProjectTaskQueryKind::Dependency),
-            })
-            .await
-            {
+        if !query.relative || query.kind.is_some() {
+            if let Ok(tasks) = Self::find_dependencies(&query.project_id).await {
                 dependencies = tasks;
                 for task in dependencies.iter() {
                     if !task_id.contains(&task._id.unwrap()) {
@@ -591,20 +1163,27 @@ impl ProjectTask {
             }
         }
 
-        let mut pipeline: Vec<Document> = Vec::<Document>::new();
         let mut queries: Vec<Document> = Vec::<Document>::new();
 
         queries.push(doc! {
             "$eq": [ "$project_id", to_bson::<ObjectId>(&query.project_id).unwrap() ]
         });
-        queries.push(doc! {
-            "$ne": [
-                {
-                    "$in": ["$_id", to_bson::<Vec<ObjectId>>(&task_id).unwrap()]
-                },
-                to_bson::<bool>(&true).unwrap()
-            ]
-        });
+        match query.kind {
+            Some(ProjectTaskQueryKind::Root) => queries.push(doc! {
+                "$eq": [ "$task_id", to_bson::<Option<ObjectId>>(&None).unwrap() ]
+            }),
+            Some(ProjectTaskQueryKind::Dependency) => queries.push(doc! {
+                "$in": ["$_id", to_bson::<Vec<ObjectId>>(&task_id).unwrap()]
+            }),
+            _ => queries.push(doc! {
+                "$ne": [
+                    {
+                        "$in": ["$_id", to_bson::<Vec<ObjectId>>(&task_id).unwrap()]
+                    },
+                    to_bson::<bool>(&true).unwrap()
+                ]
+            }),
+        }
         if let Some(_id) = query.area_id {
             queries.push(doc! {
                 "$eq": [ "$area_id", to_bson::<ObjectId>(&_id).unwrap() ]
@@ -615,6 +1194,11 @@ impl ProjectTask {
                 "$eq": [ "$task_id", to_bson::<ObjectId>(&_id).unwrap() ]
             });
         }
+        if let Some(_id) = query.user_id {
+            queries.push(doc! {
+                "$in": [ to_bson::<ObjectId>(&_id).unwrap(), { "$ifNull": ["$user_id", []] } ]
+            });
+        }
         if let Some(status) = query.status.clone() {
             queries.push(doc! {
                 "$ne": [
@@ -625,6 +1209,29 @@ impl ProjectTask {
                 ]
             });
         }
+        if let Some(since) = query.since {
+            queries.push(doc! {
+                "$gt": [ "$update_date", since ]
+            });
+        }
+
+        (queries, dependencies)
+    }
+    pub async fn count_timeline(query: &ProjectTaskTimelineQuery) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let (queries, _) = Self::find_many_timeline_queries(query).await;
+        count_matches(&collection, queries).await
+    }
+    pub async fn find_many_timeline(
+        query: &ProjectTaskTimelineQuery,
+    ) -> Result<Option<Vec<ProjectTaskMinResponse>>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let (queries, dependencies) = Self::find_many_timeline_queries(query).await;
+        let mut pipeline: Vec<Document> = Vec::<Document>::new();
 
         pipeline.push(doc! {
             "$match": {
@@ -739,17 +1346,13 @@ impl ProjectTask {
                                     "$cond": [
                                         "$period",
                                         {
-                                            "start": {
-                                                "$toString": "$period.start"
-                                            },
-                                            "end": {
-                                                "$toString": "$period.end"
-                                            },
+                                            "start": date_to_string("$period.start"),
+                                            "end": date_to_string("$period.end"),
                                         },
                                         to_bson::<Option<ObjectId>>(&None).unwrap()
                                     ]
                                 },
-                                "status": "$status",
+                                "status": status_to_string("$status"),
                                 "volume": "$volume",
                             }
                         }
@@ -783,12 +1386,8 @@ impl ProjectTask {
                     "$cond": [
                         "$period",
                         {
-                            "start": {
-                                "$toString": "$period.start"
-                            },
-                            "end": {
-                                "$toString": "$period.end"
-                            },
+                            "start": date_to_string("$period.start"),
+                            "end": date_to_string("$period.end"),
                         },
                         to_bson::<Option<ObjectId>>(&None).unwrap()
                     ]
@@ -804,13 +1403,7 @@ impl ProjectTask {
                             ]
                         },
                         {
-                            "start": {
-                                "$toString": {
-                                    "$toDate": {
-                                        "$first": "$actual.start"
-                                    }
-                                }
-                            },
+                            "start": date_to_string(doc! { "$toDate": { "$first": "$actual.start" } }),
                             "end": {
                                 "$cond": [
                                     {
@@ -833,27 +1426,18 @@ impl ProjectTask {
                                             },
                                         ]
                                     },
-                                    {
-                                        "$toString": {
-                                            "$toDate": Utc::now().timestamp_millis()
-                                        }
-                                    },
-                                    {
-                                        "$toString": {
-                                            "$toDate": {
-                                                "$first": "$actual.end"
-                                            }
-                                        }
-                                    }
+                                    date_to_string(doc! { "$toDate": Utc::now().timestamp_millis() }),
+                                    date_to_string(doc! { "$toDate": { "$first": "$actual.end" } })
                                 ]
                             },
                         },
                         to_bson::<Option<ObjectId>>(&None).unwrap()
                     ]
                 },
-                "status": "$status",
+                "status": status_to_string("$status"),
                 "volume": "$volume",
                 "value": "$value",
+                "cost": "$cost",
                 "progress": {
                     "$cond": [
                         {
@@ -867,7 +1451,8 @@ impl ProjectTask {
                         },
                         0.0
                     ]
-                }
+                },
+                "duration_days": duration_days_projection()
             }
         });
         pipeline.push(doc! {
@@ -880,30 +1465,20 @@ impl ProjectTask {
         if let Ok(mut cursor) = collection.aggregate(pipeline, None).await {
             let mut tasks: Vec<ProjectTaskMinResponse> = Vec::<ProjectTaskMinResponse>::new();
             while let Some(Ok(doc)) = cursor.next().await {
-                let task: ProjectTaskMinResponse =
+                let mut task: ProjectTaskMinResponse =
                     from_document::<ProjectTaskMinResponse>(doc).unwrap();
+                task.paused_days = ProjectTaskStatusResponse::paused_days(&task.status);
                 tasks.push(task);
             }
             if !tasks.is_empty() {
-                if !dependencies.is_empty() {
+                if !query.relative && !dependencies.is_empty() {
                     for task in tasks.iter_mut() {
                         if let Some(task_id) = &task.task_id {
-                            let mut _id = Some(task_id.clone().parse::<ObjectId>().unwrap());
-                            let mut found = true;
-                            while found {
-                                if let Some(task_id) = _id {
-                                    if let Some(index) =
-                                        dependencies.iter().position(|a| a._id.unwrap() == task_id)
-                                    {
-                                        task.value *= dependencies[index].value / 100.0;
-                                        _id = dependencies[index].task_id;
-                                    } else {
-                                        found = false;
-                                    }
-                                } else {
-                                    found = false;
-                                }
-                            }
+                            task.value = Self::effective_value(
+                                task.value,
+                                task_id.parse::<ObjectId>().ok(),
+                                &dependencies,
+                            );
                         }
                     }
                 }
@@ -1033,17 +1608,13 @@ impl ProjectTask {
                                                 "$cond": [
                                                     "$period",
                                                     {
-                                                        "start": {
-                                                            "$toString": "$period.start"
-                                                        },
-                                                        "end": {
-                                                            "$toString": "$period.end"
-                                                        },
+                                                        "start": date_to_string("$period.start"),
+                                                        "end": date_to_string("$period.end"),
                                                     },
                                                     to_bson::<Option<ObjectId>>(&None).unwrap()
                                                 ]
                                             },
-                                            "status": "$status",
+                                            "status": status_to_string("$status"),
                                             "volume": "$volume",
                                         }
                                     }
@@ -1140,19 +1711,16 @@ impl ProjectTask {
                                     "$cond": [
                                         "$period",
                                         {
-                                            "start": {
-                                                "$toString": "$period.start"
-                                            },
-                                            "end": {
-                                                "$toString": "$period.end"
-                                            },
+                                            "start": date_to_string("$period.start"),
+                                            "end": date_to_string("$period.end"),
                                         },
                                         to_bson::<Option<ObjectId>>(&None).unwrap()
                                     ]
                                 },
-                                "status": "$status",
+                                "status": status_to_string("$status"),
                                 "volume": "$volume",
                                 "value": "$value",
+                                "cost": "$cost",
                                 "progress": {
                                     "$cond": [
                                         {
@@ -1166,7 +1734,8 @@ impl ProjectTask {
                                         },
                                         0.0
                                     ]
-                                }
+                                },
+                                "duration_days": duration_days_projection()
                             }
                         },
                     ]
@@ -1182,6 +1751,7 @@ impl ProjectTask {
                                     "$toString": "$$this._id"
                                 },
                                 "name": "$$this.name",
+                                "description": "$$this.description",
                                 "task": {
                                     "$filter": {
                                         "input": "$tasks",
@@ -1208,6 +1778,7 @@ impl ProjectTask {
                 "$project": {
                     "_id": "$area._id",
                     "name": "$area.name",
+                    "description": "$area.description",
                     "task": "$area.task",
                 }
             },
@@ -1217,8 +1788,11 @@ impl ProjectTask {
         match collection.aggregate(pipeline, None).await {
             Ok(mut cursor) => {
                 while let Some(Ok(doc)) = cursor.next().await {
-                    let area: ProjectAreaResponse =
+                    let mut area: ProjectAreaResponse =
                         from_document::<ProjectAreaResponse>(doc).unwrap();
+                    for task in area.task.iter_mut().flatten() {
+                        task.paused_days = ProjectTaskStatusResponse::paused_days(&task.status);
+                    }
                     areas.push(area);
                 }
                 if !areas.is_empty() {
@@ -1230,6 +1804,367 @@ impl ProjectTask {
             Err(_) => Err("PROJECT_TASK_NOT_FOUND".to_string()),
         }
     }
+    pub async fn find_many_support(
+        project: &Project,
+    ) -> Result<Vec<ProjectTaskSupportResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let member: Vec<Document> = project
+            .member
+            .iter()
+            .flatten()
+            .filter(|a| matches!(a.kind, ProjectMemberKind::Support))
+            .map(|a| {
+                doc! {
+                    "_id": a._id,
+                    "name": to_bson::<Option<String>>(&a.name).unwrap()
+                }
+            })
+            .collect();
+        if member.is_empty() {
+            return Ok(Vec::new());
+        }
+        let member_id: Vec<ObjectId> = member
+            .iter()
+            .filter_map(|a| a.get_object_id("_id").ok())
+            .collect();
+
+        let pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$and": [
+                            { "$eq": ["$project_id", to_bson::<Option<ObjectId>>(&project._id).unwrap()] },
+                            {
+                                "$gt": [
+                                    {
+                                        "$size": {
+                                            "$setIntersection": [
+                                                { "$ifNull": ["$user_id", []] },
+                                                to_bson::<Vec<ObjectId>>(&member_id).unwrap()
+                                            ]
+                                        }
+                                    },
+                                    0
+                                ]
+                            }
+                        ]
+                    }
+                }
+            },
+            doc! {
+                "$sort": {
+                    "period.start": 1,
+                    "name": 1
+                }
+            },
+            doc! {
+                "$project": {
+                    "_id": {
+                        "$toString": "$_id"
+                    },
+                    "task_id": {
+                        "$cond": [
+                            "$task_id",
+                            {
+                                "$toString": "$task_id"
+                            },
+                            to_bson::<Option<String>>(&None).unwrap()
+                        ]
+                    },
+                    "area_id": {
+                        "$toString": "$area_id"
+                    },
+                    "member": {
+                        "$map": {
+                            "input": {
+                                "$filter": {
+                                    "input": member,
+                                    "cond": { "$in": ["$$this._id", "$user_id"] }
+                                }
+                            },
+                            "in": {
+                                "_id": { "$toString": "$$this._id" },
+                                "name": "$$this.name"
+                            }
+                        }
+                    },
+                    "name": "$name",
+                    "period": {
+                        "$cond": [
+                            "$period",
+                            {
+                                "start": date_to_string("$period.start"),
+                                "end": date_to_string("$period.end"),
+                            },
+                            to_bson::<Option<ObjectId>>(&None).unwrap()
+                        ]
+                    },
+                    "status": status_to_string("$status"),
+                    "value": "$value"
+                }
+            },
+        ];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut tasks = Vec::<ProjectTaskSupportResponse>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    tasks.push(from_document::<ProjectTaskSupportResponse>(doc).unwrap());
+                }
+                Ok(tasks)
+            }
+            Err(_) => Err("PROJECT_TASK_NOT_FOUND".to_string()),
+        }
+    }
+    pub async fn find_variance_histogram(
+        project_id: &ObjectId,
+    ) -> Result<ProjectTaskVarianceHistogramResponse, String> {
+        let tasks = Self::find_many_timeline(&ProjectTaskTimelineQuery {
+            project_id: *project_id,
+            area_id: None,
+            task_id: None,
+            status: None,
+            relative: true,
+            subtask: false,
+            kind: Some(ProjectTaskQueryKind::Base),
+            user_id: None,
+            since: None,
+        })
+        .await?
+        .unwrap_or_default();
+
+        let mut bucket: Vec<ProjectTaskVarianceBucketResponse> = [
+            ("on_time", None, Some(0)),
+            ("1-3", Some(1), Some(3)),
+            ("4-7", Some(4), Some(7)),
+            ("8+", Some(8), None),
+        ]
+        .into_iter()
+        .map(|(label, min, max)| ProjectTaskVarianceBucketResponse {
+            label: label.to_string(),
+            min,
+            max,
+            count: 0,
+        })
+        .collect();
+        let mut total = 0;
+
+        // Only finished tasks have a settled actual end to compare against
+        for task in tasks.iter().filter(|a| {
+            a.status
+                .first()
+                .is_some_and(|status| status.kind == ProjectTaskStatusKind::Finished)
+        }) {
+            let (planned, finished) = match (&task.period, &task.actual) {
+                (Some(period), Some(actual)) => (
+                    chrono::DateTime::parse_from_rfc3339(&period.end),
+                    chrono::DateTime::parse_from_rfc3339(&actual.end),
+                ),
+                _ => continue,
+            };
+            let (planned, finished) = match (planned, finished) {
+                (Ok(planned), Ok(finished)) => (planned, finished),
+                _ => continue,
+            };
+
+            let days_late = (finished.date_naive() - planned.date_naive()).num_days();
+            if let Some(bucket) = bucket.iter_mut().find(|a| {
+                a.min.is_none_or(|min| days_late >= min) && a.max.is_none_or(|max| days_late <= max)
+            }) {
+                bucket.count += 1;
+                total += 1;
+            }
+        }
+
+        Ok(ProjectTaskVarianceHistogramResponse { total, bucket })
+    }
+    pub async fn find_many_not_started(
+        project_id: &ObjectId,
+    ) -> Result<Vec<ProjectTaskNotStartedResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let task_id: Vec<ObjectId> = Self::find_dependencies(project_id)
+            .await?
+            .into_iter()
+            .filter_map(|a| a._id)
+            .collect();
+        let now = DateTime::now();
+
+        let pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$and": [
+                            { "$eq": ["$project_id", to_bson::<ObjectId>(project_id).unwrap()] },
+                            { "$not": [{ "$in": ["$_id", to_bson::<Vec<ObjectId>>(&task_id).unwrap()] }] },
+                            { "$eq": [{ "$type": "$period.start" }, "date"] },
+                            { "$lt": ["$period.start", now] },
+                            { "$eq": [{ "$first": "$status.kind" }, "pending"] }
+                        ]
+                    }
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "project-reports",
+                    "as": "report",
+                    "let": {
+                        "project_id": "$project_id",
+                        "task_id": "$_id"
+                    },
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$and": [
+                                        { "$eq": ["$project_id", "$$project_id"] },
+                                        { "$in": ["$$task_id", { "$ifNull": ["$actual.task_id", []] }] }
+                                    ]
+                                }
+                            }
+                        },
+                        {
+                            "$limit": 1
+                        }
+                    ]
+                }
+            },
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$eq": [{ "$size": "$report" }, 0]
+                    }
+                }
+            },
+            doc! {
+                "$sort": {
+                    "period.start": 1,
+                    "_id": 1
+                }
+            },
+            doc! {
+                "$project": {
+                    "_id": {
+                        "$toString": "$_id"
+                    },
+                    "task_id": {
+                        "$cond": [
+                            "$task_id",
+                            {
+                                "$toString": "$task_id"
+                            },
+                            to_bson::<Option<String>>(&None).unwrap()
+                        ]
+                    },
+                    "area_id": {
+                        "$toString": "$area_id"
+                    },
+                    "name": "$name",
+                    "period": {
+                        "start": date_to_string("$period.start"),
+                        "end": date_to_string("$period.end"),
+                    },
+                    "value": "$value",
+                    "days_late": {
+                        "$toLong": {
+                            "$divide": [
+                                {
+                                    "$subtract": [now, "$period.start"]
+                                },
+                                86400000
+                            ]
+                        }
+                    }
+                }
+            },
+        ];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut tasks = Vec::<ProjectTaskNotStartedResponse>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    tasks.push(from_document::<ProjectTaskNotStartedResponse>(doc).unwrap());
+                }
+                Ok(tasks)
+            }
+            Err(_) => Err("PROJECT_TASK_NOT_FOUND".to_string()),
+        }
+    }
+    pub async fn find_many_area_id(project_id: &ObjectId) -> Result<Vec<ObjectId>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$eq": ["$project_id", to_bson::<ObjectId>(project_id).unwrap()]
+                    }
+                }
+            },
+            doc! {
+                "$group": {
+                    "_id": "$area_id"
+                }
+            },
+        ];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut area_id = Vec::<ObjectId>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    if let Ok(_id) = doc.get_object_id("_id") {
+                        area_id.push(_id);
+                    }
+                }
+                Ok(area_id)
+            }
+            Err(_) => Err("PROJECT_TASK_NOT_FOUND".to_string()),
+        }
+    }
+    pub async fn find_many_unit(project_id: &ObjectId) -> Result<Vec<String>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
+
+        let pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$and": [
+                            { "$eq": ["$project_id", to_bson::<ObjectId>(project_id).unwrap()] },
+                            { "$eq": [{ "$type": "$volume.unit" }, "string"] }
+                        ]
+                    }
+                }
+            },
+            doc! {
+                "$group": {
+                    "_id": "$volume.unit"
+                }
+            },
+            doc! {
+                "$sort": {
+                    "_id": 1
+                }
+            },
+        ];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut units = Vec::<String>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    if let Ok(unit) = doc.get_str("_id") {
+                        units.push(unit.to_string());
+                    }
+                }
+                Ok(units)
+            }
+            Err(_) => Err("PROJECT_TASK_NOT_FOUND".to_string()),
+        }
+    }
     pub async fn find_by_id(_id: &ObjectId) -> Result<Option<ProjectTask>, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
@@ -1239,6 +2174,62 @@ impl ProjectTask {
             .await
             .map_err(|_| "PROJECT_TASK_NOT_FOUND".to_string())
     }
+    pub async fn find_ancestors(&self) -> Result<Vec<ProjectTask>, String> {
+        let mut tasks = Vec::<ProjectTask>::new();
+        let mut _id = self.task_id;
+
+        while let Some(task_id) = _id {
+            let task = Self::find_by_id(&task_id)
+                .await?
+                .ok_or_else(|| "PROJECT_TASK_NOT_FOUND".to_string())?;
+            _id = task.task_id;
+            tasks.insert(0, task);
+        }
+
+        Ok(tasks)
+    }
+    pub async fn find_many_tree(
+        project_id: &ObjectId,
+    ) -> Result<Vec<ProjectTaskTreeResponse>, String> {
+        let tasks = Self::find_many(&ProjectTaskQuery {
+            _id: None,
+            project_id: Some(*project_id),
+            task_id: None,
+            area_id: None,
+            limit: None,
+            kind: None,
+        })
+        .await?
+        .unwrap_or_default();
+
+        let mut tree = Vec::<ProjectTaskTreeResponse>::new();
+        let mut stack = tasks
+            .iter()
+            .filter(|a| a.task_id.is_none())
+            .rev()
+            .map(|a| (a, 0))
+            .collect::<Vec<(&ProjectTask, usize)>>();
+
+        while let Some((task, depth)) = stack.pop() {
+            tree.push(ProjectTaskTreeResponse {
+                _id: task._id.unwrap().to_string(),
+                task_id: task.task_id.map(|a| a.to_string()),
+                depth,
+                value: task.value,
+                effective_value: Self::effective_value(task.value, task.task_id, &tasks),
+                name: task.name.clone(),
+            });
+            stack.extend(
+                tasks
+                    .iter()
+                    .filter(|a| a.task_id == task._id)
+                    .rev()
+                    .map(|a| (a, depth + 1)),
+            );
+        }
+
+        Ok(tree)
+    }
     pub async fn find_detail_by_id(_id: &ObjectId) -> Result<Option<ProjectTaskResponse>, String> {
         let db: Database = get_db();
         let collection: Collection<ProjectTask> = db.collection::<ProjectTask>("project-tasks");
@@ -1322,13 +2313,13 @@ impl ProjectTask {
                                     "$cond": [
                                         { "$ne": ["$period", to_bson::<Option<ObjectId>>(&None).unwrap()] },
                                         {
-                                            "start": { "$toString": "$period.start" },
-                                            "end": { "$toString": "$period.end" },
+                                            "start": date_to_string("$period.start"),
+                                            "end": date_to_string("$period.end"),
                                         },
                                         to_bson::<Option<ObjectId>>(&None).unwrap()
                                     ]
                                 },
-                                "status": "$status",
+                                "status": status_to_string("$status"),
                                 "volume": "$volume"
                             }
                         }
@@ -1430,6 +2421,67 @@ impl ProjectTask {
                     ]
                 }
             },
+            doc! {
+                "$lookup": {
+                    "from": "project-incidents",
+                    "as": "incident",
+                    "let": {
+                        "task_id": "$_id"
+                    },
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$eq": ["$task_id", "$$task_id"]
+                                }
+                            },
+                        },
+                        {
+                            "$sort": {
+                                "date": -1
+                            }
+                        },
+                        {
+                            "$lookup": {
+                                "from": "users",
+                                "as": "user",
+                                "let": {
+                                    "user_id": "$user_id"
+                                },
+                                "pipeline": [
+                                    {
+                                        "$match": {
+                                            "$expr": {
+                                                "$eq": ["$_id", "$$user_id"]
+                                            }
+                                        }
+                                    },
+                                    {
+                                        "$project": {
+                                            "_id": {
+                                                "$toString": "$_id"
+                                            },
+                                            "name": "$name"
+                                        }
+                                    }
+                                ]
+                            }
+                        },
+                        {
+                            "$project": {
+                                "_id": {
+                                    "$toString": "$_id"
+                                },
+                                "user": {
+                                    "$first": "$user"
+                                },
+                                "date": date_to_string("$date"),
+                                "kind": "$kind"
+                            }
+                        }
+                    ]
+                }
+            },
             doc! {
                 "$project": {
                     "_id": {
@@ -1447,6 +2499,9 @@ impl ProjectTask {
                         "name": {
                             "$first": "$project.area.name"
                         },
+                        "description": {
+                            "$first": "$project.area.description"
+                        },
                     },
                     "user": {
                         "$concatArrays": [
@@ -1496,15 +2551,17 @@ impl ProjectTask {
                         "$cond": [
                             { "$ne": ["$period", to_bson::<Option<ObjectId>>(&None).unwrap()] },
                             {
-                                "start": { "$toString": "$period.start" },
-                                "end": { "$toString": "$period.end" },
+                                "start": date_to_string("$period.start"),
+                                "end": date_to_string("$period.end"),
                             },
                             to_bson::<Option<ObjectId>>(&None).unwrap()
                         ]
                     },
-                    "status": "$status",
+                    "status": status_to_string("$status"),
                     "volume": "$volume",
                     "value": "$value",
+                    "cost": "$cost",
+                    "effective_value": "$value",
                     "progress": {
                         "$cond": [
                             {
@@ -1519,6 +2576,65 @@ impl ProjectTask {
                             0
                         ]
                     },
+                    "duration_days": duration_days_projection(),
+                    "attachment": {
+                        "$cond": [
+                            "$attachment",
+                            {
+                                "$map": {
+                                    "input": "$attachment",
+                                    "in": {
+                                        "_id": {
+                                            "$toString": "$$this._id"
+                                        },
+                                        "name": "$$this.name",
+                                        "extension": "$$this.extension"
+                                    }
+                                }
+                            },
+                            to_bson::<Option<ObjectId>>(&None).unwrap()
+                        ]
+                    },
+                    "checklist": {
+                        "$cond": [
+                            "$checklist",
+                            {
+                                "$map": {
+                                    "input": "$checklist",
+                                    "in": {
+                                        "_id": {
+                                            "$toString": "$$this._id"
+                                        },
+                                        "label": "$$this.label",
+                                        "done": "$$this.done"
+                                    }
+                                }
+                            },
+                            to_bson::<Option<ObjectId>>(&None).unwrap()
+                        ]
+                    },
+                    "incident": "$incident",
+                    "checklist_completion": {
+                        "$cond": [
+                            {
+                                "$gt": [{ "$size": { "$ifNull": ["$checklist", []] } }, 0]
+                            },
+                            {
+                                "$divide": [
+                                    {
+                                        "$size": {
+                                            "$filter": {
+                                                "input": "$checklist",
+                                                "cond": "$$this.done"
+                                            }
+                                        }
+                                    },
+                                    { "$size": "$checklist" }
+                                ]
+                            },
+                            to_bson::<Option<f64>>(&None).unwrap()
+                        ]
+                    },
                 }
             },
         ];
@@ -1526,13 +2642,23 @@ impl ProjectTask {
         if let Ok(mut cursor) = collection.aggregate(pipeline, None).await {
             if let Some(Ok(doc)) = cursor.next().await {
                 let mut task = from_document::<ProjectTaskResponse>(doc).unwrap();
+                task.paused_days = ProjectTaskStatusResponse::paused_days(&task.status);
+                let project_id = task.project._id.parse::<ObjectId>().unwrap();
+                if let Some(parent) = Self::find_by_id(_id).await?.and_then(|a| a.task_id) {
+                    let dependencies = Self::find_dependencies(&project_id).await?;
+                    task.effective_value =
+                        Self::effective_value(task.value, Some(parent), &dependencies);
+                }
                 task.task = Self::find_many_timeline(&ProjectTaskTimelineQuery {
-                    project_id: task.project._id.parse::<ObjectId>().unwrap(),
+                    project_id,
                     area_id: None,
                     task_id: Some(*_id),
                     status: None,
                     relative: true,
                     subtask: true,
+                    kind: None,
+                    user_id: None,
+                    since: None,
                 })
                 .await
                 .map_or_else(|_| Some(Vec::<ProjectTaskMinResponse>::new()), |task| task);
@@ -1545,3 +2671,19 @@ impl ProjectTask {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ProjectTask;
+
+    #[test]
+    fn is_value_complete_accepts_thirds() {
+        assert!(ProjectTask::is_value_complete(3.0 * (100.0 / 3.0)));
+        assert!(ProjectTask::is_value_complete(33.333 + 33.333 + 33.334));
+    }
+
+    #[test]
+    fn is_value_complete_rejects_short_sum() {
+        assert!(!ProjectTask::is_value_complete(99.9));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::database::get_db;
+
+use futures::stream::StreamExt;
+use mongodb::{
+    bson::{doc, from_document, oid::ObjectId, to_bson, DateTime, Document},
+    Collection, Database,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use super::{date_to_string, project_task::ProjectTask};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskComment {
+    pub _id: Option<ObjectId>,
+    pub task_id: ObjectId,
+    pub user_id: ObjectId,
+    pub body: String,
+    pub create_date: DateTime,
+}
+#[derive(Debug, Deserialize)]
+pub struct ProjectTaskCommentRequest {
+    pub body: String,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskCommentResponse {
+    pub _id: String,
+    pub user: Option<ProjectTaskCommentUserResponse>,
+    pub body: String,
+    pub create_date: String,
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectTaskCommentUserResponse {
+    pub _id: String,
+    pub name: String,
+}
+
+impl ProjectTaskComment {
+    pub fn mentions(&self) -> Vec<ObjectId> {
+        let mut mentions = Vec::<ObjectId>::new();
+        let re = Regex::new(r"@([0-9a-fA-F]{24})\b").unwrap();
+        for capture in re.captures_iter(&self.body) {
+            if let Ok(_id) = ObjectId::from_str(&capture[1]) {
+                if !mentions.contains(&_id) {
+                    mentions.push(_id);
+                }
+            }
+        }
+        mentions
+    }
+    pub async fn save(&mut self) -> Result<ObjectId, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTaskComment> =
+            db.collection::<ProjectTaskComment>("task-comments");
+
+        self._id = Some(ObjectId::new());
+
+        if let Ok(Some(_)) = ProjectTask::find_by_id(&self.task_id).await {
+            collection
+                .insert_one(self, None)
+                .await
+                .map_err(|_| "INSERTING_FAILED".to_string())
+                .map(|result| result.inserted_id.as_object_id().unwrap())
+        } else {
+            Err("PROJECT_TASK_NOT_FOUND".to_string())
+        }
+    }
+    pub async fn delete_by_id(_id: &ObjectId) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTaskComment> =
+            db.collection::<ProjectTaskComment>("task-comments");
+
+        collection
+            .delete_one(doc! { "_id": _id }, None)
+            .await
+            .map_err(|_| "PROJECT_TASK_COMMENT_NOT_FOUND".to_string())
+            .map(|result| result.deleted_count)
+    }
+    pub async fn find_by_id(_id: &ObjectId) -> Result<Option<ProjectTaskComment>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTaskComment> =
+            db.collection::<ProjectTaskComment>("task-comments");
+
+        collection
+            .find_one(doc! { "_id": _id }, None)
+            .await
+            .map_err(|_| "PROJECT_TASK_COMMENT_NOT_FOUND".to_string())
+    }
+    pub async fn find_many_by_task_id(
+        task_id: &ObjectId,
+    ) -> Result<Vec<ProjectTaskCommentResponse>, String> {
+        let db: Database = get_db();
+        let collection: Collection<ProjectTaskComment> =
+            db.collection::<ProjectTaskComment>("task-comments");
+
+        let pipeline: Vec<Document> = vec![
+            doc! {
+                "$match": {
+                    "$expr": {
+                        "$eq": ["$task_id", to_bson::<ObjectId>(task_id).unwrap()]
+                    }
+                }
+            },
+            doc! {
+                "$sort": {
+                    "create_date": 1
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "users",
+                    "as": "user",
+                    "let": {
+                        "user_id": "$user_id"
+                    },
+                    "pipeline": [
+                        {
+                            "$match": {
+                                "$expr": {
+                                    "$eq": ["$_id", "$$user_id"]
+                                }
+                            }
+                        },
+                        {
+                            "$project": {
+                                "_id": {
+                                    "$toString": "$_id"
+                                },
+                                "name": "$name"
+                            }
+                        }
+                    ]
+                }
+            },
+            doc! {
+                "$project": {
+                    "_id": {
+                        "$toString": "$_id"
+                    },
+                    "user": {
+                        "$first": "$user"
+                    },
+                    "body": "$body",
+                    "create_date": date_to_string("$create_date")
+                }
+            },
+        ];
+
+        match collection.aggregate(pipeline, None).await {
+            Ok(mut cursor) => {
+                let mut comments = Vec::<ProjectTaskCommentResponse>::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    comments.push(from_document::<ProjectTaskCommentResponse>(doc).unwrap());
+                }
+                Ok(comments)
+            }
+            Err(_) => Err("PROJECT_TASK_COMMENT_NOT_FOUND".to_string()),
+        }
+    }
+}
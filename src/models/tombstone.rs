@@ -0,0 +1,84 @@
+use crate::database::get_db;
+
+use chrono::Utc;
+use futures::stream::StreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_bson, DateTime},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TombstoneKind {
+    Task,
+    Report,
+    Area,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Tombstone {
+    pub _id: Option<ObjectId>,
+    pub kind: TombstoneKind,
+    pub entity_id: ObjectId,
+    pub project_id: ObjectId,
+    pub delete_date: DateTime,
+}
+
+impl Tombstone {
+    pub async fn save_many(
+        kind: TombstoneKind,
+        project_id: &ObjectId,
+        entity_id: &[ObjectId],
+    ) -> Result<u64, String> {
+        let db: Database = get_db();
+        let collection: Collection<Tombstone> = db.collection::<Tombstone>("tombstones");
+
+        if entity_id.is_empty() {
+            return Ok(0);
+        }
+
+        let delete_date = DateTime::from_millis(Utc::now().timestamp_millis());
+        let tombstones = entity_id.iter().map(|_id| Tombstone {
+            _id: Some(ObjectId::new()),
+            kind: kind.clone(),
+            entity_id: *_id,
+            project_id: *project_id,
+            delete_date,
+        });
+
+        collection
+            .insert_many(tombstones, None)
+            .await
+            .map_err(|_| "INSERTING_FAILED".to_string())
+            .map(|result| result.inserted_ids.len() as u64)
+    }
+    pub async fn find_many(
+        project_id: &ObjectId,
+        kind: Option<&TombstoneKind>,
+        since: &DateTime,
+    ) -> Result<Vec<Tombstone>, String> {
+        let db: Database = get_db();
+        let collection: Collection<Tombstone> = db.collection::<Tombstone>("tombstones");
+
+        let mut filter = doc! {
+            "project_id": project_id,
+            "delete_date": { "$gt": since }
+        };
+        if let Some(kind) = kind {
+            filter.insert("kind", to_bson::<TombstoneKind>(kind).unwrap());
+        }
+
+        let mut cursor = collection
+            .find(filter, None)
+            .await
+            .map_err(|_| "TOMBSTONE_NOT_FOUND".to_string())?;
+
+        let mut tombstones: Vec<Tombstone> = Vec::<Tombstone>::new();
+        while let Some(Ok(tombstone)) = cursor.next().await {
+            tombstones.push(tombstone);
+        }
+
+        Ok(tombstones)
+    }
+}
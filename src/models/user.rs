@@ -12,7 +12,7 @@ use futures::{
     FutureExt,
 };
 use jsonwebtoken::{
-    self, decode, encode, Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation,
+    self, decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
 };
 use mongodb::{
     bson::{doc, from_document, oid::ObjectId, to_bson},
@@ -20,11 +20,21 @@ use mongodb::{
 };
 use pwhash::bcrypt;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fs::read_to_string, rc::Rc, str::FromStr};
+use std::{
+    collections::BTreeMap,
+    fs::{read_dir, read_to_string},
+    path::PathBuf,
+    rc::Rc,
+    str::FromStr,
+    sync::OnceLock,
+};
 
 use super::role::RoleResponse;
 
-static mut KEYS: BTreeMap<String, String> = BTreeMap::new();
+static ENCODING_KEYS: OnceLock<BTreeMap<String, EncodingKey>> = OnceLock::new();
+static DECODING_KEYS: OnceLock<BTreeMap<String, DecodingKey>> = OnceLock::new();
+
+const DEFAULT_KID: &str = "default";
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct User {
@@ -35,12 +45,20 @@ pub struct User {
     pub email: String,
     pub password: String,
     pub image: Option<UserImage>,
+    pub preference: Option<UserPreference>,
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UserImage {
     pub _id: ObjectId,
     pub extension: String,
 }
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UserPreference {
+    #[serde(default)]
+    pub report_email: Option<bool>,
+    #[serde(default)]
+    pub digest_email: Option<bool>,
+}
 #[derive(Debug, Deserialize)]
 pub struct UserCredential {
     pub email: String,
@@ -65,6 +83,18 @@ pub struct UserRequest {
     pub password: String,
     pub image: Option<UserImageRequest>,
 }
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserRoleAssignKind {
+    Add,
+    Remove,
+}
+#[derive(Debug, Deserialize)]
+pub struct UserRoleAssignRequest {
+    pub user_ids: Vec<ObjectId>,
+    pub role_id: ObjectId,
+    pub mode: UserRoleAssignKind,
+}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UserImageRequest {
     pub extension: String,
@@ -83,6 +113,11 @@ pub struct UserResponse {
     pub email: String,
     pub image: Option<UserImageResponse>,
 }
+#[derive(Debug, Serialize)]
+pub struct UserRoleAssignResponse {
+    pub _id: String,
+    pub result: String,
+}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UserImageResponse {
     pub _id: String,
@@ -102,6 +137,24 @@ struct UserClaim {
     iss: String,
     sub: String,
 }
+impl UserClaim {
+    fn new(sub: &ObjectId, ttl: i64) -> Self {
+        Self {
+            aud: std::env::var("JWT_AUDIENCE").unwrap(),
+            exp: Utc::now().timestamp() + ttl,
+            iss: std::env::var("JWT_ISSUER").unwrap(),
+            sub: sub.to_string(),
+        }
+    }
+    fn validation() -> Validation {
+        let mut validation: Validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[std::env::var("JWT_ISSUER").unwrap()]);
+        validation.set_audience(&[std::env::var("JWT_AUDIENCE").unwrap()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation
+    }
+}
+
 pub struct UserAuthenticationMiddleware<S> {
     service: Rc<S>,
 }
@@ -149,6 +202,28 @@ impl User {
             .map_err(|_| "UPDATE_FAILED".to_string())
             .map(|_| self._id.unwrap())
     }
+    pub async fn update_role(
+        _id: &ObjectId,
+        role_id: &ObjectId,
+        kind: &UserRoleAssignKind,
+    ) -> Result<bool, String> {
+        let db: Database = get_db();
+        let collection: Collection<User> = db.collection::<User>("users");
+
+        let update = match kind {
+            UserRoleAssignKind::Add => doc! { "$addToSet": { "role_id": role_id } },
+            UserRoleAssignKind::Remove => doc! { "$pull": { "role_id": role_id } },
+        };
+
+        match collection
+            .update_one(doc! { "_id": _id }, update, None)
+            .await
+        {
+            Ok(result) if result.matched_count == 0 => Err("USER_NOT_FOUND".to_string()),
+            Ok(result) => Ok(result.modified_count > 0),
+            Err(_) => Err("UPDATE_FAILED".to_string()),
+        }
+    }
     pub async fn delete(&self) -> Result<u64, String> {
         let db: Database = get_db();
         let collection: Collection<User> = db.collection::<User>("users");
@@ -344,119 +419,52 @@ impl UserCredential {
             return Err("INVALID_COMBINATION".to_string());
         }
 
-        let claim_access: UserClaim = UserClaim {
-            sub: ObjectId::to_string(&user._id.unwrap()),
-            exp: Utc::now().timestamp() + 1800,
-            iss: "Redian".to_string(),
-            aud: std::env::var("BASE_URL").unwrap(),
-        };
-        let claim_refresh: UserClaim = UserClaim {
-            sub: ObjectId::to_string(&user._id.unwrap()),
-            exp: Utc::now().timestamp() + 259200,
-            iss: "Redian".to_string(),
-            aud: std::env::var("BASE_URL").unwrap(),
-        };
-
-        let header: Header = Header::new(Algorithm::RS256);
-        unsafe {
-            match (
-                encode(
-                    &header,
-                    &claim_access,
-                    &EncodingKey::from_rsa_pem(KEYS.get("private_access").unwrap().as_bytes())
-                        .unwrap(),
-                ),
-                encode(
-                    &header,
-                    &claim_refresh,
-                    &EncodingKey::from_rsa_pem(KEYS.get("private_refresh").unwrap().as_bytes())
-                        .unwrap(),
-                ),
-            ) {
-                (Ok(atk), Ok(rtk)) => {
-                    let user = User::find_detail_by_id(&user._id.unwrap())
-                        .await
-                        .map_err(|_| "USER_NOT_FOUND".to_string())?
-                        .ok_or("USER_NOT_FOUND")?;
-                    Ok((atk, rtk, user))
-                }
-                _ => Err("GENERATING_FAILED".to_string()),
+        let claim_access: UserClaim = UserClaim::new(&user._id.unwrap(), 1800);
+        let claim_refresh: UserClaim = UserClaim::new(&user._id.unwrap(), 259200);
+
+        match (
+            sign_token(&claim_access, "private_access"),
+            sign_token(&claim_refresh, "private_refresh"),
+        ) {
+            (Ok(atk), Ok(rtk)) => {
+                let user = User::find_detail_by_id(&user._id.unwrap())
+                    .await
+                    .map_err(|_| "USER_NOT_FOUND".to_string())?
+                    .ok_or("USER_NOT_FOUND")?;
+                Ok((atk, rtk, user))
             }
+            _ => Err("GENERATING_FAILED".to_string()),
         }
     }
     pub async fn refresh(token: &str) -> Result<(String, String, UserResponse), String> {
-        let validation: Validation = Validation::new(Algorithm::RS256);
-        let data: TokenData<UserClaim>;
-
-        unsafe {
-            data = decode::<UserClaim>(
-                token,
-                &DecodingKey::from_rsa_pem(KEYS.get("public_refresh").unwrap().as_bytes()).unwrap(),
-                &validation,
-            )
-            .map_err(|_| "INVALID_TOKEN")?;
-        }
-        let _id = ObjectId::from_str(&data.claims.sub).map_err(|_| "INVALID_ID".to_string())?;
+        let claim = verify_token(token, "public_refresh")?;
+        let _id = ObjectId::from_str(&claim.sub).map_err(|_| "INVALID_ID".to_string())?;
 
         let user = User::find_by_id(&_id)
             .await?
             .ok_or_else(|| "USER_NOT_FOUDN".to_string())?;
 
-        let claim_access: UserClaim = UserClaim {
-            sub: ObjectId::to_string(&user._id.unwrap()),
-            exp: Utc::now().timestamp() + 1800,
-            iss: "Redian".to_string(),
-            aud: std::env::var("BASE_URL").unwrap(),
-        };
-        let claim_refresh: UserClaim = UserClaim {
-            sub: ObjectId::to_string(&user._id.unwrap()),
-            exp: Utc::now().timestamp() + 259200,
-            iss: "Redian".to_string(),
-            aud: std::env::var("BASE_URL").unwrap(),
-        };
-
-        let header: Header = Header::new(Algorithm::RS256);
-        unsafe {
-            match (
-                encode(
-                    &header,
-                    &claim_access,
-                    &EncodingKey::from_rsa_pem(KEYS.get("private_access").unwrap().as_bytes())
-                        .unwrap(),
-                ),
-                encode(
-                    &header,
-                    &claim_refresh,
-                    &EncodingKey::from_rsa_pem(KEYS.get("private_refresh").unwrap().as_bytes())
-                        .unwrap(),
-                ),
-            ) {
-                (Ok(atk), Ok(rtk)) => {
-                    let user = User::find_detail_by_id(&user._id.unwrap())
-                        .await
-                        .map_err(|_| "USER_NOT_FOUND".to_string())?
-                        .ok_or("USER_NOT_FOUND")?;
-                    Ok((atk, rtk, user))
-                }
-                _ => Err("GENERATING_FAILED".to_string()),
+        let claim_access: UserClaim = UserClaim::new(&user._id.unwrap(), 1800);
+        let claim_refresh: UserClaim = UserClaim::new(&user._id.unwrap(), 259200);
+
+        match (
+            sign_token(&claim_access, "private_access"),
+            sign_token(&claim_refresh, "private_refresh"),
+        ) {
+            (Ok(atk), Ok(rtk)) => {
+                let user = User::find_detail_by_id(&user._id.unwrap())
+                    .await
+                    .map_err(|_| "USER_NOT_FOUND".to_string())?
+                    .ok_or("USER_NOT_FOUND")?;
+                Ok((atk, rtk, user))
             }
+            _ => Err("GENERATING_FAILED".to_string()),
         }
     }
     pub fn verify(token: &str) -> Option<ObjectId> {
-        let validation: Validation = Validation::new(Algorithm::RS256);
-        unsafe {
-            match decode::<UserClaim>(
-                token,
-                &DecodingKey::from_rsa_pem(KEYS.get("public_access").unwrap().as_bytes()).unwrap(),
-                &validation,
-            ) {
-                Ok(data) => match ObjectId::from_str(&data.claims.sub) {
-                    Ok(id) => Some(id),
-                    Err(_) => None,
-                },
-                Err(_) => None,
-            }
-        }
+        verify_token(token, "public_access")
+            .ok()
+            .and_then(|claim| ObjectId::from_str(&claim.sub).ok())
     }
 }
 
@@ -521,19 +529,89 @@ where
     }
 }
 
+fn signing_kid() -> String {
+    std::env::var("JWT_KID").unwrap()
+}
+
+fn sign_token(claim: &UserClaim, name: &str) -> Result<String, String> {
+    let kid = signing_kid();
+    let key = ENCODING_KEYS
+        .get()
+        .and_then(|keys| keys.get(&format!("{}:{}", kid, name)))
+        .ok_or_else(|| "GENERATING_FAILED".to_string())?;
+
+    let mut header: Header = Header::new(Algorithm::RS256);
+    header.kid = Some(kid);
+
+    encode(&header, claim, key).map_err(|_| "GENERATING_FAILED".to_string())
+}
+
+fn verify_token(token: &str, name: &str) -> Result<UserClaim, String> {
+    // Tokens issued before key rotation carry no kid and belong to the default set
+    let kid = decode_header(token)
+        .map_err(|_| "INVALID_TOKEN".to_string())?
+        .kid
+        .unwrap_or_else(|| DEFAULT_KID.to_string());
+    let key = DECODING_KEYS
+        .get()
+        .and_then(|keys| keys.get(&format!("{}:{}", kid, name)))
+        .ok_or_else(|| "INVALID_TOKEN".to_string())?;
+
+    decode::<UserClaim>(token, key, &UserClaim::validation())
+        .map(|data| data.claims)
+        .map_err(|_| "INVALID_TOKEN".to_string())
+}
+
 pub fn load_keys() {
-    let private_access_file =
-        read_to_string("./keys/private_access.key").expect("LOAD_FAILED_PRIVATE_ACCESS");
-    let public_access_file =
-        read_to_string("./keys/public_access.pem").expect("LOAD_FAILED_PUBLIC_ACCESS");
-    let private_refresh_file =
-        read_to_string("./keys/private_refresh.key").expect("LOAD_FAILED_PRIVATE_ACCESS");
-    let public_refresh_file =
-        read_to_string("./keys/public_refresh.pem").expect("LOAD_FAILED_PUBLIC_ACCESS");
-    unsafe {
-        KEYS.insert("private_access".to_string(), private_access_file);
-        KEYS.insert("public_access".to_string(), public_access_file);
-        KEYS.insert("private_refresh".to_string(), private_refresh_file);
-        KEYS.insert("public_refresh".to_string(), public_refresh_file);
+    let names = [
+        ("private_access", "key"),
+        ("public_access", "pem"),
+        ("private_refresh", "key"),
+        ("public_refresh", "pem"),
+    ];
+
+    // ./keys holds the default set, ./keys/{kid} holds additional sets for rotation.
+    // A ./keys/default directory would shadow the root set, so it is ignored
+    let mut dirs = vec![(DEFAULT_KID.to_string(), PathBuf::from("./keys"))];
+    if let Ok(entries) = read_dir("./keys") {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() && entry.file_name() != DEFAULT_KID {
+                dirs.push((
+                    entry.file_name().to_string_lossy().to_string(),
+                    entry.path(),
+                ));
+            }
+        }
+    }
+
+    let mut encoding_keys = BTreeMap::<String, EncodingKey>::new();
+    let mut decoding_keys = BTreeMap::<String, DecodingKey>::new();
+    for (kid, dir) in dirs {
+        for (name, extension) in names {
+            let key = match read_to_string(dir.join(format!("{}.{}", name, extension))) {
+                Ok(key) => key,
+                Err(_) => continue,
+            };
+            let id = format!("{}:{}", kid, name);
+            if extension == "key" {
+                if let Ok(key) = EncodingKey::from_rsa_pem(key.as_bytes()) {
+                    encoding_keys.insert(id, key);
+                }
+            } else if let Ok(key) = DecodingKey::from_rsa_pem(key.as_bytes()) {
+                decoding_keys.insert(id, key);
+            }
+        }
+    }
+
+    let kid = signing_kid();
+    for (name, _) in names {
+        let id = format!("{}:{}", kid, name);
+        if !encoding_keys.contains_key(&id) && !decoding_keys.contains_key(&id) {
+            panic!("LOAD_FAILED_{}", name.to_uppercase());
+        }
+    }
+
+    if ENCODING_KEYS.set(encoding_keys).is_err() || DECODING_KEYS.set(decoding_keys).is_err() {
+        panic!("KEYS_ALREADY_LOADED");
     }
 }
@@ -1,17 +1,22 @@
 use std::{
-    fs::{create_dir_all, remove_dir_all, rename},
+    fs::{create_dir_all, remove_dir_all, remove_file, rename},
     path::PathBuf,
 };
 
 use actix_multipart::form::MultipartForm;
-use actix_web::{get, post, put, web, HttpMessage, HttpRequest, HttpResponse};
+use actix_web::{delete, get, post, put, web, HttpMessage, HttpRequest, HttpResponse};
 use mime_guess::get_mime_extensions_str;
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{doc, oid::ObjectId, to_bson};
 
+use super::created_at;
 use crate::models::{
-    company::{Company, CompanyImage, CompanyImageMultipartRequest, CompanyRequest},
+    company::{
+        Company, CompanyImage, CompanyImageMultipartRequest, CompanyImageOrderRequest,
+        CompanyImagesMultipartRequest, CompanyRequest,
+    },
     role::{Role, RolePermission},
     user::UserAuthentication,
+    FileKind,
 };
 
 #[get("/companies")]
@@ -39,6 +44,7 @@ pub async fn create_company(payload: web::Json<CompanyRequest>, req: HttpRequest
         field: payload.field,
         contact: payload.contact,
         image: None,
+        images: None,
     };
 
     if let Some(image) = payload.image {
@@ -49,7 +55,7 @@ pub async fn create_company(payload: web::Json<CompanyRequest>, req: HttpRequest
     }
 
     match company.save().await {
-        Ok(id) => HttpResponse::Created().body(id.to_string()),
+        Ok(id) => created_at("/companies".to_string()).json(doc! { "_id": id.to_string() }),
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
@@ -76,7 +82,7 @@ pub async fn update_company(
         let payload = payload.into_inner();
 
         if company.image.is_some() {
-            match remove_dir_all(format!("./files/companies/{company_id}")) {
+            match remove_dir_all(FileKind::CompanyImage.path(company_id)) {
                 _ => (),
             };
         }
@@ -86,6 +92,7 @@ pub async fn update_company(
             field: payload.field,
             contact: payload.contact,
             image: None,
+            images: company.images,
         };
 
         if let Some(image) = payload.image {
@@ -128,7 +135,7 @@ pub async fn update_company_image(
             None => return HttpResponse::BadRequest().body("COMPANY_IMAGE_NOT_FOUND"),
         };
 
-        let save_dir = format!("./files/companies/{}/", company_id);
+        let save_dir = FileKind::CompanyImage.path(format!("{}/", company_id));
 
         if create_dir_all(&save_dir).is_err() {
             return HttpResponse::InternalServerError()
@@ -182,3 +189,185 @@ pub async fn update_company_image(
         HttpResponse::NotFound().body("COMPANY_NOT_FOUND")
     }
 }
+#[post("/companies/{company_id}/images")]
+pub async fn create_company_images(
+    company_id: web::Path<String>,
+    form: MultipartForm<CompanyImagesMultipartRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty() || !Role::validate(&issuer_role, &RolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let company_id = match company_id.parse::<ObjectId>() {
+        Ok(company_id) => company_id,
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut company = match Company::find_by_id(&company_id).await {
+        Ok(Some(company)) => company,
+        _ => return HttpResponse::NotFound().body("COMPANY_NOT_FOUND"),
+    };
+
+    if form.files.is_empty() {
+        return HttpResponse::BadRequest().body("COMPANY_IMAGE_NOT_FOUND");
+    }
+
+    let save_dir = FileKind::CompanyGallery.path(format!("{}/", company_id));
+
+    if create_dir_all(&save_dir).is_err() {
+        return HttpResponse::InternalServerError().body("DIRECTORY_CREATION_FAILED".to_string());
+    }
+
+    let mut images = company.images.take().unwrap_or_default();
+    let mut file_paths = Vec::<PathBuf>::new();
+    let mut image_id = Vec::<ObjectId>::new();
+
+    for file in form.files.iter() {
+        let ext = match file
+            .content_type
+            .as_ref()
+            .filter(|mime| mime.type_().as_str() == "image")
+            .and_then(|mime| get_mime_extensions_str(mime.essence_str()))
+            .and_then(|ext| ext.first())
+        {
+            Some(ext) => ext.to_string(),
+            None => {
+                for file_path in file_paths {
+                    let _ = remove_file(file_path);
+                }
+                return HttpResponse::BadRequest().body("COMPANY_IMAGE_INVALID_MIME");
+            }
+        };
+        let _id = ObjectId::new();
+        let file_path = PathBuf::from(save_dir.to_owned() + &_id.to_string() + "." + &ext);
+        if rename(file.file.path(), &file_path).is_err() {
+            for file_path in file_paths {
+                let _ = remove_file(file_path);
+            }
+            return HttpResponse::InternalServerError().body("COMPANY_IMAGE_RENAME_FAILED");
+        }
+
+        images.push(CompanyImage {
+            _id,
+            extension: ext,
+        });
+        file_paths.push(file_path);
+        image_id.push(_id);
+    }
+
+    company.images = Some(images);
+
+    match company.update().await {
+        Ok(_) => HttpResponse::Created().json(doc! {
+            "_id": to_bson::<Vec<ObjectId>>(&image_id).unwrap()
+        }),
+        Err(error) => {
+            for file_path in file_paths {
+                let _ = remove_file(file_path);
+            }
+            HttpResponse::InternalServerError().body(error)
+        }
+    }
+}
+#[put("/companies/{company_id}/images")]
+pub async fn update_company_images(
+    company_id: web::Path<String>,
+    payload: web::Json<CompanyImageOrderRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty() || !Role::validate(&issuer_role, &RolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let company_id = match company_id.parse::<ObjectId>() {
+        Ok(company_id) => company_id,
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut company = match Company::find_by_id(&company_id).await {
+        Ok(Some(company)) => company,
+        _ => return HttpResponse::NotFound().body("COMPANY_NOT_FOUND"),
+    };
+
+    let mut images = company.images.take().unwrap_or_default();
+    let payload = payload.into_inner();
+
+    if payload.image_id.len() != images.len() {
+        return HttpResponse::BadRequest().body("INVALID_IMAGE_ORDER");
+    }
+
+    let mut ordered = Vec::<CompanyImage>::new();
+    for _id in payload.image_id.iter() {
+        match images.iter().position(|a| a._id == *_id) {
+            Some(index) => ordered.push(images.remove(index)),
+            None => return HttpResponse::BadRequest().body("INVALID_IMAGE_ORDER"),
+        }
+    }
+
+    company.images = if ordered.is_empty() {
+        None
+    } else {
+        Some(ordered)
+    };
+
+    match company.update().await {
+        Ok(company_id) => HttpResponse::Ok().body(company_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[delete("/companies/{company_id}/images/{image_id}")]
+pub async fn delete_company_image(
+    _id: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty() || !Role::validate(&issuer_role, &RolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let (company_id, image_id) = match (_id.0.parse::<ObjectId>(), _id.1.parse::<ObjectId>()) {
+        (Ok(company_id), Ok(image_id)) => (company_id, image_id),
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut company = match Company::find_by_id(&company_id).await {
+        Ok(Some(company)) => company,
+        _ => return HttpResponse::NotFound().body("COMPANY_NOT_FOUND"),
+    };
+
+    let mut images = company.images.take().unwrap_or_default();
+    let index = match images.iter().position(|a| a._id == image_id) {
+        Some(index) => index,
+        None => return HttpResponse::NotFound().body("COMPANY_IMAGE_NOT_FOUND"),
+    };
+    let removed = images.remove(index);
+
+    company.images = if images.is_empty() {
+        None
+    } else {
+        Some(images)
+    };
+
+    match company.update().await {
+        Ok(company_id) => {
+            let _ = remove_file(FileKind::CompanyGallery.path(format!(
+                "{}/{}.{}",
+                company_id, removed._id, removed.extension
+            )));
+            HttpResponse::Ok().body(company_id.to_string())
+        }
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
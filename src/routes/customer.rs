@@ -1,26 +1,36 @@
 use std::{
-    fs::{create_dir_all, remove_dir_all, rename},
+    fs::{create_dir_all, remove_dir_all, remove_file, rename},
     path::PathBuf,
 };
 
 use actix_multipart::form::MultipartForm;
 use actix_web::{delete, get, post, put, web, HttpMessage, HttpRequest, HttpResponse};
 use mime_guess::get_mime_extensions_str;
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{doc, oid::ObjectId, to_bson};
+use serde::Deserialize;
 
+use super::created_at;
 use crate::models::{
     customer::{
-        Customer, CustomerImage, CustomerImageMultipartRequest, CustomerQuery, CustomerRequest,
+        Customer, CustomerImage, CustomerImageMultipartRequest, CustomerImageOrderRequest,
+        CustomerImagesMultipartRequest, CustomerPerson, CustomerPersonRequest, CustomerQuery,
+        CustomerRequest,
     },
     role::{Role, RolePermission},
     user::UserAuthentication,
+    FileKind,
 };
 
+#[derive(Deserialize)]
+pub struct CustomerQueryParams {
+    pub text: Option<String>,
+}
+
 #[get("/customers")]
-pub async fn get_customers() -> HttpResponse {
+pub async fn get_customers(query: web::Query<CustomerQueryParams>) -> HttpResponse {
     let query: CustomerQuery = CustomerQuery {
         _id: None,
-        name: None,
+        name: query.text.clone(),
         limit: None,
     };
 
@@ -62,11 +72,16 @@ pub async fn create_customer(
     let mut customer: Customer = Customer {
         _id: None,
         name: payload.name,
+        name_normalized: None,
         field: payload.field,
         contact: payload.contact,
         person: payload.person,
         image: None,
+        images: None,
     };
+    if let Err(error) = customer.validate() {
+        return HttpResponse::BadRequest().body(error);
+    }
     if let Some(image) = payload.image {
         customer.image = Some(CustomerImage {
             _id: ObjectId::new(),
@@ -74,7 +89,7 @@ pub async fn create_customer(
         });
     }
     match customer.save().await {
-        Ok(id) => HttpResponse::Created().body(id.to_string()),
+        Ok(id) => created_at(format!("/customers/{}", id)).json(doc! { "_id": id.to_string() }),
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
@@ -100,10 +115,20 @@ pub async fn update_customer(
     };
 
     if let Ok(Some(customer)) = Customer::find_by_id(&customer_id).await {
-        let payload = payload.into_inner();
+        let mut payload = payload.into_inner();
+
+        let mut contact = payload.contact;
+        if let Err(error) = contact.validate() {
+            return HttpResponse::BadRequest().body(error);
+        }
+        for person in payload.person.iter_mut() {
+            if let Err(error) = person.validate() {
+                return HttpResponse::BadRequest().body(error);
+            }
+        }
 
         if customer.image.is_some() {
-            let old_path = format!("./files/customers/{customer_id}",);
+            let old_path = FileKind::CustomerImage.path(customer_id);
             match remove_dir_all(old_path) {
                 _ => (),
             };
@@ -112,10 +137,12 @@ pub async fn update_customer(
         let mut customer = Customer {
             _id: Some(customer_id),
             name: payload.name,
+            name_normalized: None,
             field: payload.field,
-            contact: payload.contact,
+            contact,
             person: payload.person,
             image: None,
+            images: customer.images,
         };
 
         if let Some(image) = payload.image {
@@ -160,7 +187,7 @@ pub async fn update_customer_image(
             None => return HttpResponse::BadRequest().body("CUSTOMER_IMAGE_NOT_FOUND"),
         };
 
-        let save_dir = format!("./files/customers/{}/", customer_id);
+        let save_dir = FileKind::CustomerImage.path(format!("{}/", customer_id));
 
         if create_dir_all(&save_dir).is_err() {
             return HttpResponse::InternalServerError()
@@ -214,6 +241,336 @@ pub async fn update_customer_image(
         HttpResponse::NotFound().body("CUSTOMER_NOT_FOUND")
     }
 }
+#[post("/customers/{customer_id}/images")]
+pub async fn create_customer_images(
+    customer_id: web::Path<String>,
+    form: MultipartForm<CustomerImagesMultipartRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty()
+        || !Role::validate(&issuer_role, &RolePermission::UpdateCustomer).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let customer_id = match customer_id.parse::<ObjectId>() {
+        Ok(customer_id) => customer_id,
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut customer = match Customer::find_by_id(&customer_id).await {
+        Ok(Some(customer)) => customer,
+        _ => return HttpResponse::NotFound().body("CUSTOMER_NOT_FOUND"),
+    };
+
+    if form.files.is_empty() {
+        return HttpResponse::BadRequest().body("CUSTOMER_IMAGE_NOT_FOUND");
+    }
+
+    let save_dir = FileKind::CustomerGallery.path(format!("{}/", customer_id));
+
+    if create_dir_all(&save_dir).is_err() {
+        return HttpResponse::InternalServerError().body("DIRECTORY_CREATION_FAILED".to_string());
+    }
+
+    let mut images = customer.images.take().unwrap_or_default();
+    let mut file_paths = Vec::<PathBuf>::new();
+    let mut image_id = Vec::<ObjectId>::new();
+
+    for file in form.files.iter() {
+        let ext = match file
+            .content_type
+            .as_ref()
+            .filter(|mime| mime.type_().as_str() == "image")
+            .and_then(|mime| get_mime_extensions_str(mime.essence_str()))
+            .and_then(|ext| ext.first())
+        {
+            Some(ext) => ext.to_string(),
+            None => {
+                for file_path in file_paths {
+                    let _ = remove_file(file_path);
+                }
+                return HttpResponse::BadRequest().body("CUSTOMER_IMAGE_INVALID_MIME");
+            }
+        };
+        let _id = ObjectId::new();
+        let file_path = PathBuf::from(save_dir.to_owned() + &_id.to_string() + "." + &ext);
+        if rename(file.file.path(), &file_path).is_err() {
+            for file_path in file_paths {
+                let _ = remove_file(file_path);
+            }
+            return HttpResponse::InternalServerError().body("CUSTOMER_IMAGE_RENAME_FAILED");
+        }
+
+        images.push(CustomerImage {
+            _id,
+            extension: ext,
+        });
+        file_paths.push(file_path);
+        image_id.push(_id);
+    }
+
+    customer.images = Some(images);
+
+    match customer.update().await {
+        Ok(_) => HttpResponse::Created().json(doc! {
+            "_id": to_bson::<Vec<ObjectId>>(&image_id).unwrap()
+        }),
+        Err(error) => {
+            for file_path in file_paths {
+                let _ = remove_file(file_path);
+            }
+            HttpResponse::InternalServerError().body(error)
+        }
+    }
+}
+#[put("/customers/{customer_id}/images")]
+pub async fn update_customer_images(
+    customer_id: web::Path<String>,
+    payload: web::Json<CustomerImageOrderRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty()
+        || !Role::validate(&issuer_role, &RolePermission::UpdateCustomer).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let customer_id = match customer_id.parse::<ObjectId>() {
+        Ok(customer_id) => customer_id,
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut customer = match Customer::find_by_id(&customer_id).await {
+        Ok(Some(customer)) => customer,
+        _ => return HttpResponse::NotFound().body("CUSTOMER_NOT_FOUND"),
+    };
+
+    let mut images = customer.images.take().unwrap_or_default();
+    let payload = payload.into_inner();
+
+    if payload.image_id.len() != images.len() {
+        return HttpResponse::BadRequest().body("INVALID_IMAGE_ORDER");
+    }
+
+    let mut ordered = Vec::<CustomerImage>::new();
+    for _id in payload.image_id.iter() {
+        match images.iter().position(|a| a._id == *_id) {
+            Some(index) => ordered.push(images.remove(index)),
+            None => return HttpResponse::BadRequest().body("INVALID_IMAGE_ORDER"),
+        }
+    }
+
+    customer.images = if ordered.is_empty() {
+        None
+    } else {
+        Some(ordered)
+    };
+
+    match customer.update().await {
+        Ok(customer_id) => HttpResponse::Ok().body(customer_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[delete("/customers/{customer_id}/images/{image_id}")]
+pub async fn delete_customer_image(
+    _id: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty()
+        || !Role::validate(&issuer_role, &RolePermission::UpdateCustomer).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let (customer_id, image_id) = match (_id.0.parse::<ObjectId>(), _id.1.parse::<ObjectId>()) {
+        (Ok(customer_id), Ok(image_id)) => (customer_id, image_id),
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut customer = match Customer::find_by_id(&customer_id).await {
+        Ok(Some(customer)) => customer,
+        _ => return HttpResponse::NotFound().body("CUSTOMER_NOT_FOUND"),
+    };
+
+    let mut images = customer.images.take().unwrap_or_default();
+    let index = match images.iter().position(|a| a._id == image_id) {
+        Some(index) => index,
+        None => return HttpResponse::NotFound().body("CUSTOMER_IMAGE_NOT_FOUND"),
+    };
+    let removed = images.remove(index);
+
+    customer.images = if images.is_empty() {
+        None
+    } else {
+        Some(images)
+    };
+
+    match customer.update().await {
+        Ok(customer_id) => {
+            let _ = remove_file(FileKind::CustomerGallery.path(format!(
+                "{}/{}.{}",
+                customer_id, removed._id, removed.extension
+            )));
+            HttpResponse::Ok().body(customer_id.to_string())
+        }
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[post("/customers/{customer_id}/persons")]
+pub async fn create_customer_person(
+    customer_id: web::Path<String>,
+    payload: web::Json<CustomerPersonRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty()
+        || !Role::validate(&issuer_role, &RolePermission::UpdateCustomer).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let customer_id = match customer_id.parse::<ObjectId>() {
+        Ok(customer_id) => customer_id,
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut customer = match Customer::find_by_id(&customer_id).await {
+        Ok(Some(customer)) => customer,
+        _ => return HttpResponse::NotFound().body("CUSTOMER_NOT_FOUND"),
+    };
+
+    let payload = payload.into_inner();
+    let mut person = CustomerPerson {
+        _id: Some(ObjectId::new()),
+        name: payload.name,
+        address: payload.address,
+        phone: payload.phone,
+        email: payload.email,
+        role: payload.role,
+    };
+    if let Err(error) = person.validate() {
+        return HttpResponse::BadRequest().body(error);
+    }
+
+    let person_id = person._id.unwrap();
+    customer.person.push(person);
+
+    match customer.update().await {
+        Ok(_) => created_at(format!("/customers/{}", customer_id))
+            .json(doc! { "_id": person_id.to_string() }),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[put("/customers/{customer_id}/persons/{person_id}")]
+pub async fn update_customer_person(
+    _id: web::Path<(String, String)>,
+    payload: web::Json<CustomerPersonRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty()
+        || !Role::validate(&issuer_role, &RolePermission::UpdateCustomer).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let (customer_id, person_id) = match (_id.0.parse::<ObjectId>(), _id.1.parse::<ObjectId>()) {
+        (Ok(customer_id), Ok(person_id)) => (customer_id, person_id),
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut customer = match Customer::find_by_id(&customer_id).await {
+        Ok(Some(customer)) => customer,
+        _ => return HttpResponse::NotFound().body("CUSTOMER_NOT_FOUND"),
+    };
+
+    let payload = payload.into_inner();
+    let mut person = CustomerPerson {
+        _id: Some(person_id),
+        name: payload.name,
+        address: payload.address,
+        phone: payload.phone,
+        email: payload.email,
+        role: payload.role,
+    };
+    if let Err(error) = person.validate() {
+        return HttpResponse::BadRequest().body(error);
+    }
+
+    match customer
+        .person
+        .iter_mut()
+        .find(|a| a._id == Some(person_id))
+    {
+        Some(current) => *current = person,
+        None => return HttpResponse::NotFound().body("CUSTOMER_PERSON_NOT_FOUND"),
+    }
+
+    match customer.update().await {
+        Ok(_) => HttpResponse::Ok().body(person_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[delete("/customers/{customer_id}/persons/{person_id}")]
+pub async fn delete_customer_person(
+    _id: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty()
+        || !Role::validate(&issuer_role, &RolePermission::UpdateCustomer).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let (customer_id, person_id) = match (_id.0.parse::<ObjectId>(), _id.1.parse::<ObjectId>()) {
+        (Ok(customer_id), Ok(person_id)) => (customer_id, person_id),
+        _ => return HttpResponse::BadRequest().body("INVALID_ID"),
+    };
+
+    let mut customer = match Customer::find_by_id(&customer_id).await {
+        Ok(Some(customer)) => customer,
+        _ => return HttpResponse::NotFound().body("CUSTOMER_NOT_FOUND"),
+    };
+
+    match customer
+        .person
+        .iter()
+        .position(|a| a._id == Some(person_id))
+    {
+        Some(index) => {
+            customer.person.remove(index);
+        }
+        None => return HttpResponse::NotFound().body("CUSTOMER_PERSON_NOT_FOUND"),
+    }
+
+    match customer.update().await {
+        Ok(_) => HttpResponse::Ok().body(person_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
 #[delete("/customers/{customer_id}")]
 pub async fn delete_customer(customer_id: web::Path<String>, req: HttpRequest) -> HttpResponse {
     let issuer_role = match req.extensions().get::<UserAuthentication>() {
@@ -233,6 +590,10 @@ pub async fn delete_customer(customer_id: web::Path<String>, req: HttpRequest) -
 
     if let Ok(Some(customer)) = Customer::find_by_id(&customer_id).await {
         match customer.delete().await {
+            Ok(count) if customer.images.is_some() => {
+                let _ = remove_dir_all(FileKind::CustomerGallery.path(customer_id));
+                HttpResponse::Ok().body(format!("Deleted {count} customer"))
+            }
             Ok(count) => HttpResponse::Ok().body(format!("Deleted {count} customer")),
             Err(error) => HttpResponse::InternalServerError().body(error),
         }
@@ -1,35 +1,71 @@
 use crate::{
     database::get_db,
+    middleware::{client_address, is_maintenance, set_maintenance, take_file_token},
     models::{
+        date_to_string,
         project::{
             Project, ProjectCustomerImageResponse, ProjectCustomerResponse, ProjectPeriodResponse,
             ProjectProgressResponse,
         },
+        project_incident_report::{ProjectIncidentReport, ProjectIncidentReportQuery},
+        project_progress_report::ProjectProgressReport,
+        project_role::{ProjectRole, ProjectRolePermission},
         project_task::{ProjectTask, ProjectTaskQuery, ProjectTaskQueryKind},
+        role::{Role, RolePermission},
+        status_to_string,
+        user::UserAuthentication,
+        FileKind,
     },
 };
-use actix_web::{get, web, HttpResponse};
-use futures::stream::StreamExt;
+use actix_multipart::{form::MultipartFormConfig, MultipartError};
+use actix_web::{
+    dev::Payload,
+    error::{InternalError, JsonPayloadError, PayloadError},
+    get, post, put, web,
+    web::Bytes,
+    FromRequest, HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder,
+};
+use futures::{
+    future::{ready, Ready},
+    stream::{self, StreamExt},
+};
 use mime_guess::from_path;
-use mongodb::bson::{doc, from_document, oid::ObjectId, to_bson};
+use mongodb::bson::{doc, from_document, oid::ObjectId, to_bson, DateTime, Document};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fs;
 
 use crate::models::project_task::{ProjectTaskAreaResponse, ProjectTaskPeriodResponse};
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub enum FileKind {
-    ProjectDocumentation,
-    CompanyImage,
-    CustomerImage,
-    UserImage,
-}
 #[derive(Deserialize)]
 pub struct FileQueryParams {
     pub kind: FileKind,
     pub name: String,
 }
+#[derive(Serialize)]
+pub struct ApiError {
+    pub error: ApiErrorDetail,
+}
+#[derive(Serialize)]
+pub struct ApiErrorDetail {
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+}
+#[derive(Deserialize)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+}
+#[derive(Serialize)]
+pub struct HealthResponse {
+    pub maintenance: bool,
+}
+#[derive(Deserialize)]
+pub struct OverviewIncidentQueryParams {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
 #[derive(Deserialize, Debug)]
 pub struct OverviewCount {
     pub project_count: usize,
@@ -63,59 +99,201 @@ pub struct OverviewTask {
     pub period: Option<ProjectTaskPeriodResponse>,
 }
 
+pub struct PathObjectId<T>(pub T);
+
+pub trait FromObjectIds: Sized {
+    fn from_object_ids(ids: &[ObjectId]) -> Option<Self>;
+}
+impl FromObjectIds for ObjectId {
+    fn from_object_ids(ids: &[ObjectId]) -> Option<Self> {
+        match ids {
+            [a] => Some(*a),
+            _ => None,
+        }
+    }
+}
+impl FromObjectIds for (ObjectId, ObjectId) {
+    fn from_object_ids(ids: &[ObjectId]) -> Option<Self> {
+        match ids {
+            [a, b] => Some((*a, *b)),
+            _ => None,
+        }
+    }
+}
+impl FromObjectIds for (ObjectId, ObjectId, ObjectId) {
+    fn from_object_ids(ids: &[ObjectId]) -> Option<Self> {
+        match ids {
+            [a, b, c] => Some((*a, *b, *c)),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromObjectIds> FromRequest for PathObjectId<T> {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let ids = req
+            .match_info()
+            .iter()
+            .map(|(_, value)| value.parse::<ObjectId>())
+            .collect::<Result<Vec<ObjectId>, _>>();
+
+        ready(
+            ids.ok()
+                .and_then(|ids| T::from_object_ids(&ids))
+                .map(PathObjectId)
+                .ok_or_else(|| {
+                    InternalError::from_response(
+                        "INVALID_ID",
+                        HttpResponse::BadRequest().body("INVALID_ID"),
+                    )
+                    .into()
+                }),
+        )
+    }
+}
+
 pub mod company;
 pub mod customer;
 pub mod project;
 pub mod role;
 pub mod user;
 
+fn payload_limit(key: &str) -> usize {
+    std::env::var(key)
+        .ok()
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .expect("INVALID_PAYLOAD_LIMIT")
+}
+
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(payload_limit("MAX_JSON_BYTES"))
+        .error_handler(|error, _| {
+            let message = error.to_string();
+            let field = Regex::new(r"(?:missing|unknown|duplicate) field `(\w+)`")
+                .unwrap()
+                .captures(&message)
+                .map(|a| a[1].to_string());
+            let response = match error {
+                JsonPayloadError::Overflow { .. }
+                | JsonPayloadError::OverflowKnownLength { .. } => HttpResponse::PayloadTooLarge()
+                    .json(ApiError {
+                        error: ApiErrorDetail {
+                            code: "PAYLOAD_TOO_LARGE".to_string(),
+                            message: message.clone(),
+                            field: None,
+                        },
+                    }),
+                _ => HttpResponse::BadRequest().json(ApiError {
+                    error: ApiErrorDetail {
+                        code: "INVALID_BODY".to_string(),
+                        message: message.clone(),
+                        field,
+                    },
+                }),
+            };
+            InternalError::from_response(message, response).into()
+        })
+}
+pub fn multipart_config() -> MultipartFormConfig {
+    MultipartFormConfig::default()
+        .total_limit(payload_limit("MAX_MULTIPART_BYTES"))
+        .error_handler(|error, _| {
+            let message = error.to_string();
+            let response = match error {
+                MultipartError::Payload(PayloadError::Overflow) => HttpResponse::PayloadTooLarge()
+                    .json(ApiError {
+                        error: ApiErrorDetail {
+                            code: "PAYLOAD_TOO_LARGE".to_string(),
+                            message: message.clone(),
+                            field: None,
+                        },
+                    }),
+                _ => HttpResponse::BadRequest().json(ApiError {
+                    error: ApiErrorDetail {
+                        code: "INVALID_BODY".to_string(),
+                        message: message.clone(),
+                        field: None,
+                    },
+                }),
+            };
+            InternalError::from_response(message, response).into()
+        })
+}
+pub fn created_at(location: String) -> HttpResponseBuilder {
+    let mut response = HttpResponse::Created();
+    response.insert_header((
+        "Location",
+        format!("{}{}", std::env::var("BASE_PATH").unwrap(), location),
+    ));
+    response
+}
+
+#[get("/health")]
+pub async fn get_health() -> HttpResponse {
+    HttpResponse::Ok().json(HealthResponse {
+        maintenance: is_maintenance(),
+    })
+}
 #[get("/files")]
-pub async fn get_file(query: web::Query<FileQueryParams>) -> HttpResponse {
-    let path = match query.kind {
-        FileKind::ProjectDocumentation => format!("./files/reports/documentation/{}", query.name),
-        FileKind::CompanyImage => format!("./files/companies/{}", query.name),
-        FileKind::CustomerImage => format!("./files/customers/{}", query.name),
-        FileKind::UserImage => format!("./files/users/{}", query.name),
+pub async fn get_file(query: web::Query<FileQueryParams>, req: HttpRequest) -> HttpResponse {
+    let address = client_address(&req);
+    if !take_file_token(&address) {
+        return HttpResponse::TooManyRequests().body("TOO_MANY_REQUESTS");
+    }
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id,
+        None => None,
     };
-    if let Ok(file) = fs::read(path.clone()) {
-        let mime = from_path(path).first_or_octet_stream();
-        HttpResponse::Ok().content_type(mime).body(file)
-    } else {
-        HttpResponse::NotFound().body("CONTENT_NOT_FOUND")
+    if !query.kind.is_public() && issuer_id.is_none() {
+        return HttpResponse::Forbidden().body("FORBIDDEN");
     }
-}
-#[get("/overview")]
-pub async fn get_overview() -> HttpResponse {
-    let db = get_db();
-    let collection = db.collection::<ProjectTask>("project-tasks");
-
-    let mut overview = Overview {
-        project_count: 0,
-        project_completed: 0,
-        project_completition: 0.0,
-        project: Vec::new(),
-        task: Vec::new(),
+    let path = match query.kind.resolve(&query.name) {
+        Some(path) => path,
+        None => return HttpResponse::NotFound().body("CONTENT_NOT_FOUND"),
     };
-    let mut task_id = Vec::<ObjectId>::new();
 
-    if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
-        _id: None,
-        project_id: None,
-        task_id: None,
-        area_id: None,
-        limit: None,
-        kind: Some(ProjectTaskQueryKind::Dependency),
-    })
-    .await
-    {
-        for task in tasks.iter() {
-            if !task_id.contains(&task._id.unwrap()) {
-                task_id.push(task._id.unwrap());
+    let owner_id = query
+        .name
+        .split('/')
+        .next()
+        .and_then(|a| a.parse::<ObjectId>().ok());
+    let owner = match (&query.kind, owner_id) {
+        (FileKind::ProjectDocumentation, Some(report_id)) => {
+            match ProjectProgressReport::find_by_id(&report_id).await {
+                Ok(Some(report)) => Some((report.project_id, ProjectRolePermission::GetReport)),
+                _ => return HttpResponse::NotFound().body("CONTENT_NOT_FOUND"),
+            }
+        }
+        (FileKind::TaskAttachment, Some(task_id)) => {
+            match ProjectTask::find_by_id(&task_id).await {
+                Ok(Some(task)) => Some((task.project_id, ProjectRolePermission::GetTask)),
+                _ => return HttpResponse::NotFound().body("CONTENT_NOT_FOUND"),
             }
         }
+        (FileKind::ProjectDocumentation, None) | (FileKind::TaskAttachment, None) => {
+            return HttpResponse::NotFound().body("CONTENT_NOT_FOUND")
+        }
+        _ => None,
+    };
+    if let (Some((project_id, permission)), Some(issuer_id)) = (owner, issuer_id) {
+        if !ProjectRole::validate(&project_id, &issuer_id, &permission).await {
+            return HttpResponse::Forbidden().body("FORBIDDEN");
+        }
     }
 
-    let pipeline = vec![
+    if let Ok(file) = fs::read(&path) {
+        let mime = from_path(&path).first_or_octet_stream();
+        HttpResponse::Ok().content_type(mime).body(file)
+    } else {
+        HttpResponse::NotFound().body("CONTENT_NOT_FOUND")
+    }
+}
+fn overview_pipeline(task_id: &[ObjectId]) -> Vec<Document> {
+    vec![
         doc! {
             "$match": {
                 "$expr": {
@@ -131,7 +309,7 @@ pub async fn get_overview() -> HttpResponse {
                         {
                             "$ne": [
                                 {
-                                    "$in": ["$_id", to_bson::<Vec<ObjectId>>(&task_id).unwrap()]
+                                    "$in": ["$_id", to_bson::<[ObjectId]>(task_id).unwrap()]
                                 },
                                 to_bson::<bool>(&true).unwrap()
                             ]
@@ -184,10 +362,10 @@ pub async fn get_overview() -> HttpResponse {
                             "area": "$area",
                             "name": "$name",
                             "code": "$code",
-                            "status": "$status",
+                            "status": status_to_string("$status"),
                             "period": {
-                                "start": { "$toString": "$period.start" },
-                                "end": { "$toString": "$period.end" },
+                                "start": date_to_string("$period.start"),
+                                "end": date_to_string("$period.end"),
                             },
                             "progress": to_bson::<Option<ProjectProgressResponse>>(&None).unwrap()
                         }
@@ -256,12 +434,8 @@ pub async fn get_overview() -> HttpResponse {
                     "$cond": [
                         "$period",
                         {
-                            "start": {
-                                "$toString": "$period.start"
-                            },
-                            "end": {
-                                "$toString": "$period.end"
-                            },
+                            "start": date_to_string("$period.start"),
+                            "end": date_to_string("$period.end"),
                         },
                         to_bson::<Option<ObjectId>>(&None).unwrap()
                     ]
@@ -284,7 +458,52 @@ pub async fn get_overview() -> HttpResponse {
                 "period": "$period"
             }
         },
-    ];
+    ]
+}
+async fn overview_dependency_ids() -> Vec<ObjectId> {
+    let mut task_id = Vec::<ObjectId>::new();
+
+    if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
+        _id: None,
+        project_id: None,
+        task_id: None,
+        area_id: None,
+        limit: None,
+        kind: Some(ProjectTaskQueryKind::Dependency),
+    })
+    .await
+    {
+        for task in tasks.iter() {
+            if !task_id.contains(&task._id.unwrap()) {
+                task_id.push(task._id.unwrap());
+            }
+        }
+    }
+
+    task_id
+}
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[get("/overview")]
+pub async fn get_overview() -> HttpResponse {
+    let db = get_db();
+    let collection = db.collection::<ProjectTask>("project-tasks");
+
+    let mut overview = Overview {
+        project_count: 0,
+        project_completed: 0,
+        project_completition: 0.0,
+        project: Vec::new(),
+        task: Vec::new(),
+    };
+    let task_id = overview_dependency_ids().await;
+    let pipeline = overview_pipeline(&task_id);
 
     if let Ok(mut cursor) = collection.aggregate(pipeline, None).await {
         while let Some(Ok(doc)) = cursor.next().await {
@@ -295,16 +514,24 @@ pub async fn get_overview() -> HttpResponse {
                 .find(|a| &a._id == &task.project._id)
                 .is_none()
             {
-                let mut project = task.project.clone();
-                project.progress =
-                    Project::calculate_progress(&project._id.parse::<ObjectId>().unwrap())
-                        .await
-                        .map_or_else(|_| None, Some);
-                overview.project.push(project);
+                overview.project.push(task.project.clone());
             }
             overview.task.push(task);
         }
 
+        let project_id = overview
+            .project
+            .iter()
+            .map(|project| project._id.parse::<ObjectId>().unwrap())
+            .collect::<Vec<ObjectId>>();
+        for (project, progress) in overview
+            .project
+            .iter_mut()
+            .zip(Project::calculate_progress_many(&project_id).await)
+        {
+            project.progress = progress;
+        }
+
         let collection = db.collection::<ProjectTask>("projects");
         let pipeline = vec![doc! {
             "$group": {
@@ -363,3 +590,117 @@ pub async fn get_overview() -> HttpResponse {
 
     HttpResponse::Ok().json(overview)
 }
+#[get("/overview.csv")]
+pub async fn get_overview_csv() -> HttpResponse {
+    let db = get_db();
+    let collection = db.collection::<ProjectTask>("project-tasks");
+
+    let task_id = overview_dependency_ids().await;
+    let pipeline = overview_pipeline(&task_id);
+
+    let cursor = match collection.aggregate(pipeline, None).await {
+        Ok(cursor) => cursor,
+        Err(_) => return HttpResponse::InternalServerError().body("OVERVIEW_NOT_FOUND"),
+    };
+
+    let header = stream::once(async {
+        Ok::<Bytes, actix_web::Error>(Bytes::from(
+            "project_code,area,task,period_start,period_end\n",
+        ))
+    });
+    let rows = cursor.filter_map(|doc| async move {
+        let task = from_document::<OverviewTask>(doc.ok()?).ok()?;
+        let (start, end) = match task.period {
+            Some(period) => (period.start, period.end),
+            None => (String::new(), String::new()),
+        };
+        let row = [task.project.code, task.area.name, task.name, start, end]
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<String>>()
+            .join(",");
+        Some(Ok::<Bytes, actix_web::Error>(Bytes::from(format!(
+            "{}\n",
+            row
+        ))))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/csv")
+        .insert_header((
+            "Content-Disposition",
+            "attachment; filename=\"overview.csv\"",
+        ))
+        .streaming(header.chain(rows))
+}
+#[get("/overview/incidents")]
+pub async fn get_overview_incidents(
+    query: web::Query<OverviewIncidentQueryParams>,
+) -> HttpResponse {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return HttpResponse::BadRequest().body("INVALID_PERIOD");
+        }
+    }
+
+    match ProjectIncidentReport::find_many_overview(&ProjectIncidentReportQuery {
+        project_id: None,
+        from: query.from.map(DateTime::from_millis),
+        to: query.to.map(DateTime::from_millis),
+    })
+    .await
+    {
+        Ok(incidents) => HttpResponse::Ok().json(incidents),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+
+#[put("/maintenance")]
+pub async fn update_maintenance(
+    payload: web::Json<MaintenanceRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if issuer_role.is_empty() || !Role::validate(&issuer_role, &RolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    set_maintenance(payload.enabled);
+
+    HttpResponse::Ok().json(HealthResponse {
+        maintenance: is_maintenance(),
+    })
+}
+#[get("/admin/files/verify")]
+pub async fn get_admin_files_verify(req: HttpRequest) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if issuer_role.is_empty() || !Role::validate(&issuer_role, &RolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectProgressReport::verify_documentation(false).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[post("/admin/files/verify")]
+pub async fn fix_admin_files(req: HttpRequest) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if issuer_role.is_empty() || !Role::validate(&issuer_role, &RolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectProgressReport::verify_documentation(true).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
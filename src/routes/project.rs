@@ -1,51 +1,539 @@
 use std::{
     cmp,
+    collections::BTreeMap,
     ffi::OsStr,
-    fs::{self, create_dir_all, remove_dir_all, rename},
+    fs::{self, create_dir_all, rename},
+    io::{self, Write},
     path::{Path, PathBuf},
     vec,
 };
 
-use actix_multipart::form::MultipartForm;
+use actix_multipart::form::{tempfile::TempFile, MultipartForm};
 use actix_web::{delete, get, post, put, web, HttpMessage, HttpRequest, HttpResponse};
-use chrono::{FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono::{
+    Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone,
+    Utc, Weekday,
+};
+use futures::{channel::mpsc, executor::block_on, SinkExt};
 use mongodb::bson::{doc, oid::ObjectId, to_bson, DateTime};
-use serde::Deserialize;
+use plotters::prelude::{
+    BitMapBackend, ChartBuilder, Color, IntoDrawingArea, LineSeries, RGBColor, WHITE,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
 
+use super::{created_at, PathObjectId};
 use crate::models::{
+    notification::{Notification, NotificationKind},
+    page_limit,
     project::{
-        Project, ProjectArea, ProjectAreaRequest, ProjectMemberKind, ProjectMemberRequest,
-        ProjectPeriod, ProjectProgressGraphResponse, ProjectQuery, ProjectQuerySortKind,
-        ProjectQueryStatusKind, ProjectRequest, ProjectStatus, ProjectStatusKind,
+        Project, ProjectArea, ProjectAreaMergeRequest, ProjectAreaRequest, ProjectBaseline,
+        ProjectBudgetRequest, ProjectFullRequest, ProjectMemberBulkErrorResponse,
+        ProjectMemberBulkRow, ProjectMemberKind, ProjectMemberMultipartRequest, ProjectMemberQuery,
+        ProjectMemberReassignRequest, ProjectMemberRequest, ProjectMinResponse, ProjectPeriod,
+        ProjectPlanCoverageResponse, ProjectProgressGraphResponse, ProjectProgressWeeklyResponse,
+        ProjectQuery, ProjectQuerySortKind, ProjectQueryStatusKind, ProjectReportQuery,
+        ProjectRequest, ProjectStatus, ProjectStatusKind,
+    },
+    project_incident_report::{
+        ProjectIncidentReport, ProjectIncidentReportQuery, ProjectIncidentReportRequest,
     },
-    project_incident_report::{ProjectIncidentReport, ProjectIncidentReportRequest},
     project_progress_report::{
-        ProjectProgressReport, ProjectProgressReportDocumentation,
+        ProjectProgressReport, ProjectProgressReportCoverRequest,
+        ProjectProgressReportCreateResponse, ProjectProgressReportDocumentation,
         ProjectProgressReportDocumentationMultipartRequest, ProjectProgressReportQuery,
-        ProjectProgressReportRequest,
+        ProjectProgressReportRequest, ProjectProgressReportSlotResponse,
     },
     project_role::{ProjectRole, ProjectRolePermission, ProjectRoleRequest},
     project_task::{
-        ProjectTask, ProjectTaskMinResponse, ProjectTaskMultipartRequest, ProjectTaskPeriod,
-        ProjectTaskPeriodRequest, ProjectTaskQuery, ProjectTaskQueryKind, ProjectTaskRequest,
-        ProjectTaskStatus, ProjectTaskStatusKind, ProjectTaskStatusRequest,
-        ProjectTaskTimelineQuery, ProjectTaskVolume,
+        ProjectTask, ProjectTaskAreaResponse, ProjectTaskAttachment,
+        ProjectTaskAttachmentMultipartRequest, ProjectTaskBreadcrumbResponse,
+        ProjectTaskBreadcrumbTaskResponse, ProjectTaskChecklist, ProjectTaskChecklistRequest,
+        ProjectTaskChecklistUpdateRequest, ProjectTaskCost, ProjectTaskDeleteRequest,
+        ProjectTaskDeltaResponse, ProjectTaskDuplicateRequest, ProjectTaskMultipartRequest,
+        ProjectTaskPeriod, ProjectTaskPeriodRequest, ProjectTaskPeriodResponse, ProjectTaskQuery,
+        ProjectTaskQueryKind, ProjectTaskRequest, ProjectTaskShiftRequest, ProjectTaskStatus,
+        ProjectTaskStatusKind, ProjectTaskStatusRequest, ProjectTaskTimelineQuery,
+        ProjectTaskVolume,
     },
+    project_task_comment::{ProjectTaskComment, ProjectTaskCommentRequest},
     role::{Role, RolePermission},
+    sync_timestamp,
+    tombstone::{Tombstone, TombstoneKind},
     user::UserAuthentication,
+    CountResponse, CursorPageResponse, FileKind, PageResponse,
 };
 
+struct ZipChunkWriter(mpsc::Sender<Result<web::Bytes, io::Error>>);
+
+impl Write for ZipChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        block_on(self.0.send(Ok(web::Bytes::copy_from_slice(buf))))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "STREAM_CLOSED"))?;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn set_bulk_task_end(task: &mut ProjectTask, value: &str, date_format: &str) -> Result<(), String> {
+    if let Some(period) = task.period.as_mut() {
+        match NaiveDate::parse_from_str(value, date_format) {
+            Ok(date) => {
+                period.end = DateTime::from_millis(
+                    NaiveDateTime::new(
+                        date,
+                        NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap(),
+                    )
+                    .timestamp_millis(),
+                )
+            }
+            Err(_) if !value.is_empty() => return Err("INVALID_DATE_FORMAT".to_string()),
+            _ => (),
+        }
+        if period.end < period.start {
+            return Err("INVALID_PERIOD".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn set_bulk_task_cost(task: &mut ProjectTask, value: &str) -> Result<(), String> {
+    if !value.is_empty() {
+        match ProjectTaskCost::parse(value) {
+            Some(cost) => task.cost = Some(cost),
+            None => return Err("INVALID_COST".to_string()),
+        }
+    }
+    Ok(())
+}
+
+fn read_bulk_file(file: &TempFile) -> Result<Vec<u8>, Box<HttpResponse>> {
+    let path = file.file.path();
+
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return Err(Box::new(
+                HttpResponse::BadRequest().body("PROJECT_TASK_CSV_UPLOAD_FAILED"),
+            ));
+        }
+    };
+    if fs::remove_file(path).is_err() {
+        return Err(Box::new(
+            HttpResponse::InternalServerError().body("PROJECT_TASK_CSV_DELETE_FAILED".to_string()),
+        ));
+    }
+
+    Ok(bytes)
+}
+
+fn parse_bulk_tasks(
+    bytes: &[u8],
+    project_id: ObjectId,
+    query: &ProjectTaskBulkQueryParams,
+) -> Result<(Vec<ProjectArea>, Vec<ProjectTask>), Box<HttpResponse>> {
+    let mut row_index = -1;
+    let mut data_index = 0;
+    let mut data = String::new();
+    let mut area_index = 0;
+    let mut areas = Vec::<ProjectArea>::new();
+    let mut task_level = 0;
+    let mut task_value = Vec::<(usize, f64)>::new();
+    let mut tasks = Vec::<ProjectTask>::new();
+    let mut task: Option<ProjectTask> = None;
+    let mut total = 0.0;
+    let mut row_errors = Vec::<ProjectTaskBulkRowError>::new();
+    let date_format = match query.date_format {
+        Some(ProjectTaskBulkDateFormat::YearMonthDay) => "%Y-%m-%d",
+        _ => "%d-%m-%Y",
+    };
+    for index in 1..=bytes.len() {
+        let string = if index == bytes.len() {
+            String::from_utf8_lossy(&bytes[(index - 1)..])
+        } else {
+            String::from_utf8_lossy(&bytes[(index - 1)..index])
+        };
+
+        if string == "\n" {
+            if let Some(mut task) = task {
+                let result = if data_index == 7 {
+                    set_bulk_task_cost(&mut task, data.trim())
+                } else {
+                    set_bulk_task_end(&mut task, data.trim(), date_format)
+                };
+                if let Err(error) = result {
+                    row_errors.push(ProjectTaskBulkRowError {
+                        row: row_index + 2,
+                        value: data.trim().to_string(),
+                        error,
+                    });
+                }
+                tasks.push(task);
+            }
+            task = None;
+            row_index += 1;
+            data_index = 0;
+            data.clear();
+        } else if string == "," || string == ";" {
+            if row_index >= 0 {
+                if data_index == 0 && !data.is_empty() {
+                    if !areas.is_empty() {
+                        area_index += 1;
+                    }
+                    areas.push(ProjectArea {
+                        _id: ObjectId::new(),
+                        name: data.clone(),
+                        description: None,
+                        update_date: None,
+                    });
+                } else if data_index == 1 && !data.is_empty() {
+                    let mut task_id: Option<ObjectId> = None;
+                    let mut level = 0;
+                    let name = data
+                        .clone()
+                        .chars()
+                        .filter(|c| {
+                            if *c == '_' {
+                                level += 1;
+                                false
+                            } else {
+                                true
+                            }
+                        })
+                        .collect();
+
+                    match level.cmp(&task_level) {
+                        cmp::Ordering::Greater => task_level += 1,
+                        cmp::Ordering::Less => {
+                            let mut total = 0.0;
+                            let task_complete = &task_value[level..];
+                            for (index, value) in task_complete.iter().rev() {
+                                let task_id = tasks.get(*index).unwrap()._id;
+                                for (index, task) in tasks[*index..].iter_mut().enumerate() {
+                                    if index == 0 {
+                                        total += *value;
+                                        task.value = total;
+                                    } else if task.task_id == task_id && task.value != 0.0 {
+                                        task.value = task.value / total * 100.0;
+                                    }
+                                }
+                            }
+                            task_value.truncate(level);
+                            task_level = level;
+                        }
+                        _ => (),
+                    };
+
+                    if let Some((index, _)) = task_value.last() {
+                        task_id = tasks.get(*index).unwrap()._id;
+                    }
+
+                    task = Some(ProjectTask {
+                        _id: Some(ObjectId::new()),
+                        project_id,
+                        area_id: areas.get(area_index).unwrap()._id,
+                        task_id,
+                        user_id: None,
+                        name,
+                        description: None,
+                        period: None,
+                        status: vec![ProjectTaskStatus {
+                            kind: ProjectTaskStatusKind::Pending,
+                            time: DateTime::from_millis(Utc::now().timestamp_millis()),
+                            message: None,
+                        }],
+                        volume: None,
+                        value: 0.0,
+                        cost: None,
+                        attachment: None,
+                        checklist: None,
+                        update_date: None,
+                    });
+                } else if data_index == 2 && !data.is_empty() {
+                    if let Some(task) = task.as_mut() {
+                        if let Some(value) = data
+                            .trim()
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|value| value.is_finite() && *value >= 0.0)
+                        {
+                            task.volume = Some(ProjectTaskVolume {
+                                value,
+                                unit: "pcs".to_owned(),
+                            });
+                        }
+                    }
+                } else if data_index == 3 && !data.is_empty() {
+                    if let Some(task) = task.as_mut() {
+                        if let Some(volume) = task.volume.as_mut() {
+                            volume.unit = data.clone();
+                        }
+                    }
+                } else if data_index == 4 {
+                    if let Some(task) = task.as_mut() {
+                        if let Ok(value) = data.parse::<f64>() {
+                            if let Some(parent_value) = task_value.last_mut() {
+                                parent_value.1 =
+                                    ((parent_value.1 + value) * 1e6_f64).round() / 1e6_f64;
+                            }
+                            total = ((total + value) * 1e6_f64).round() / 1e6_f64;
+                            task.value = value;
+                        } else {
+                            task_value.push((tasks.len(), 0.0));
+                            task.value = 0.0;
+                        }
+                    }
+                } else if data_index == 5 && !data.is_empty() {
+                    if let Some(task) = task.as_mut() {
+                        if let Ok(date) = NaiveDate::parse_from_str(data.trim(), date_format) {
+                            task.period = Some(ProjectTaskPeriod {
+                                start: DateTime::from_millis(
+                                    NaiveDateTime::new(
+                                        date,
+                                        NaiveTime::from_hms_milli_opt(0, 0, 0, 0).unwrap(),
+                                    )
+                                    .timestamp_millis(),
+                                ),
+                                end: DateTime::from_millis(
+                                    NaiveDateTime::new(
+                                        date,
+                                        NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap(),
+                                    )
+                                    .timestamp_millis(),
+                                ),
+                            });
+                        } else {
+                            row_errors.push(ProjectTaskBulkRowError {
+                                row: row_index + 2,
+                                value: data.trim().to_string(),
+                                error: "INVALID_DATE_FORMAT".to_string(),
+                            });
+                        }
+                    }
+                } else if data_index == 6 {
+                    if let Some(task) = task.as_mut() {
+                        if let Err(error) = set_bulk_task_end(task, data.trim(), date_format) {
+                            row_errors.push(ProjectTaskBulkRowError {
+                                row: row_index + 2,
+                                value: data.trim().to_string(),
+                                error,
+                            });
+                        }
+                    }
+                }
+            }
+
+            data_index += 1;
+            data.clear();
+        } else {
+            data.push_str(&string);
+        }
+    }
+
+    if !ProjectTask::is_value_complete(total) {
+        return Err(Box::new(
+            HttpResponse::BadRequest().body("PROJECT_TASK_INVALID_VALUE"),
+        ));
+    }
+
+    if let Some(mut task) = task {
+        let result = if data_index == 7 {
+            set_bulk_task_cost(&mut task, data.trim())
+        } else {
+            set_bulk_task_end(&mut task, data.trim(), date_format)
+        };
+        if let Err(error) = result {
+            row_errors.push(ProjectTaskBulkRowError {
+                row: row_index + 2,
+                value: data.trim().to_string(),
+                error,
+            });
+        }
+        tasks.push(task);
+    }
+
+    if !row_errors.is_empty() {
+        let error = ["INVALID_DATE_FORMAT", "INVALID_PERIOD", "INVALID_COST"]
+            .into_iter()
+            .find(|error| row_errors.iter().any(|a| a.error == *error))
+            .unwrap_or("INVALID_PERIOD");
+        return Err(Box::new(HttpResponse::BadRequest().json(
+            ProjectTaskBulkErrorResponse {
+                error: error.to_string(),
+                row: row_errors,
+            },
+        )));
+    }
+
+    let mut total = 0.0;
+    for (index, value) in task_value.iter().rev() {
+        let task_id = tasks.get(*index).unwrap()._id;
+        for (index, task) in tasks[*index..].iter_mut().enumerate() {
+            if index == 0 {
+                total += *value;
+                task.value = total;
+            } else if task.task_id == task_id && task.value != 0.0 {
+                task.value = task.value / total * 100.0;
+            }
+        }
+    }
+
+    Ok((areas, tasks))
+}
+
+fn split_bulk_line(line: &str) -> Vec<String> {
+    let mut column = Vec::<String>::new();
+    let mut data = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                data.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' | ';' if !quoted => {
+                column.push(data.trim().to_string());
+                data.clear();
+            }
+            _ => data.push(c),
+        }
+    }
+    column.push(data.trim().to_string());
+
+    column
+}
+
+fn parse_bulk_members(
+    csv: &str,
+) -> (
+    Vec<ProjectMemberBulkRow>,
+    Vec<ProjectMemberBulkErrorResponse>,
+) {
+    let mut rows = Vec::<ProjectMemberBulkRow>::new();
+    let mut errors = Vec::<ProjectMemberBulkErrorResponse>::new();
+
+    // Columns are email, roles separated by "|", and an optional kind; the first row is a header
+    for (index, line) in csv.lines().enumerate().skip(1) {
+        let row = index + 1;
+        let column = split_bulk_line(line);
+        let email = column.first().cloned().unwrap_or_default();
+        if email.is_empty() {
+            continue;
+        }
+
+        let kind = match column.get(2).map_or("", |a| a.as_str()) {
+            "" | "direct" => ProjectMemberKind::Direct,
+            "indirect" => ProjectMemberKind::Indirect,
+            value => {
+                errors.push(ProjectMemberBulkErrorResponse {
+                    row,
+                    value: value.to_string(),
+                    error: "INVALID_MEMBER_KIND".to_string(),
+                });
+                continue;
+            }
+        };
+        let role = column
+            .get(1)
+            .map_or("", |a| a.as_str())
+            .split('|')
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .map(|a| a.to_string())
+            .collect();
+
+        rows.push(ProjectMemberBulkRow {
+            row,
+            email,
+            role,
+            kind,
+        });
+    }
+
+    (rows, errors)
+}
+
 #[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ProjectTaskQueryParamsKind {
     Full,
     Default,
+    Root,
+    Dependency,
+    Base,
+}
+#[derive(Deserialize)]
+pub struct ProjectChangeQueryParams {
+    pub since: i64,
 }
 #[derive(Deserialize, Clone)]
 pub struct ProjectTaskQueryParams {
     pub area_id: Option<ObjectId>,
+    pub user_id: Option<ObjectId>,
     pub status: Option<ProjectTaskStatusKind>,
     pub kind: Option<ProjectTaskQueryParamsKind>,
+    pub progress_min: Option<f64>,
+    pub progress_max: Option<f64>,
+    pub since: Option<i64>,
+}
+
+impl ProjectTaskQueryParams {
+    fn has_progress_range(&self) -> bool {
+        self.progress_min.is_some() || self.progress_max.is_some()
+    }
+    fn is_valid_progress_range(&self) -> bool {
+        let min = self.progress_min.unwrap_or(0.0);
+        let max = self.progress_max.unwrap_or(100.0);
+        min.is_finite() && max.is_finite() && min <= max
+    }
+    fn in_progress_range(&self, progress: f64) -> bool {
+        self.progress_min.is_none_or(|min| progress >= min)
+            && self.progress_max.is_none_or(|max| progress <= max)
+    }
+}
+#[derive(Deserialize)]
+pub enum ProjectTaskBulkDateFormat {
+    #[serde(rename = "dd-mm-yyyy")]
+    DayMonthYear,
+    #[serde(rename = "yyyy-mm-dd")]
+    YearMonthDay,
+}
+#[derive(Deserialize)]
+pub struct ProjectTaskBulkQueryParams {
+    pub date_format: Option<ProjectTaskBulkDateFormat>,
+}
+#[derive(Serialize)]
+pub struct ProjectTaskBulkRowError {
+    pub row: i64,
+    pub value: String,
+    pub error: String,
+}
+#[derive(Serialize)]
+pub struct ProjectTaskBulkErrorResponse {
+    pub error: String,
+    pub row: Vec<ProjectTaskBulkRowError>,
+}
+#[derive(Serialize)]
+pub struct ProjectTaskBulkPreviewAreaResponse {
+    pub _id: String,
+    pub name: String,
+    pub task: Vec<ProjectTaskBulkPreviewTaskResponse>,
+}
+#[derive(Serialize)]
+pub struct ProjectTaskBulkPreviewTaskResponse {
+    pub _id: String,
+    pub task_id: Option<String>,
+    pub depth: usize,
+    pub name: String,
+    pub value: f64,
+    pub volume: Option<ProjectTaskVolume>,
+    pub period: Option<ProjectTaskPeriodResponse>,
+    pub cost: Option<ProjectTaskCost>,
 }
 #[derive(Deserialize)]
 pub struct ProjectIncidentReportQueryParams {
@@ -56,8 +544,44 @@ pub struct ProjectStatusQueryParams {
     pub status: ProjectStatusKind,
 }
 #[derive(Deserialize)]
+pub struct ProjectTaskDeleteQueryParams {
+    pub cascade: Option<bool>,
+}
+#[derive(Deserialize)]
+pub struct ProjectAreaDeleteQueryParams {
+    pub move_to: Option<ObjectId>,
+}
+#[derive(Deserialize)]
+pub struct ProjectEvmQueryParams {
+    pub actual_cost: Option<f64>,
+}
+#[derive(Deserialize)]
+pub struct ProjectProgressDeltaQueryParams {
+    pub from: i64,
+    pub to: i64,
+}
+#[derive(Deserialize)]
+pub struct ProjectReportQueryParams {
+    pub area_id: Option<ObjectId>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+#[derive(Deserialize)]
 pub struct ProjectProgressQueryParams {
     pub area_id: Option<ObjectId>,
+    pub baseline: Option<bool>,
+}
+#[derive(Deserialize)]
+pub struct ProjectProgressImageQueryParams {
+    pub area_id: Option<ObjectId>,
+    pub baseline: Option<bool>,
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+}
+#[derive(Deserialize)]
+pub struct ProjectMemberQueryParams {
+    pub kind: Option<ProjectMemberKind>,
+    pub role_id: Option<ObjectId>,
 }
 #[derive(Deserialize)]
 pub struct ProjectQueryParams {
@@ -67,59 +591,20 @@ pub struct ProjectQueryParams {
     pub limit: Option<usize>,
     pub skip: Option<usize>,
 }
-
-#[get("/projects")]
-pub async fn get_projects(query: web::Query<ProjectQueryParams>) -> HttpResponse {
-    match Project::find_many(&ProjectQuery {
-        status: query.status.clone(),
-        sort: query.sort.clone(),
-        text: query.text.clone(),
-        limit: query.limit,
-        skip: query.skip,
-    })
-    .await
-    {
-        Ok(Some(projects)) => HttpResponse::Ok().json(projects),
-        Ok(None) => HttpResponse::NotFound().body("PROJECT_NOT_FOUND"),
-        Err(error) => HttpResponse::InternalServerError().body(error),
-    }
-}
-#[get("/projects/{project_id}")]
-pub async fn get_project(project_id: web::Path<String>) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
-    match Project::find_detail_by_id(&project_id).await {
-        Ok(Some(project)) => HttpResponse::Ok().json(project),
-        Ok(None) => HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
-        Err(error) => HttpResponse::InternalServerError().body(error),
-    }
+#[derive(Deserialize)]
+pub struct ProjectRecentQueryParams {
+    pub limit: Option<usize>,
 }
-#[get("/projects/{project_id}/areas")]
-pub async fn get_project_areas(project_id: web::Path<String>) -> HttpResponse {
-    let project_id: ObjectId = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
-    match ProjectTask::find_many_area(&project_id).await {
-        Ok(Some(project)) => HttpResponse::Ok().json(project),
-        Ok(None) => HttpResponse::NotFound().body("PROJECT_AREA_NOT_FOUND".to_string()),
-        Err(error) => HttpResponse::InternalServerError().body(error),
-    }
+#[derive(Deserialize)]
+pub struct ProjectEndingQueryParams {
+    pub days: Option<i64>,
+    pub limit: Option<usize>,
 }
-#[get("/projects/{project_id}/tasks")]
-pub async fn get_project_tasks(
-    project_id: web::Path<String>,
-    query: web::Query<ProjectTaskQueryParams>,
-) -> HttpResponse {
-    let project_id: ObjectId = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
 
+fn task_timeline_query(
+    project_id: ObjectId,
+    query: &ProjectTaskQueryParams,
+) -> ProjectTaskTimelineQuery {
     let mut task_query = ProjectTaskTimelineQuery {
         project_id,
         area_id: query.area_id,
@@ -127,60 +612,46 @@ pub async fn get_project_tasks(
         status: query.status.clone(),
         relative: false,
         subtask: false,
+        kind: None,
+        user_id: query.user_id,
+        since: query.since.map(DateTime::from_millis),
     };
 
-    if query.kind == Some(ProjectTaskQueryParamsKind::Full) {
-        task_query.area_id = None;
-        task_query.status = None;
-        task_query.relative = true;
-    }
-
-    match ProjectTask::find_many_timeline(&task_query).await {
-        Ok(Some(tasks)) => HttpResponse::Ok().json(tasks),
-        Ok(None) => HttpResponse::Ok().json(Vec::<ProjectTaskMinResponse>::new()),
-        Err(error) => HttpResponse::InternalServerError().body(error),
-    }
-}
-#[get("/projects/{project_id}/tasks/{task_id}")]
-pub async fn get_project_task(_id: web::Path<(String, String)>, req: HttpRequest) -> HttpResponse {
-    let (project_id, task_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(task_id)) => (project_id, task_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
-    let issuer_id = match req.extensions().get::<UserAuthentication>() {
-        Some(issuer) => issuer._id.unwrap(),
-        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
-    };
-    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTask).await {
-        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    match query.kind {
+        Some(ProjectTaskQueryParamsKind::Full) => {
+            task_query.area_id = None;
+            task_query.status = None;
+            task_query.relative = true;
+        }
+        Some(ProjectTaskQueryParamsKind::Root) => {
+            task_query.kind = Some(ProjectTaskQueryKind::Root);
+        }
+        Some(ProjectTaskQueryParamsKind::Dependency) => {
+            task_query.kind = Some(ProjectTaskQueryKind::Dependency);
+        }
+        Some(ProjectTaskQueryParamsKind::Base) => {
+            task_query.kind = Some(ProjectTaskQueryKind::Base);
+        }
+        _ => (),
     }
 
-    match ProjectTask::find_detail_by_id(&task_id).await {
-        Ok(Some(project)) => HttpResponse::Ok().json(project),
-        Ok(None) => HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
-        Err(error) => HttpResponse::InternalServerError().body(error),
-    }
+    task_query
 }
-#[get("/projects/{project_id}/progress")]
-pub async fn get_project_progress(
-    project_id: web::Path<String>,
-    query: web::Query<ProjectProgressQueryParams>,
-) -> HttpResponse {
-    let project_id: ObjectId = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
 
+async fn progress_graph(
+    project_id: &ObjectId,
+    area_id: Option<ObjectId>,
+    baseline: bool,
+) -> Result<Vec<ProjectProgressGraphResponse>, Box<HttpResponse>> {
     let mut bases: Vec<ProjectTask> = Vec::new();
     let mut dependencies: Vec<ProjectTask> = Vec::new();
     let mut progresses: Vec<ProjectProgressReport> = Vec::new();
 
     if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
         _id: None,
-        project_id: Some(project_id),
+        project_id: Some(*project_id),
         task_id: None,
-        area_id: query.area_id,
+        area_id,
         limit: None,
         kind: Some(ProjectTaskQueryKind::Base),
     })
@@ -190,9 +661,9 @@ pub async fn get_project_progress(
     }
     if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
         _id: None,
-        project_id: Some(project_id),
+        project_id: Some(*project_id),
         task_id: None,
-        area_id: query.area_id,
+        area_id,
         limit: None,
         kind: Some(ProjectTaskQueryKind::Dependency),
     })
@@ -201,7 +672,7 @@ pub async fn get_project_progress(
         dependencies = tasks;
     }
     if let Ok(Some(reports)) = ProjectProgressReport::find_many(ProjectProgressReportQuery {
-        project_id,
+        project_id: *project_id,
         area_id: None,
     })
     .await
@@ -211,22 +682,41 @@ pub async fn get_project_progress(
 
     if !bases.is_empty() && !dependencies.is_empty() {
         for task in bases.iter_mut() {
-            let mut _id = task.task_id;
-            let mut found = true;
-            while found {
-                if let Some(task_id) = _id {
-                    if let Some(index) = dependencies.iter().position(|a| a._id.unwrap() == task_id)
-                    {
-                        task.value *= dependencies[index].value / 100.0;
-                        _id = dependencies[index].task_id;
-                    }
-                } else {
-                    found = false;
-                }
-            }
+            task.value = ProjectTask::effective_value(task.value, task.task_id, &dependencies);
         }
     }
 
+    let mut baselines: Option<Vec<(f64, ProjectTaskPeriod)>> = None;
+    if baseline {
+        let project = match Project::find_by_id(project_id).await {
+            Ok(Some(project)) => project,
+            _ => {
+                return Err(Box::new(
+                    HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+                ))
+            }
+        };
+        let baseline = match project.baseline {
+            Some(baseline) => baseline,
+            None => {
+                return Err(Box::new(
+                    HttpResponse::NotFound().body("PROJECT_BASELINE_NOT_FOUND".to_string()),
+                ))
+            }
+        };
+        baselines = Some(
+            bases
+                .iter()
+                .filter_map(|a| {
+                    baseline
+                        .iter()
+                        .find(|b| a._id == Some(b.task_id))
+                        .map(|b| (a.value, b.period.clone()))
+                })
+                .collect(),
+        );
+    }
+
     let mut start_base = false;
     let mut start = 0;
     let mut end_base = false;
@@ -234,8 +724,9 @@ pub async fn get_project_progress(
 
     if let Some(date) = bases
         .iter()
-        .filter(|a| a.period.is_some())
-        .map(|a| a.period.clone().unwrap().start.timestamp_millis())
+        .filter_map(|a| a.period.as_ref())
+        .chain(baselines.iter().flatten().map(|(_, a)| a))
+        .map(|a| a.start.timestamp_millis())
         .min()
     {
         start = date;
@@ -249,8 +740,9 @@ pub async fn get_project_progress(
 
     if let Some(date) = bases
         .iter()
-        .filter(|a| a.period.is_some())
-        .map(|a| a.period.clone().unwrap().end.timestamp_millis())
+        .filter_map(|a| a.period.as_ref())
+        .chain(baselines.iter().flatten().map(|(_, a)| a))
+        .map(|a| a.end.timestamp_millis())
         .max()
     {
         end = date;
@@ -264,7 +756,11 @@ pub async fn get_project_progress(
 
     let mut datas: Vec<ProjectProgressGraphResponse> = vec![ProjectProgressGraphResponse {
         x: start - 86400000,
-        y: vec![0.0, 0.0],
+        y: if baselines.is_some() {
+            vec![0.0, 0.0, 0.0]
+        } else {
+            vec![0.0, 0.0]
+        },
     }];
 
     if start != 0 {
@@ -273,7 +769,7 @@ pub async fn get_project_progress(
         for i in 0..diff {
             let date = start + i * 86400000;
             let prev_y1 = datas.last().map_or_else(|| 0.0, |v| *v.y.first().unwrap());
-            let prev_y2 = datas.last().map_or_else(|| 0.0, |v| *v.y.last().unwrap());
+            let prev_y2 = datas.last().map_or_else(|| 0.0, |v| v.y[1]);
             let mut y1: f64 = bases
                 .iter()
                 .filter(|a| {
@@ -286,10 +782,7 @@ pub async fn get_project_progress(
                     }
                 })
                 .fold(prev_y1, |a, b| {
-                    let period = b.period.as_ref().unwrap();
-                    let start = period.start.timestamp_millis();
-                    let end = period.end.timestamp_millis();
-                    let diff = (end - start) / 86400000 + 1;
+                    let diff = b.period.as_ref().unwrap().duration_days();
                     a + (b.value / (diff as f64))
                 });
             let mut y2 = progresses
@@ -331,441 +824,2066 @@ pub async fn get_project_progress(
                 y2 = 100.0
             }
 
-            let data = ProjectProgressGraphResponse {
-                x: date,
-                y: vec![y1, y2],
-            };
+            let mut y = vec![y1, y2];
+            if let Some(baselines) = baselines.as_ref() {
+                let prev_y3 = datas.last().and_then(|v| v.y.get(2)).map_or(0.0, |v| *v);
+                let mut y3 = baselines
+                    .iter()
+                    .filter(|(_, a)| {
+                        date >= a.start.timestamp_millis() && date <= a.end.timestamp_millis()
+                    })
+                    .fold(prev_y3, |a, (b, c)| a + (b / (c.duration_days() as f64)));
+                if y3 >= 99.99 {
+                    y3 = 100.0
+                }
+                y.push(y3);
+            }
+
+            let data = ProjectProgressGraphResponse { x: date, y };
 
             datas.push(data);
         }
     }
 
-    HttpResponse::Ok().json(datas)
+    Ok(datas)
 }
-#[get("/projects/{project_id}/members")]
-pub async fn get_project_members(project_id: web::Path<String>) -> HttpResponse {
-    let project_id: ObjectId = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
 
-    match Project::find_users(&project_id).await {
-        Ok(Some(users)) => HttpResponse::Ok().json(users),
-        Ok(None) => HttpResponse::NotFound().body("PROJECT_USER_NOT_FOUND".to_string()),
-        Err(error) => HttpResponse::InternalServerError().body(error),
+fn render_progress_png(
+    datas: &[ProjectProgressGraphResponse],
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, String> {
+    let mut buffer = vec![255; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&WHITE).map_err(|_| "RENDER_FAILED".to_string())?;
+
+        let start = datas.first().map_or(0, |a| a.x);
+        let end = datas.last().map_or(1, |a| a.x.max(start + 1));
+        let mut chart = ChartBuilder::on(&root)
+            .margin(16)
+            .build_cartesian_2d(start..end, 0.0..100.0)
+            .map_err(|_| "RENDER_FAILED".to_string())?;
+
+        for y in [0.0, 25.0, 50.0, 75.0, 100.0] {
+            chart
+                .draw_series(LineSeries::new(
+                    vec![(start, y), (end, y)],
+                    &RGBColor(224, 224, 224),
+                ))
+                .map_err(|_| "RENDER_FAILED".to_string())?;
+        }
+
+        let colors = [
+            RGBColor(33, 150, 243),
+            RGBColor(76, 175, 80),
+            RGBColor(158, 158, 158),
+        ];
+        for (index, color) in colors.iter().enumerate() {
+            if datas.first().is_none_or(|a| a.y.len() <= index) {
+                continue;
+            }
+            chart
+                .draw_series(LineSeries::new(
+                    datas.iter().map(|a| (a.x, a.y[index])),
+                    color.stroke_width(2),
+                ))
+                .map_err(|_| "RENDER_FAILED".to_string())?;
+        }
+
+        root.present().map_err(|_| "RENDER_FAILED".to_string())?;
     }
+
+    let mut png: Vec<u8> = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&buffer))
+        .map_err(|_| "RENDER_FAILED".to_string())?;
+
+    Ok(png)
 }
-#[get("/projects/{project_id}/reports")]
-pub async fn get_project_reports(project_id: web::Path<String>) -> HttpResponse {
-    let project_id: ObjectId = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
 
-    match Project::find_reports(&project_id).await {
-        Ok(Some(reports)) => HttpResponse::Ok().json(reports),
+#[get("/projects")]
+pub async fn get_projects(query: web::Query<ProjectQueryParams>) -> HttpResponse {
+    let limit = page_limit(query.limit);
+
+    match Project::find_many(&ProjectQuery {
+        status: query.status.clone(),
+        sort: query.sort.clone(),
+        text: query.text.clone(),
+        end_before: None,
+        limit: Some(limit),
+        skip: query.skip,
+    })
+    .await
+    {
+        Ok(Some(projects)) => HttpResponse::Ok().json(PageResponse {
+            limit,
+            skip: query.skip.unwrap_or(0),
+            data: projects,
+        }),
+        Ok(None) => HttpResponse::NotFound().body("PROJECT_NOT_FOUND"),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/count")]
+pub async fn get_projects_count(query: web::Query<ProjectQueryParams>) -> HttpResponse {
+    match Project::count(&ProjectQuery {
+        status: query.status.clone(),
+        sort: None,
+        text: query.text.clone(),
+        end_before: None,
+        limit: None,
+        skip: None,
+    })
+    .await
+    {
+        Ok(count) => HttpResponse::Ok().json(CountResponse { count }),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/recent")]
+pub async fn get_projects_recent(query: web::Query<ProjectRecentQueryParams>) -> HttpResponse {
+    match Project::find_many(&ProjectQuery {
+        status: None,
+        sort: Some(ProjectQuerySortKind::Activity),
+        text: None,
+        end_before: None,
+        limit: Some(page_limit(query.limit)),
+        skip: None,
+    })
+    .await
+    {
+        Ok(Some(projects)) => HttpResponse::Ok().json(projects),
+        Ok(None) => HttpResponse::Ok().json(Vec::<ProjectMinResponse>::new()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/ending-soon")]
+pub async fn get_projects_ending_soon(query: web::Query<ProjectEndingQueryParams>) -> HttpResponse {
+    let end_before = match query
+        .days
+        .unwrap_or(7)
+        .checked_mul(86400000)
+        .and_then(|a| a.checked_add(Utc::now().timestamp_millis()))
+    {
+        Some(end_before) if query.days.unwrap_or(7) >= 0 => end_before,
+        _ => return HttpResponse::BadRequest().body("INVALID_DAYS"),
+    };
+
+    match Project::find_many(&ProjectQuery {
+        status: None,
+        sort: None,
+        text: None,
+        end_before: Some(DateTime::from_millis(end_before)),
+        limit: Some(page_limit(query.limit)),
+        skip: None,
+    })
+    .await
+    {
+        Ok(Some(projects)) => HttpResponse::Ok().json(projects),
+        Ok(None) => HttpResponse::Ok().json(Vec::<ProjectMinResponse>::new()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/summary")]
+pub async fn get_projects_summary() -> HttpResponse {
+    match Project::find_many_summary().await {
+        Ok(projects) => HttpResponse::Ok().json(projects),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}")]
+pub async fn get_project(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match Project::find_detail_by_id(&project_id).await {
+        Ok(Some(project)) => HttpResponse::Ok().json(project),
+        Ok(None) => HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/areas")]
+pub async fn get_project_areas(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTask::find_many_area(&project_id).await {
+        Ok(Some(project)) => HttpResponse::Ok().json(project),
+        Ok(None) => HttpResponse::NotFound().body("PROJECT_AREA_NOT_FOUND".to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/areas/empty")]
+pub async fn get_project_areas_empty(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+
+    match project.find_empty_areas().await {
+        Ok(areas) => HttpResponse::Ok().json(
+            areas
+                .into_iter()
+                .map(|a| ProjectTaskAreaResponse {
+                    _id: a._id.to_string(),
+                    name: a.name,
+                    description: a.description,
+                })
+                .collect::<Vec<ProjectTaskAreaResponse>>(),
+        ),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/units")]
+pub async fn get_project_units(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTask::find_many_unit(&project_id).await {
+        Ok(units) => HttpResponse::Ok().json(units),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/tasks")]
+pub async fn get_project_tasks(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectTaskQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if !query.is_valid_progress_range() {
+        return HttpResponse::BadRequest().body("INVALID_PROGRESS_RANGE".to_string());
+    }
+
+    let task_query = task_timeline_query(project_id, &query);
+
+    // Taken before querying so nothing written in between is missed on the next sync
+    let timestamp = sync_timestamp();
+
+    let mut tasks = match ProjectTask::find_many_timeline(&task_query).await {
+        Ok(tasks) => tasks.unwrap_or_default(),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+    if query.has_progress_range() {
+        tasks.retain(|a| query.in_progress_range(a.progress));
+    }
+
+    if let Some(since) = task_query.since {
+        match Tombstone::find_many(&project_id, Some(&TombstoneKind::Task), &since).await {
+            Ok(tombstones) => HttpResponse::Ok().json(ProjectTaskDeltaResponse {
+                timestamp,
+                task: tasks,
+                deleted: tombstones.iter().map(|a| a.entity_id.to_string()).collect(),
+            }),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::Ok().json(tasks)
+    }
+}
+#[get("/projects/{project_id}/tasks/count")]
+pub async fn get_project_tasks_count(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectTaskQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if !query.is_valid_progress_range() {
+        return HttpResponse::BadRequest().body("INVALID_PROGRESS_RANGE".to_string());
+    }
+
+    let task_query = task_timeline_query(project_id, &query);
+
+    // Progress only exists after the report join, so a range needs the full query
+    if query.has_progress_range() {
+        return match ProjectTask::find_many_timeline(&task_query).await {
+            Ok(tasks) => HttpResponse::Ok().json(CountResponse {
+                count: tasks
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|a| query.in_progress_range(a.progress))
+                    .count() as u64,
+            }),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        };
+    }
+
+    match ProjectTask::count_timeline(&task_query).await {
+        Ok(count) => HttpResponse::Ok().json(CountResponse { count }),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/tasks/not-started")]
+pub async fn get_project_tasks_not_started(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTask::find_many_not_started(&project_id).await {
+        Ok(tasks) => HttpResponse::Ok().json(tasks),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/variance/histogram")]
+pub async fn get_project_variance_histogram(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTask::find_variance_histogram(&project_id).await {
+        Ok(histogram) => HttpResponse::Ok().json(histogram),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/tasks/tree")]
+pub async fn get_project_tasks_tree(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTask::find_many_tree(&project_id).await {
+        Ok(tasks) => HttpResponse::Ok().json(tasks),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/support-tasks")]
+pub async fn get_project_support_tasks(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+
+    match ProjectTask::find_many_support(&project).await {
+        Ok(tasks) => HttpResponse::Ok().json(tasks),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/tasks/{task_id}")]
+pub async fn get_project_task(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTask::find_detail_by_id(&task_id).await {
+        Ok(Some(project)) => HttpResponse::Ok().json(project),
+        Ok(None) => HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/tasks/{task_id}/breadcrumb")]
+pub async fn get_project_task_breadcrumb(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let task = match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => task,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+    };
+    let project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        _ => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+    };
+    let area = match project
+        .area
+        .unwrap_or_default()
+        .into_iter()
+        .find(|a| a._id == task.area_id)
+    {
+        Some(area) => area,
+        None => return HttpResponse::NotFound().body("PROJECT_AREA_NOT_FOUND".to_string()),
+    };
+
+    match task.find_ancestors().await {
+        Ok(mut tasks) => {
+            tasks.push(task);
+            HttpResponse::Ok().json(ProjectTaskBreadcrumbResponse {
+                area: ProjectTaskAreaResponse {
+                    _id: area._id.to_string(),
+                    name: area.name,
+                    description: area.description,
+                },
+                task: tasks
+                    .into_iter()
+                    .map(|a| ProjectTaskBreadcrumbTaskResponse {
+                        _id: a._id.unwrap().to_string(),
+                        name: a.name,
+                    })
+                    .collect(),
+            })
+        }
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/tasks/{task_id}/comments")]
+pub async fn get_project_task_comments(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => (),
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+    }
+
+    match ProjectTaskComment::find_many_by_task_id(&task_id).await {
+        Ok(comments) => HttpResponse::Ok().json(comments),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/cost")]
+pub async fn get_project_cost(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(_)) = Project::find_by_id(&project_id).await {
+        match Project::calculate_cost(&project_id).await {
+            Ok(cost) => HttpResponse::Ok().json(cost),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
+#[get("/projects/{project_id}/budget")]
+pub async fn get_project_budget(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(project)) = Project::find_by_id(&project_id).await {
+        match project.calculate_budget().await {
+            Ok(budget) => HttpResponse::Ok().json(budget),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
+#[get("/projects/{project_id}/evm")]
+pub async fn get_project_evm(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectEvmQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Some(actual_cost) = query.actual_cost {
+        if !actual_cost.is_finite() || actual_cost < 0.0 {
+            return HttpResponse::BadRequest().body("INVALID_ACTUAL_COST".to_string());
+        }
+    }
+
+    if let Ok(Some(_)) = Project::find_by_id(&project_id).await {
+        match Project::calculate_evm(&project_id, query.actual_cost).await {
+            Ok(evm) => HttpResponse::Ok().json(evm),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
+#[get("/projects/{project_id}/readiness")]
+pub async fn get_project_readiness(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(project)) = Project::find_by_id(&project_id).await {
+        match project.calculate_readiness().await {
+            Ok(readiness) => HttpResponse::Ok().json(readiness),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
+#[get("/projects/{project_id}/progress/delta")]
+pub async fn get_project_progress_delta(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectProgressDeltaQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if query.from >= query.to {
+        return HttpResponse::BadRequest().body("INVALID_PERIOD".to_string());
+    }
+
+    if let Ok(Some(_)) = Project::find_by_id(&project_id).await {
+        match Project::calculate_progress_delta(&project_id, query.from, query.to).await {
+            Ok(delta) => HttpResponse::Ok().json(delta),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
+#[get("/projects/{project_id}/progress.png")]
+pub async fn get_project_progress_png(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectProgressImageQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let width = query.w.unwrap_or(800);
+    let height = query.h.unwrap_or(400);
+    if !(100..=4000).contains(&width) || !(100..=4000).contains(&height) {
+        return HttpResponse::BadRequest().body("INVALID_SIZE".to_string());
+    }
+
+    let datas =
+        match progress_graph(&project_id, query.area_id, query.baseline.unwrap_or(false)).await {
+            Ok(datas) => datas,
+            Err(response) => return *response,
+        };
+
+    match web::block(move || render_progress_png(&datas, width, height)).await {
+        Ok(Ok(png)) => HttpResponse::Ok().content_type("image/png").body(png),
+        Ok(Err(error)) => HttpResponse::InternalServerError().body(error),
+        Err(_) => HttpResponse::InternalServerError().body("RENDER_FAILED".to_string()),
+    }
+}
+#[get("/projects/{project_id}/progress")]
+pub async fn get_project_progress(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectProgressQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetProject).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match progress_graph(&project_id, query.area_id, query.baseline.unwrap_or(false)).await {
+        Ok(datas) => HttpResponse::Ok().json(datas),
+        Err(response) => *response,
+    }
+}
+#[get("/projects/{project_id}/members")]
+pub async fn get_project_members(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectMemberQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetMembers).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let member_query = ProjectMemberQuery {
+        kind: query.kind.clone(),
+        role_id: query.role_id,
+    };
+
+    match Project::find_users(&project_id, &member_query).await {
+        Ok(Some(users)) => HttpResponse::Ok().json(users),
+        Ok(None) => HttpResponse::NotFound().body("PROJECT_USER_NOT_FOUND".to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/members/{member_id}/activity")]
+pub async fn get_project_member_activity(
+    PathObjectId((project_id, member_id)): PathObjectId<(ObjectId, ObjectId)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetMembers).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+    if !project.member.iter().flatten().any(|a| a._id == member_id) {
+        return HttpResponse::NotFound().body("PROJECT_MEMBER_NOT_FOUND".to_string());
+    }
+
+    match project.calculate_member_activity(&member_id).await {
+        Ok(activity) => HttpResponse::Ok().json(activity),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/changes")]
+pub async fn get_project_changes(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectChangeQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetTasks).await
+        || !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+
+    match project
+        .find_changes(&DateTime::from_millis(query.since))
+        .await
+    {
+        Ok(changes) => HttpResponse::Ok().json(changes),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/reports")]
+pub async fn get_project_reports(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    query: web::Query<ProjectReportQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let limit = page_limit(query.limit);
+
+    match Project::find_reports(
+        &project_id,
+        &ProjectReportQuery {
+            area_id: query.area_id,
+            cursor: query.cursor.clone(),
+            limit,
+        },
+    )
+    .await
+    {
+        Ok(Some(mut reports)) => {
+            let mut next_cursor = None;
+            if reports.len() > limit {
+                reports.truncate(limit);
+                next_cursor = reports.last().map(|a| a.cursor());
+            }
+            HttpResponse::Ok().json(CursorPageResponse {
+                limit,
+                next_cursor,
+                data: reports,
+            })
+        }
+        Err(error) if error == "INVALID_CURSOR" => HttpResponse::BadRequest().body(error),
+        Ok(None) => HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/reports/count")]
+pub async fn get_project_reports_count(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match Project::count_reports(&project_id).await {
+        Ok(count) => HttpResponse::Ok().json(CountResponse { count }),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/reports/list")]
+pub async fn get_project_reports_list(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match Project::find_reports_list(&project_id).await {
+        Ok(reports) => HttpResponse::Ok().json(reports),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[get("/projects/{project_id}/reports/weekly")]
+pub async fn get_project_reports_weekly(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut bases: Vec<ProjectTask> = Vec::new();
+    let mut dependencies: Vec<ProjectTask> = Vec::new();
+    let mut progresses: Vec<ProjectProgressReport> = Vec::new();
+    let mut incidents: Vec<ProjectIncidentReport> = Vec::new();
+
+    if let Ok(Some(tasks)) = ProjectTask::find_many(&ProjectTaskQuery {
+        _id: None,
+        project_id: Some(project_id),
+        task_id: None,
+        area_id: None,
+        limit: None,
+        kind: Some(ProjectTaskQueryKind::Base),
+    })
+    .await
+    {
+        bases = tasks;
+    }
+    if let Ok(tasks) = ProjectTask::find_dependencies(&project_id).await {
+        dependencies = tasks;
+    }
+    if let Ok(Some(reports)) = ProjectProgressReport::find_many(ProjectProgressReportQuery {
+        project_id,
+        area_id: None,
+    })
+    .await
+    {
+        progresses = reports;
+    }
+    if let Ok(Some(reports)) = ProjectIncidentReport::find_many(&ProjectIncidentReportQuery {
+        project_id: Some(project_id),
+        from: None,
+        to: None,
+    })
+    .await
+    {
+        incidents = reports;
+    }
+
+    for task in bases.iter_mut() {
+        task.value = ProjectTask::effective_value(task.value, task.task_id, &dependencies);
+    }
+
+    let offset = FixedOffset::east_opt(Local::now().offset().local_minus_utc()).unwrap();
+    let mut weeks = BTreeMap::<(i32, u32), ProjectProgressWeeklyResponse>::new();
+    let get_week = |date: &DateTime| {
+        let week = offset
+            .timestamp_millis_opt(date.timestamp_millis())
+            .unwrap()
+            .iso_week();
+        (week.year(), week.week())
+    };
+    let new_week = |(year, week): (i32, u32)| {
+        let start = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+            .unwrap()
+            .and_time(NaiveTime::default());
+        ProjectProgressWeeklyResponse {
+            year,
+            week,
+            start: offset
+                .from_local_datetime(&start)
+                .unwrap()
+                .timestamp_millis(),
+            progress: 0.0,
+            manpower: 0,
+            report: 0,
+            incident: 0,
+        }
+    };
+
+    for progress in progresses.iter() {
+        let key = get_week(&progress.date);
+        let week = weeks.entry(key).or_insert_with(|| new_week(key));
+        week.report += 1;
+        if let Some(member_id) = &progress.member_id {
+            week.manpower += member_id.len();
+        }
+        if let Some(actual) = &progress.actual {
+            week.progress += actual.iter().fold(0.0, |a, b| {
+                if let Some(index) = bases.iter().position(|c| c._id.unwrap() == b.task_id) {
+                    a + b.value * bases[index].value / 100.0
+                } else {
+                    a
+                }
+            });
+        }
+    }
+    for incident in incidents.iter() {
+        let key = get_week(&incident.date);
+        weeks.entry(key).or_insert_with(|| new_week(key)).incident += 1;
+    }
+
+    HttpResponse::Ok().json(
+        weeks
+            .into_values()
+            .collect::<Vec<ProjectProgressWeeklyResponse>>(),
+    )
+}
+#[get("/projects/{project_id}/reports/plan-coverage")]
+pub async fn get_project_reports_plan_coverage(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut progresses: Vec<ProjectProgressReport> = Vec::new();
+    if let Ok(Some(reports)) = ProjectProgressReport::find_many(ProjectProgressReportQuery {
+        project_id,
+        area_id: None,
+    })
+    .await
+    {
+        progresses = reports;
+    }
+
+    // A plan entry marks a task as scheduled for the report's day; it is covered
+    // when any report of the same day carries an actual entry for it
+    let offset = FixedOffset::east_opt(Local::now().offset().local_minus_utc()).unwrap();
+    let mut days = BTreeMap::<NaiveDate, (Vec<ObjectId>, Vec<ObjectId>)>::new();
+    for progress in progresses.iter() {
+        let date = offset
+            .timestamp_millis_opt(progress.date.timestamp_millis())
+            .unwrap()
+            .date_naive();
+        let (planned, reported) = days.entry(date).or_default();
+        for plan in progress.plan.iter().flatten() {
+            if !planned.contains(&plan.task_id) {
+                planned.push(plan.task_id);
+            }
+        }
+        for actual in progress.actual.iter().flatten() {
+            if !reported.contains(&actual.task_id) {
+                reported.push(actual.task_id);
+            }
+        }
+    }
+
+    HttpResponse::Ok().json(
+        days.into_iter()
+            .map(|(date, (planned, reported))| {
+                let missed = planned
+                    .iter()
+                    .filter(|a| !reported.contains(a))
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>();
+                let covered = planned.len() - missed.len();
+                ProjectPlanCoverageResponse {
+                    date: offset
+                        .from_local_datetime(&date.and_time(NaiveTime::default()))
+                        .unwrap()
+                        .to_rfc3339_opts(SecondsFormat::Millis, false),
+                    planned: planned.len(),
+                    reported: reported.len(),
+                    covered,
+                    coverage: if planned.is_empty() {
+                        None
+                    } else {
+                        Some(covered as f64 / planned.len() as f64 * 100.0)
+                    },
+                    missed,
+                }
+            })
+            .collect::<Vec<ProjectPlanCoverageResponse>>(),
+    )
+}
+#[get("/projects/{project_id}/reports/{report_id}/documentation.zip")]
+pub async fn get_project_report_documentation_zip(
+    PathObjectId((project_id, report_id)): PathObjectId<(ObjectId, ObjectId)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let documentation = match ProjectProgressReport::find_by_id(&report_id).await {
+        Ok(Some(report)) if report.project_id == project_id => {
+            report.documentation.unwrap_or_default()
+        }
+        _ => return HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
+    };
+
+    let save_dir = FileKind::ProjectDocumentation.path(format!("{}/", report_id));
+    let files = documentation
+        .into_iter()
+        .filter_map(|a| {
+            let path = PathBuf::from(format!("{}{}.{}", save_dir, a._id, a.extension));
+            if !path.is_file() {
+                return None;
+            }
+            let name = a
+                .description
+                .map(|description| {
+                    description
+                        .chars()
+                        .map(|c| if "/\\:*?\"<>|".contains(c) { '_' } else { c })
+                        .collect::<String>()
+                })
+                .filter(|description| !description.trim().is_empty())
+                .unwrap_or_else(|| a._id.to_string());
+            Some((path, name, a.extension))
+        })
+        .collect::<Vec<(PathBuf, String, String)>>();
+
+    if files.is_empty() {
+        return HttpResponse::NotFound().body("PROJECT_REPORT_DOCUMENTATION_NOT_FOUND".to_string());
+    }
+
+    let (sender, receiver) = mpsc::channel::<Result<web::Bytes, io::Error>>(8);
+
+    actix_web::rt::task::spawn_blocking(move || {
+        let mut zip = ZipWriter::new_stream(io::BufWriter::with_capacity(
+            64 * 1024,
+            ZipChunkWriter(sender),
+        ));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        for (index, (path, name, extension)) in files.iter().enumerate() {
+            let mut file = match fs::File::open(path) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+            if zip
+                .start_file(format!("{:02}-{}.{}", index + 1, name, extension), options)
+                .is_err()
+                || io::copy(&mut file, &mut zip).is_err()
+            {
+                return;
+            }
+        }
+        let _ = zip.finish().map(|mut writer| writer.flush());
+    });
+
+    HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"report-{}.zip\"", report_id),
+        ))
+        .streaming(receiver)
+}
+#[get("/projects/{project_id}/reports/{report_id}")]
+pub async fn get_project_report(
+    PathObjectId((project_id, report_id)): PathObjectId<(ObjectId, ObjectId)>,
+    query: web::Query<ProjectReportQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::GetReport).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectProgressReport::find_detail_by_id(&report_id, query.area_id).await {
+        Ok(Some(report)) if report.project._id == project_id.to_string() => {
+            HttpResponse::Ok().json(report)
+        }
+        Ok(Some(_)) => HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
         Ok(None) => HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
-#[get("/projects/{project_id}/reports/{report_id}")]
-pub async fn get_project_report(_id: web::Path<(String, String)>) -> HttpResponse {
-    let report_id = match _id.1.parse() {
-        Ok(report_id) => report_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
+
+#[post("/projects")] // FINISHED
+pub async fn create_project(payload: web::Json<ProjectRequest>, req: HttpRequest) -> HttpResponse {
+    let issuer = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if issuer.role_id.is_empty()
+        || !Role::validate(&issuer.role_id, &RolePermission::CreateProject).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let payload: ProjectRequest = payload.into_inner();
+
+    if payload.period.start >= payload.period.end {
+        return HttpResponse::BadRequest().body("INVALID_PERIOD".to_string());
+    }
+
+    match payload.status.clone().unwrap_or(ProjectStatusKind::Pending) {
+        ProjectStatusKind::Pending => (),
+        ProjectStatusKind::Running => {
+            return HttpResponse::BadRequest().body("RUNNING_REQUIRES_FULL_CREATE".to_string())
+        }
+        _ => return HttpResponse::BadRequest().body("INVALID_STATUS".to_string()),
+    }
+    let (budget, currency) = match Project::validate_budget(payload.budget, payload.currency) {
+        Ok(budget) => budget,
+        Err(error) => return HttpResponse::BadRequest().body(error),
+    };
+
+    let mut project: Project = Project {
+        _id: None,
+        customer_id: payload.customer_id,
+        user_id: issuer._id.unwrap(),
+        name: payload.name,
+        name_normalized: None,
+        code: payload.code.unwrap_or_default(),
+        period: ProjectPeriod {
+            start: DateTime::from_millis(payload.period.start),
+            end: DateTime::from_millis(payload.period.end),
+        },
+        status: vec![ProjectStatus {
+            kind: ProjectStatusKind::Pending,
+            time: DateTime::from_millis(Utc::now().timestamp_millis()),
+            message: None,
+        }],
+        member: None,
+        area: None,
+        leave: payload.leave,
+        baseline: None,
+        import_hash: None,
+        budget,
+        currency,
+        spent: None,
+        create_date: DateTime::from_millis(Utc::now().timestamp_millis()),
+        last_activity: Some(DateTime::from_millis(Utc::now().timestamp_millis())),
+    };
+
+    if let Some(_id) = payload.user_id {
+        project.user_id = _id;
+    }
+
+    match project.save().await {
+        Ok(project_id) => {
+            let mut project_role: ProjectRole = ProjectRole {
+                _id: None,
+                name: "Owner".to_string(),
+                permission: vec![ProjectRolePermission::Owner],
+                project_id,
+            };
+
+            match project_role.save().await {
+                Ok(role_id) => {
+                    let member = ProjectMemberRequest {
+                        _id: Some(issuer._id.unwrap()),
+                        role_id: vec![role_id],
+                        kind: ProjectMemberKind::Indirect,
+                        name: None,
+                    };
+
+                    match project.add_member(&[member]).await {
+                        Ok(project_id) => created_at(format!("/projects/{}", project_id))
+                            .insert_header(("Project-Code", project.code.clone()))
+                            .json(doc! { "_id": project_id.to_string() }),
+                        Err(error) => {
+                            if let Err(error) = Project::delete_by_id(&project_id).await {
+                                return HttpResponse::InternalServerError().body(error);
+                            }
+                            if let Err(error) = ProjectRole::delete_by_id(&role_id).await {
+                                return HttpResponse::InternalServerError().body(error);
+                            }
+                            HttpResponse::InternalServerError().body(error)
+                        }
+                    }
+                }
+                Err(error) => {
+                    if let Err(error) = Project::delete_by_id(&project_id).await {
+                        return HttpResponse::InternalServerError().body(error);
+                    }
+                    HttpResponse::InternalServerError().body(error)
+                }
+            }
+            // @TODO: Add preset!
+        }
+        Err(error) if error == "PROJECT_CODE_EXISTS" => HttpResponse::Conflict().body(error),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[post("/projects/full")]
+pub async fn create_project_full(
+    payload: web::Json<ProjectFullRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if issuer.role_id.is_empty()
+        || !Role::validate(&issuer.role_id, &RolePermission::CreateProject).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let payload: ProjectFullRequest = payload.into_inner();
+
+    if payload.project.period.start >= payload.project.period.end {
+        return HttpResponse::BadRequest().body("INVALID_PERIOD".to_string());
+    }
+
+    let status = payload
+        .project
+        .status
+        .clone()
+        .unwrap_or(ProjectStatusKind::Pending);
+    if status != ProjectStatusKind::Pending && status != ProjectStatusKind::Running {
+        return HttpResponse::BadRequest().body("INVALID_STATUS".to_string());
+    }
+
+    let mut area_id = BTreeMap::<String, ObjectId>::new();
+    let mut areas = Vec::<ProjectArea>::new();
+    for i in payload.area {
+        if area_id.contains_key(&i.key) {
+            return HttpResponse::BadRequest().body("INVALID_AREA_KEY".to_string());
+        }
+        let _id = ObjectId::new();
+        area_id.insert(i.key, _id);
+        areas.push(ProjectArea {
+            _id,
+            name: i.name,
+            description: i.description,
+            update_date: None,
+        });
+    }
+
+    let mut task_id = BTreeMap::<String, (ObjectId, ObjectId)>::new();
+    let mut task_value = BTreeMap::<Option<String>, f64>::new();
+    let mut tasks = Vec::<ProjectTask>::new();
+    for i in payload.task {
+        if task_id.contains_key(&i.key) {
+            return HttpResponse::BadRequest().body("INVALID_TASK_KEY".to_string());
+        }
+        let (parent_id, area) = match &i.parent_key {
+            Some(key) => match task_id.get(key) {
+                Some((_id, area_id)) => (Some(*_id), *area_id),
+                None => return HttpResponse::BadRequest().body("INVALID_PARENT_KEY".to_string()),
+            },
+            None => match i.area_key.as_ref().and_then(|key| area_id.get(key)) {
+                Some(_id) => (None, *_id),
+                None => return HttpResponse::BadRequest().body("INVALID_AREA_KEY".to_string()),
+            },
+        };
+        let cost = match i.cost.map(ProjectTaskCost::from_request).transpose() {
+            Ok(cost) => cost,
+            Err(error) => return HttpResponse::BadRequest().body(error),
+        };
+        let period = match i.period {
+            Some(period) if period.start > period.end => {
+                return HttpResponse::BadRequest().body("INVALID_PERIOD".to_string())
+            }
+            Some(period) => Some(ProjectTaskPeriod {
+                start: DateTime::from_millis(period.start),
+                end: DateTime::from_millis(period.end),
+            }),
+            None => None,
+        };
+
+        *task_value.entry(i.parent_key).or_insert(0.0) += i.value;
+
+        let _id = ObjectId::new();
+        task_id.insert(i.key, (_id, area));
+        tasks.push(ProjectTask {
+            _id: Some(_id),
+            project_id: ObjectId::new(),
+            area_id: area,
+            task_id: parent_id,
+            user_id: i.user_id,
+            name: i.name,
+            description: i.description,
+            period,
+            status: vec![ProjectTaskStatus {
+                kind: ProjectTaskStatusKind::Pending,
+                time: DateTime::from_millis(Utc::now().timestamp_millis()),
+                message: None,
+            }],
+            volume: i.volume,
+            value: i.value,
+            cost,
+            attachment: None,
+            checklist: None,
+            update_date: None,
+        });
+    }
+
+    if task_value
+        .values()
+        .any(|value| !ProjectTask::is_value_complete(*value))
+        || (status == ProjectStatusKind::Running && tasks.is_empty())
+    {
+        return HttpResponse::BadRequest().body("PROJECT_TASK_VALUE_SUM_MUST_BE_100".to_string());
+    }
+    if tasks
+        .iter()
+        .any(|a| a.period.is_some() && tasks.iter().any(|b| b.task_id == a._id))
+    {
+        return HttpResponse::BadRequest().body("PROJECT_TASK_DEPENDENCY".to_string());
+    }
+    let (budget, currency) =
+        match Project::validate_budget(payload.project.budget, payload.project.currency) {
+            Ok(budget) => budget,
+            Err(error) => return HttpResponse::BadRequest().body(error),
+        };
+
+    let mut project: Project = Project {
+        _id: None,
+        customer_id: payload.project.customer_id,
+        user_id: payload.project.user_id.unwrap_or(issuer._id.unwrap()),
+        name: payload.project.name,
+        name_normalized: None,
+        code: payload.project.code.unwrap_or_default(),
+        period: ProjectPeriod {
+            start: DateTime::from_millis(payload.project.period.start),
+            end: DateTime::from_millis(payload.project.period.end),
+        },
+        status: vec![ProjectStatus {
+            kind: ProjectStatusKind::Pending,
+            time: DateTime::from_millis(Utc::now().timestamp_millis()),
+            message: None,
+        }],
+        member: None,
+        area: None,
+        leave: payload.project.leave,
+        baseline: None,
+        import_hash: None,
+        budget,
+        currency,
+        spent: None,
+        create_date: DateTime::from_millis(Utc::now().timestamp_millis()),
+        last_activity: Some(DateTime::from_millis(Utc::now().timestamp_millis())),
+    };
+
+    let project_id = match project
+        .save_full(
+            &issuer._id.unwrap(),
+            areas,
+            tasks,
+            status == ProjectStatusKind::Running,
+        )
+        .await
+    {
+        Ok(project_id) => project_id,
+        Err(error) if error == "PROJECT_CODE_EXISTS" => {
+            return HttpResponse::Conflict().body(error)
+        }
+        Err(error) if error == "CUSTOMER_NOT_FOUND" => return HttpResponse::NotFound().body(error),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+
+    created_at(format!("/projects/{}", project_id)).json(doc! {
+        "_id": project_id.to_string(),
+        "code": &project.code,
+        "area": to_bson::<BTreeMap<String, String>>(
+            &area_id.into_iter().map(|(key, _id)| (key, _id.to_string())).collect()
+        )
+        .unwrap(),
+        "task": to_bson::<BTreeMap<String, String>>(
+            &task_id.into_iter().map(|(key, (_id, _))| (key, _id.to_string())).collect()
+        )
+        .unwrap(),
+    })
+}
+#[post("/projects/{project_id}/roles")] // FINISHED
+pub async fn create_project_role(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    payload: web::Json<ProjectRoleRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateRole).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let payload: ProjectRoleRequest = payload.into_inner();
+
+    let mut project_role: ProjectRole = ProjectRole {
+        _id: None,
+        project_id,
+        name: payload.name,
+        permission: payload.permission,
+    };
+
+    match project_role.save().await {
+        Ok(role_id) => created_at(format!("/projects/{}", project_id))
+            .json(doc! { "_id": role_id.to_string() }),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+
+#[post("/projects/{project_id}/members/{member_id}/reassign")]
+pub async fn create_project_member_reassign(
+    PathObjectId((project_id, member_id)): PathObjectId<(ObjectId, ObjectId)>,
+    payload: web::Json<ProjectMemberReassignRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
     };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
 
-    match ProjectProgressReport::find_detail_by_id(&report_id).await {
-        Ok(Some(report)) => HttpResponse::Ok().json(report),
-        Ok(None) => HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
+    let payload = payload.into_inner();
+    if payload.to_member_id == member_id {
+        return HttpResponse::BadRequest().body("INVALID_MEMBER".to_string());
+    }
+
+    let mut project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        _ => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+    };
+
+    match project
+        .reassign_member(&member_id, &payload.to_member_id)
+        .await
+    {
+        Ok(count) => HttpResponse::Ok().json(doc! {
+            "count": to_bson::<u64>(&count).unwrap()
+        }),
+        Err(error) if error == "PROJECT_MEMBER_NOT_FOUND" || error == "USER_NOT_FOUND" => {
+            HttpResponse::NotFound().body(error)
+        }
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[post("/projects/{project_id}/areas/merge")]
+pub async fn create_project_area_merge(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    payload: web::Json<ProjectAreaMergeRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::DeleteTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        _ => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+    };
+
+    match project
+        .merge_area(&payload.source_area_id, &payload.target_area_id)
+        .await
+    {
+        Ok(count) => HttpResponse::Ok().json(doc! {
+            "count": to_bson::<u64>(&count).unwrap()
+        }),
+        Err(error) if error == "INVALID_TARGET_AREA" => HttpResponse::BadRequest().body(error),
+        Err(error) if error == "PROJECT_AREA_NOT_FOUND" => HttpResponse::NotFound().body(error),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[post("/projects/{project_id}/baseline")]
+pub async fn create_project_baseline(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        _ => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+    };
+
+    let baseline: Vec<ProjectBaseline> = match ProjectTask::find_many(&ProjectTaskQuery {
+        _id: None,
+        project_id: Some(project_id),
+        task_id: None,
+        area_id: None,
+        limit: None,
+        kind: Some(ProjectTaskQueryKind::Base),
+    })
+    .await
+    {
+        Ok(Some(tasks)) => tasks
+            .into_iter()
+            .filter_map(|a| {
+                a.period.map(|period| ProjectBaseline {
+                    task_id: a._id.unwrap(),
+                    period,
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    if baseline.is_empty() {
+        return HttpResponse::BadRequest().body("PROJECT_TASK_PERIOD_NOT_FOUND".to_string());
+    }
+
+    match project.update_baseline(baseline).await {
+        Ok(_id) => HttpResponse::Created().body(_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[post("/projects/{project_id}/tasks/bulk")] // FINISHED
+pub async fn create_project_task_bulk(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    form: MultipartForm<ProjectTaskMultipartRequest>,
+    query: web::Query<ProjectTaskBulkQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(mut project)) = Project::find_by_id(&project_id).await {
+        match project.current_status() {
+            Ok(status) if status.kind != ProjectStatusKind::Pending => {
+                return HttpResponse::BadRequest().body("PROJECT_STATUS_NOT_PENDING".to_string());
+            }
+            Err(error) => return HttpResponse::InternalServerError().body(error),
+            _ => (),
+        }
+
+        let bytes = match read_bulk_file(&form.file) {
+            Ok(bytes) => bytes,
+            Err(response) => return *response,
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        if let Some(ProjectTaskBulkDateFormat::YearMonthDay) = query.date_format {
+            hasher.update(b"yyyy-mm-dd");
+        }
+        let hash = format!("{:x}", hasher.finalize());
+        if project.import_hash.as_deref() == Some(hash.as_str()) {
+            return HttpResponse::Ok().json(doc! { "unchanged": true });
+        }
+
+        let (areas, tasks) = match parse_bulk_tasks(&bytes, project_id, &query) {
+            Ok(result) => result,
+            Err(response) => return *response,
+        };
+
+        if ProjectTask::delete_many_by_project_id(&project_id)
+            .await
+            .is_err()
+        {
+            return HttpResponse::InternalServerError().body("PROJECT_TASK_DELETE_FAILED");
+        }
+        if project.replace_areas(areas).await.is_err() {
+            return HttpResponse::InternalServerError().body("PROJECT_AREA_CREATION_FAILED");
+        }
+        match ProjectTask::save_bulk(tasks).await {
+            Ok(task_id) => {
+                if let Err(error) = project.update_import_hash(hash).await {
+                    return HttpResponse::InternalServerError().body(error);
+                }
+                HttpResponse::Created().json(doc! {
+                    "_id": to_bson::<Vec<ObjectId>>(&task_id).unwrap()
+                })
+            }
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::BadRequest().body("PROJECT_TASK_CSV_UPLOAD_FAILED")
+    }
+}
+#[post("/projects/{project_id}/tasks/bulk/preview")]
+pub async fn create_project_task_bulk_preview(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    form: MultipartForm<ProjectTaskMultipartRequest>,
+    query: web::Query<ProjectTaskBulkQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(_)) = Project::find_by_id(&project_id).await {
+        let (areas, tasks) = match read_bulk_file(&form.file)
+            .and_then(|bytes| parse_bulk_tasks(&bytes, project_id, &query))
+        {
+            Ok(result) => result,
+            Err(response) => return *response,
+        };
+
+        HttpResponse::Ok().json(
+            areas
+                .into_iter()
+                .map(|area| ProjectTaskBulkPreviewAreaResponse {
+                    _id: area._id.to_string(),
+                    name: area.name,
+                    task: tasks
+                        .iter()
+                        .filter(|a| a.area_id == area._id)
+                        .map(|a| ProjectTaskBulkPreviewTaskResponse {
+                            _id: a._id.unwrap().to_string(),
+                            task_id: a.task_id.map(|a| a.to_string()),
+                            depth: a.find_depth(&tasks),
+                            name: a.name.clone(),
+                            value: a.value,
+                            volume: a.volume.clone(),
+                            period: a.period.as_ref().map(|a| ProjectTaskPeriodResponse {
+                                start: a.start.try_to_rfc3339_string().unwrap_or_default(),
+                                end: a.end.try_to_rfc3339_string().unwrap_or_default(),
+                            }),
+                            cost: a.cost.clone(),
+                        })
+                        .collect(),
+                })
+                .collect::<Vec<ProjectTaskBulkPreviewAreaResponse>>(),
+        )
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
+#[post("/projects/{project_id}/tasks")] // FINISHED
+pub async fn create_project_task(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    payload: web::Json<ProjectTaskRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(project)) = Project::find_by_id(&project_id).await {
+        match project.current_status() {
+            Ok(status) if status.kind != ProjectStatusKind::Pending => {
+                return HttpResponse::BadRequest()
+                    .body("PROJECT_STATUS_MUST_BE_PENDING".to_string());
+            }
+            Err(error) => return HttpResponse::InternalServerError().body(error),
+            _ => (),
+        }
+    } else {
+        return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string());
+    }
+
+    let payload: ProjectTaskRequest = payload.into_inner();
+
+    let cost = match payload.cost.map(ProjectTaskCost::from_request).transpose() {
+        Ok(cost) => cost,
+        Err(error) => return HttpResponse::BadRequest().body(error),
+    };
+
+    let mut project_task: ProjectTask = ProjectTask {
+        _id: None,
+        project_id,
+        area_id: ObjectId::new(),
+        task_id: None,
+        user_id: payload.user_id,
+        name: payload.name,
+        volume: payload.volume,
+        value: payload.value,
+        cost,
+        description: payload.description,
+        period: None,
+        status: vec![ProjectTaskStatus {
+            kind: ProjectTaskStatusKind::Pending,
+            time: DateTime::from_millis(Utc::now().timestamp_millis()),
+            message: None,
+        }],
+        attachment: None,
+        checklist: None,
+        update_date: None,
+    };
+
+    if let Some(area_id) = payload.area_id {
+        project_task.area_id = area_id
+    } else {
+        return HttpResponse::BadRequest().body("PROJECT_TASK_MUST_HAVE_AREA_ID".to_string());
+    }
+
+    match project_task.save().await {
+        Ok(task_id) => created_at(format!("/projects/{}/tasks/{}", project_id, task_id))
+            .json(doc! { "_id": task_id.to_string() }),
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
-
-#[post("/projects")] // FINISHED
-pub async fn create_project(payload: web::Json<ProjectRequest>, req: HttpRequest) -> HttpResponse {
-    let issuer = match req.extensions().get::<UserAuthentication>() {
-        Some(issuer) => issuer.clone(),
+#[post("/projects/{project_id}/tasks/shift")]
+pub async fn create_project_task_shift(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    payload: web::Json<ProjectTaskShiftRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let payload: ProjectTaskShiftRequest = payload.into_inner();
+
+    if payload.days == 0 {
+        return HttpResponse::BadRequest().body("INVALID_DAYS".to_string());
+    }
+
+    let tasks = match ProjectTask::find_many(&ProjectTaskQuery {
+        _id: None,
+        project_id: Some(project_id),
+        task_id: None,
+        area_id: payload.area_id,
+        limit: None,
+        kind: Some(ProjectTaskQueryKind::Base),
+    })
+    .await
+    {
+        Ok(Some(tasks)) => tasks,
+        Ok(None) => Vec::<ProjectTask>::new(),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+
+    let offset = payload.days * 86400000;
+    let mut shifted = 0;
+    let mut failed = Vec::<ObjectId>::new();
+
+    for mut task in tasks {
+        let period = match &task.period {
+            Some(period) => period.clone(),
+            None => continue,
+        };
+        if let Some(from_date) = payload.from_date {
+            if period.start.timestamp_millis() < from_date {
+                continue;
+            }
+        }
+
+        let period = ProjectTaskPeriod {
+            start: DateTime::from_millis(period.start.timestamp_millis() + offset),
+            end: DateTime::from_millis(period.end.timestamp_millis() + offset),
+        };
+
+        match task.update_period(period).await {
+            Ok(_) => shifted += 1,
+            Err(_) => failed.push(task._id.unwrap()),
+        }
+    }
+
+    HttpResponse::Ok().json(doc! {
+        "shifted": shifted,
+        "failed": to_bson::<Vec<ObjectId>>(&failed).unwrap()
+    })
+}
+#[post("/projects/{project_id}/tasks/{task_id}")] // FINISHED
+pub async fn create_project_task_sub(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    payload: web::Json<Vec<ProjectTaskRequest>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
     };
-    if issuer.role_id.is_empty()
-        || !Role::validate(&issuer.role_id, &RolePermission::CreateProject).await
-    {
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
         return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
     }
 
-    let payload: ProjectRequest = payload.into_inner();
-
-    if payload.period.start >= payload.period.end {
-        return HttpResponse::BadRequest().body("INVALID_PERIOD".to_string());
+    if let Ok(Some(_)) = ProjectTask::find_many(&ProjectTaskQuery {
+        _id: None,
+        project_id: None,
+        task_id: Some(task_id),
+        area_id: None,
+        limit: None,
+        kind: None,
+    })
+    .await
+    {
+        if ProjectTask::delete_many_by_task_id(&task_id).await.is_err() {
+            return HttpResponse::InternalServerError()
+                .body("PROJECT_TASK_DELETION_FAILED".to_string());
+        }
     }
 
-    let mut project: Project = Project {
-        _id: None,
-        customer_id: payload.customer_id,
-        user_id: issuer._id.unwrap(),
-        name: payload.name,
-        code: payload.code,
-        period: ProjectPeriod {
-            start: DateTime::from_millis(payload.period.start),
-            end: DateTime::from_millis(payload.period.end),
-        },
-        status: vec![ProjectStatus {
-            kind: ProjectStatusKind::Pending,
-            time: DateTime::from_millis(Utc::now().timestamp_millis()),
-            message: None,
-        }],
-        member: None,
-        area: None,
-        leave: payload.leave,
-        create_date: DateTime::from_millis(Utc::now().timestamp_millis()),
-    };
+    if let Ok(Some(task)) = ProjectTask::find_by_id(&task_id).await {
+        if let Ok(Some(project)) = Project::find_by_id(&task.project_id).await {
+            match project.current_status() {
+                Ok(status) if status.kind != ProjectStatusKind::Pending => {
+                    return HttpResponse::BadRequest()
+                        .body("PROJECT_STATUS_MUST_BE_PENDING".to_string());
+                }
+                Err(error) => return HttpResponse::InternalServerError().body(error),
+                _ => (),
+            }
+            let mut payload = payload.into_inner();
+            let mut new_task_id = Vec::<ObjectId>::new();
+            let mut total = 0.0;
 
-    if let Some(_id) = payload.user_id {
-        project.user_id = _id;
-    }
+            for i in &payload {
+                total += i.value;
+            }
 
-    match project.save().await {
-        Ok(project_id) => {
-            let mut project_role: ProjectRole = ProjectRole {
-                _id: None,
-                name: "Owner".to_string(),
-                permission: vec![ProjectRolePermission::Owner],
-                project_id,
-            };
+            if !ProjectTask::is_value_complete(total) {
+                return HttpResponse::BadRequest().body("PROJECT_TASK_VALUE_SUM_MUST_BE_100");
+            }
 
-            match project_role.save().await {
-                Ok(role_id) => {
-                    let member = ProjectMemberRequest {
-                        _id: Some(issuer._id.unwrap()),
-                        role_id: vec![role_id],
-                        kind: ProjectMemberKind::Indirect,
-                        name: None,
-                    };
+            let mut costs = Vec::<Option<ProjectTaskCost>>::new();
+            for i in payload.iter_mut() {
+                match i.cost.take().map(ProjectTaskCost::from_request).transpose() {
+                    Ok(cost) => costs.push(cost),
+                    Err(error) => return HttpResponse::BadRequest().body(error),
+                }
+            }
 
-                    match project.add_member(&[member]).await {
-                        Ok(project_id) => HttpResponse::Ok().body(project_id.to_string()),
-                        Err(error) => {
-                            Project::delete_by_id(&project_id)
-                                .await
-                                .expect("PROJECT_DELETION_FAILED");
-                            ProjectRole::delete_by_id(&role_id)
+            for (i, cost) in payload.into_iter().zip(costs) {
+                let mut project_task: ProjectTask = ProjectTask {
+                    _id: None,
+                    project_id,
+                    area_id: task.area_id,
+                    task_id: Some(task_id),
+                    user_id: i.user_id,
+                    name: i.name,
+                    volume: i.volume,
+                    value: i.value,
+                    cost,
+                    description: i.description,
+                    period: None,
+                    status: vec![ProjectTaskStatus {
+                        kind: ProjectTaskStatusKind::Pending,
+                        time: DateTime::from_millis(Utc::now().timestamp_millis()),
+                        message: None,
+                    }],
+                    attachment: None,
+                    checklist: None,
+                    update_date: None,
+                };
+                match project_task.save().await {
+                    Ok(task_id) => new_task_id.push(task_id),
+                    Err(error) => {
+                        for i in new_task_id {
+                            ProjectTask::delete_by_id(&i)
                                 .await
-                                .expect("PROJECT_ROLE_DELETION_FAILED");
-                            HttpResponse::InternalServerError().body(error)
+                                .expect("PROJECT_TASK_DELETION_FAILED");
                         }
+                        return HttpResponse::InternalServerError().body(error);
                     }
                 }
-                Err(error) => {
-                    Project::delete_by_id(&project_id)
-                        .await
-                        .expect("PROJECT_DELETION_FAILED");
-                    HttpResponse::InternalServerError().body(error)
-                }
             }
-            // @TODO: Add preset!
+
+            HttpResponse::Created().json(doc! {
+                "_id": to_bson::<Vec<ObjectId>>(&new_task_id).unwrap()
+            })
+        } else {
+            HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
         }
-        Err(error) => HttpResponse::InternalServerError().body(error),
+    } else {
+        HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string())
     }
 }
-#[post("/projects/{project_id}/roles")] // FINISHED
-pub async fn create_project_role(
-    project_id: web::Path<String>,
-    payload: web::Json<ProjectRoleRequest>,
+
+#[post("/projects/{project_id}/tasks/{task_id}/checklist")]
+pub async fn create_project_task_checklist(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    payload: web::Json<ProjectTaskChecklistRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
     };
-    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateRole).await {
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::UpdateTask).await {
         return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
     }
 
-    let payload: ProjectRoleRequest = payload.into_inner();
-
-    let mut project_role: ProjectRole = ProjectRole {
-        _id: None,
-        project_id,
-        name: payload.name,
-        permission: payload.permission,
+    let mut task = match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => task,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
     };
 
-    match project_role.save().await {
-        Ok(role_id) => HttpResponse::Ok().body(role_id.to_string()),
+    let payload = payload.into_inner();
+    if payload.label.trim().is_empty() {
+        return HttpResponse::BadRequest().body("INVALID_LABEL".to_string());
+    }
+
+    let _id = ObjectId::new();
+    let mut checklist = task.checklist.take().unwrap_or_default();
+    checklist.push(ProjectTaskChecklist {
+        _id,
+        label: payload.label.trim().to_string(),
+        done: false,
+    });
+    task.checklist = Some(checklist);
+
+    match task.update().await {
+        Ok(_) => created_at(format!("/projects/{}/tasks/{}", project_id, task_id))
+            .json(doc! { "_id": _id.to_string() }),
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
-
-#[post("/projects/{project_id}/tasks/bulk")] // FINISHED
-pub async fn create_project_task_bulk(
-    project_id: web::Path<String>,
-    form: MultipartForm<ProjectTaskMultipartRequest>,
+#[post("/projects/{project_id}/tasks/{task_id}/attachments")]
+pub async fn create_project_task_attachment(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    form: MultipartForm<ProjectTaskAttachmentMultipartRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
     };
-    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::UpdateTask).await {
         return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
     }
 
-    if let Ok(Some(mut project)) = Project::find_by_id(&project_id).await {
-        if project.status.first().unwrap().kind != ProjectStatusKind::Pending {
-            return HttpResponse::BadRequest().body("PROJECT_STATUS_NOT_PENDING".to_string());
-        }
-
-        let path = form.file.file.path();
-
-        if let Ok(bytes) = fs::read(path) {
-            if fs::remove_file(path).is_err() {
-                return HttpResponse::InternalServerError()
-                    .body("PROJECT_TASK_CSV_DELETE_FAILED".to_string());
-            }
+    let mut task = match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => task,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+    };
 
-            let mut row_index = -1;
-            let mut data_index = 0;
-            let mut data = String::new();
-            let mut area_index = 0;
-            let mut areas = Vec::<ProjectArea>::new();
-            let mut task_level = 0;
-            let mut task_value = Vec::<(usize, f64)>::new();
-            let mut tasks = Vec::<ProjectTask>::new();
-            let mut task: Option<ProjectTask> = None;
-            let mut total = 0.0;
-            for index in 1..=bytes.len() {
-                let string = if index == bytes.len() {
-                    String::from_utf8_lossy(&bytes[(index - 1)..])
-                } else {
-                    String::from_utf8_lossy(&bytes[(index - 1)..index])
-                };
+    if form.files.is_empty() {
+        return HttpResponse::BadRequest().body("PROJECT_TASK_ATTACHMENT_NOT_FOUND".to_string());
+    }
 
-                if string == "\n" {
-                    if let Some(mut task) = task {
-                        if let Some(period) = task.period.as_mut() {
-                            if let Ok(date) = NaiveDate::parse_from_str(&data, "%d-%m-%Y") {
-                                period.end = DateTime::from_millis(
-                                    NaiveDateTime::new(
-                                        date,
-                                        NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap(),
-                                    )
-                                    .timestamp_millis(),
-                                )
-                            }
-                        }
-                        tasks.push(task);
-                    }
-                    task = None;
-                    row_index += 1;
-                    data_index = 0;
-                    data.clear();
-                } else if string == "," || string == ";" {
-                    if row_index >= 0 {
-                        if data_index == 0 && !data.is_empty() {
-                            if !areas.is_empty() {
-                                area_index += 1;
-                            }
-                            areas.push(ProjectArea {
-                                _id: ObjectId::new(),
-                                name: data.clone(),
-                            });
-                        } else if data_index == 1 && !data.is_empty() {
-                            let mut task_id: Option<ObjectId> = None;
-                            let mut level = 0;
-                            let name = data
-                                .clone()
-                                .chars()
-                                .filter(|c| {
-                                    if *c == '_' {
-                                        level += 1;
-                                        false
-                                    } else {
-                                        true
-                                    }
-                                })
-                                .collect();
-
-                            match level.cmp(&task_level) {
-                                cmp::Ordering::Greater => task_level += 1,
-                                cmp::Ordering::Less => {
-                                    let mut total = 0.0;
-                                    let task_complete = &task_value[level..];
-                                    for (index, value) in task_complete.iter().rev() {
-                                        let task_id = tasks.get(*index).unwrap()._id;
-                                        for (index, task) in tasks[*index..].iter_mut().enumerate()
-                                        {
-                                            if index == 0 {
-                                                total += *value;
-                                                task.value = total;
-                                            } else if task.task_id == task_id && task.value != 0.0 {
-                                                task.value = task.value / total * 100.0;
-                                            }
-                                        }
-                                    }
-                                    task_value.truncate(level);
-                                    task_level = level;
-                                }
-                                _ => (),
-                            };
+    let save_dir = FileKind::TaskAttachment.path(format!("{}/", task_id));
 
-                            if let Some((index, _)) = task_value.last() {
-                                task_id = tasks.get(*index).unwrap()._id;
-                            }
+    if create_dir_all(&save_dir).is_err() {
+        return HttpResponse::InternalServerError().body("DIRECTORY_CREATION_FAILED".to_string());
+    }
 
-                            task = Some(ProjectTask {
-                                _id: Some(ObjectId::new()),
-                                project_id,
-                                area_id: areas.get(area_index).unwrap()._id,
-                                task_id,
-                                user_id: None,
-                                name,
-                                description: None,
-                                period: None,
-                                status: vec![ProjectTaskStatus {
-                                    kind: ProjectTaskStatusKind::Pending,
-                                    time: DateTime::from_millis(Utc::now().timestamp_millis()),
-                                    message: None,
-                                }],
-                                volume: None,
-                                value: 0.0,
-                            });
-                        } else if data_index == 2 && !data.is_empty() {
-                            if let Some(task) = task.as_mut() {
-                                if let Ok(value) = data.parse::<usize>() {
-                                    task.volume = Some(ProjectTaskVolume {
-                                        value,
-                                        unit: "pcs".to_owned(),
-                                    });
-                                }
-                            }
-                        } else if data_index == 3 && !data.is_empty() {
-                            if let Some(task) = task.as_mut() {
-                                if let Some(volume) = task.volume.as_mut() {
-                                    volume.unit = data.clone();
-                                }
-                            }
-                        } else if data_index == 4 {
-                            if let Some(task) = task.as_mut() {
-                                if let Ok(value) = data.parse::<f64>() {
-                                    if let Some(parent_value) = task_value.last_mut() {
-                                        parent_value.1 =
-                                            ((parent_value.1 + value) * 1e6_f64).round() / 1e6_f64;
-                                    }
-                                    total = ((total + value) * 1e6_f64).round() / 1e6_f64;
-                                    task.value = value;
-                                } else {
-                                    task_value.push((tasks.len(), 0.0));
-                                    task.value = 0.0;
-                                }
-                            }
-                        } else if data_index == 5 && !data.is_empty() {
-                            if let Some(task) = task.as_mut() {
-                                if let Ok(date) = NaiveDate::parse_from_str(&data, "%d-%m-%Y") {
-                                    task.period = Some(ProjectTaskPeriod {
-                                        start: DateTime::from_millis(
-                                            NaiveDateTime::new(
-                                                date,
-                                                NaiveTime::from_hms_milli_opt(0, 0, 0, 0).unwrap(),
-                                            )
-                                            .timestamp_millis(),
-                                        ),
-                                        end: DateTime::from_millis(
-                                            NaiveDateTime::new(
-                                                date,
-                                                NaiveTime::from_hms_milli_opt(23, 59, 59, 999)
-                                                    .unwrap(),
-                                            )
-                                            .timestamp_millis(),
-                                        ),
-                                    });
-                                }
-                            }
-                        }
-                    }
+    let mut attachment = task.attachment.clone().unwrap_or_default();
+    let mut file_paths = Vec::<PathBuf>::new();
+    let mut attachment_id = Vec::<ObjectId>::new();
 
-                    data_index += 1;
-                    data.clear();
-                } else {
-                    data.push_str(&string);
+    for file in form.files.iter() {
+        let ext = match file
+            .file_name
+            .as_ref()
+            .and_then(|file_name| Path::new(file_name).extension().and_then(OsStr::to_str))
+        {
+            Some(ext) => ext.to_string(),
+            None => {
+                for file_path in file_paths {
+                    let _ = fs::remove_file(file_path);
                 }
+                return HttpResponse::BadRequest()
+                    .body("PROJECT_TASK_ATTACHMENT_MALFORMED".to_string());
             }
-
-            if (total - 100.0).abs() > 0.001 {
-                return HttpResponse::BadRequest().body("PROJECT_TASK_INVALID_VALUE");
+        };
+        let _id = ObjectId::new();
+        let file_path = PathBuf::from(save_dir.to_owned() + &_id.to_string() + "." + &ext);
+        if rename(file.file.path(), &file_path).is_err() {
+            for file_path in file_paths {
+                let _ = fs::remove_file(file_path);
             }
+            return HttpResponse::InternalServerError()
+                .body("PROJECT_TASK_ATTACHMENT_RENAME_FAILED".to_string());
+        }
 
-            if let Some(mut task) = task {
-                if let Some(period) = task.period.as_mut() {
-                    if let Ok(date) = NaiveDate::parse_from_str(&data, "%d-%m-%Y") {
-                        period.end = DateTime::from_millis(
-                            NaiveDateTime::new(
-                                date,
-                                NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap(),
-                            )
-                            .timestamp_millis(),
-                        )
-                    }
-                }
-                tasks.push(task);
-            }
+        attachment.push(ProjectTaskAttachment {
+            _id,
+            name: file.file_name.clone(),
+            extension: ext,
+        });
+        file_paths.push(file_path);
+        attachment_id.push(_id);
+    }
 
-            let mut total = 0.0;
-            for (index, value) in task_value.iter().rev() {
-                let task_id = tasks.get(*index).unwrap()._id;
-                for (index, task) in tasks[*index..].iter_mut().enumerate() {
-                    if index == 0 {
-                        total += *value;
-                        task.value = total;
-                    } else if task.task_id == task_id && task.value != 0.0 {
-                        task.value = task.value / total * 100.0;
-                    }
-                }
-            }
+    task.attachment = Some(attachment);
 
-            if ProjectTask::delete_many_by_project_id(&project_id)
-                .await
-                .is_err()
-            {
-                return HttpResponse::InternalServerError().body("PROJECT_TASK_DELETE_FAILED");
-            }
-            if project.replace_areas(areas).await.is_err() {
-                return HttpResponse::InternalServerError().body("PROJECT_AREA_CREATION_FAILED");
-            }
-            match ProjectTask::save_bulk(tasks).await {
-                Ok(task_id) => HttpResponse::Created().json(doc! {
-                    "_id": to_bson::<Vec<ObjectId>>(&task_id).unwrap()
-                }),
-                Err(error) => HttpResponse::InternalServerError().body(error),
+    match task.update().await {
+        Ok(_) => HttpResponse::Created().json(doc! {
+            "_id": to_bson::<Vec<ObjectId>>(&attachment_id).unwrap()
+        }),
+        Err(error) => {
+            for file_path in file_paths {
+                let _ = fs::remove_file(file_path);
             }
-        } else {
-            HttpResponse::BadRequest().body("PROJECT_TASK_CSV_UPLOAD_FAILED")
+            HttpResponse::InternalServerError().body(error)
         }
-    } else {
-        HttpResponse::BadRequest().body("PROJECT_TASK_CSV_UPLOAD_FAILED")
     }
 }
-#[post("/projects/{project_id}/tasks")] // FINISHED
-pub async fn create_project_task(
-    project_id: web::Path<String>,
-    payload: web::Json<ProjectTaskRequest>,
+
+#[post("/projects/{project_id}/tasks/{task_id}/duplicate")]
+pub async fn create_project_task_duplicate(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    payload: web::Json<ProjectTaskDuplicateRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -773,143 +2891,132 @@ pub async fn create_project_task(
     if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
         return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
     }
-    let payload: ProjectTaskRequest = payload.into_inner();
 
-    let mut project_task: ProjectTask = ProjectTask {
-        _id: None,
-        project_id,
-        area_id: ObjectId::new(),
-        task_id: None,
-        user_id: payload.user_id,
-        name: payload.name,
-        volume: payload.volume,
-        value: payload.value,
-        description: payload.description,
-        period: None,
-        status: vec![ProjectTaskStatus {
-            kind: ProjectTaskStatusKind::Pending,
-            time: DateTime::from_millis(Utc::now().timestamp_millis()),
-            message: None,
-        }],
+    let task = match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => task,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
     };
 
-    if let Some(area_id) = payload.area_id {
-        project_task.area_id = area_id
-    } else {
-        return HttpResponse::BadRequest().body("PROJECT_TASK_MUST_HAVE_AREA_ID".to_string());
-    }
+    if let Ok(Some(project)) = Project::find_by_id(&project_id).await {
+        match project.current_status() {
+            Ok(status) if status.kind != ProjectStatusKind::Pending => {
+                return HttpResponse::BadRequest()
+                    .body("PROJECT_STATUS_MUST_BE_PENDING".to_string());
+            }
+            Err(error) => return HttpResponse::InternalServerError().body(error),
+            _ => (),
+        }
+    } else {
+        return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string());
+    }
+
+    let payload: ProjectTaskDuplicateRequest = payload.into_inner();
+    let name_suffix = payload.name_suffix.unwrap_or_else(|| " (Copy)".to_string());
+
+    match task.duplicate(payload.include_subtasks, &name_suffix).await {
+        Ok(new_task_id) => {
+            let total = ProjectTask::find_many(&ProjectTaskQuery {
+                _id: None,
+                project_id: Some(project_id),
+                task_id: task.task_id,
+                area_id: None,
+                limit: None,
+                kind: if task.task_id.is_some() {
+                    None
+                } else {
+                    Some(ProjectTaskQueryKind::Root)
+                },
+            })
+            .await
+            .ok()
+            .flatten()
+            .map_or(0.0, |tasks| tasks.iter().fold(0.0, |a, b| a + b.value));
+
+            let warning = if total - 100.0 > ProjectTask::value_epsilon() {
+                Some("PROJECT_TASK_VALUE_SUM_EXCEEDS_100".to_string())
+            } else {
+                None
+            };
 
-    match project_task.save().await {
-        Ok(task_id) => HttpResponse::Created().body(task_id.to_string()),
+            HttpResponse::Created().json(doc! {
+                "_id": to_bson::<Vec<ObjectId>>(&new_task_id).unwrap(),
+                "warning": to_bson::<Option<String>>(&warning).unwrap()
+            })
+        }
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
-#[post("/projects/{project_id}/tasks/{task_id}")] // FINISHED
-pub async fn create_project_task_sub(
-    _id: web::Path<(String, String)>,
-    payload: web::Json<Vec<ProjectTaskRequest>>,
+
+#[post("/projects/{project_id}/tasks/{task_id}/comments")]
+pub async fn create_project_task_comment(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    payload: web::Json<ProjectTaskCommentRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, task_id) = match (_id.0.parse(), _id.1.parse::<ObjectId>()) {
-        (Ok(project_id), Ok(task_id)) => (project_id, task_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
     };
-    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateTask).await {
+    let members = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project.member.unwrap_or_default(),
+        _ => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+    };
+    if !members.iter().any(|a| a._id == issuer_id) {
         return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
     }
 
-    if let Ok(Some(_)) = ProjectTask::find_many(&ProjectTaskQuery {
-        _id: None,
-        project_id: None,
-        task_id: Some(task_id),
-        area_id: None,
-        limit: None,
-        kind: None,
-    })
-    .await
-    {
-        if ProjectTask::delete_many_by_task_id(&task_id).await.is_err() {
-            return HttpResponse::InternalServerError()
-                .body("PROJECT_TASK_DELETION_FAILED".to_string());
-        }
+    match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => (),
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
     }
 
-    if let Ok(Some(task)) = ProjectTask::find_by_id(&task_id).await {
-        if let Ok(Some(project)) = Project::find_by_id(&task.project_id).await {
-            if project.status.get(0).unwrap().kind != ProjectStatusKind::Pending {
-                return HttpResponse::BadRequest()
-                    .body("PROJECT_STATUS_MUST_BE_PENDING".to_string());
-            }
-            let payload = payload.into_inner();
-            let mut new_task_id = Vec::<ObjectId>::new();
-            let mut total = 0.0;
-
-            for i in &payload {
-                total += i.value;
-            }
+    let payload = payload.into_inner();
+    if payload.body.trim().is_empty() {
+        return HttpResponse::BadRequest().body("INVALID_BODY".to_string());
+    }
 
-            if total != 100.0 {
-                return HttpResponse::BadRequest().body("PROJECT_TASK_VALUE_SUM_MUST_BE_100");
-            }
+    let mut comment = ProjectTaskComment {
+        _id: None,
+        task_id,
+        user_id: issuer_id,
+        body: payload.body.trim().to_string(),
+        create_date: DateTime::from_millis(Utc::now().timestamp_millis()),
+    };
 
-            for i in payload {
-                let mut project_task: ProjectTask = ProjectTask {
+    match comment.save().await {
+        Ok(comment_id) => {
+            let mut notifications = comment
+                .mentions()
+                .into_iter()
+                .filter(|a| *a != issuer_id && members.iter().any(|b| b._id == *a))
+                .map(|a| Notification {
                     _id: None,
+                    user_id: a,
+                    kind: NotificationKind::Mention,
                     project_id,
-                    area_id: task.area_id,
                     task_id: Some(task_id),
-                    user_id: i.user_id,
-                    name: i.name,
-                    volume: i.volume,
-                    value: i.value,
-                    description: i.description,
-                    period: None,
-                    status: vec![ProjectTaskStatus {
-                        kind: ProjectTaskStatusKind::Pending,
-                        time: DateTime::from_millis(Utc::now().timestamp_millis()),
-                        message: None,
-                    }],
-                };
-                match project_task.save().await {
-                    Ok(task_id) => new_task_id.push(task_id),
-                    Err(error) => {
-                        for i in new_task_id {
-                            ProjectTask::delete_by_id(&i)
-                                .await
-                                .expect("PROJECT_TASK_DELETION_FAILED");
-                        }
-                        return HttpResponse::InternalServerError().body(error);
-                    }
-                }
-            }
-
-            HttpResponse::Created().json(doc! {
-                "_id": to_bson::<Vec<ObjectId>>(&new_task_id).unwrap()
-            })
-        } else {
-            HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+                    comment_id: Some(comment_id),
+                    read: false,
+                    create_date: comment.create_date,
+                })
+                .collect::<Vec<Notification>>();
+            let _ = Notification::save_many(&mut notifications).await;
+
+            created_at(format!(
+                "/projects/{}/tasks/{}/comments",
+                project_id, task_id
+            ))
+            .json(doc! { "_id": comment_id.to_string() })
         }
-    } else {
-        HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string())
+        Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
-
 #[post("/projects/{project_id}/reports")]
 pub async fn create_project_report(
-    project_id: web::Path<String>,
+    PathObjectId(project_id): PathObjectId<ObjectId>,
     payload: web::Json<ProjectProgressReportRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -937,38 +3044,75 @@ pub async fn create_project_report(
         plan: payload.plan,
         documentation: None,
         weather: payload.weather,
+        update_date: None,
     };
 
     if let Some(documentation) = payload.documentation {
+        if documentation
+            .iter()
+            .filter(|a| a.cover.unwrap_or(false))
+            .count()
+            > 1
+        {
+            return HttpResponse::BadRequest()
+                .body("PROJECT_REPORT_DOCUMENTATION_MULTIPLE_COVER".to_string());
+        }
         let docs: Vec<ProjectProgressReportDocumentation> = documentation
             .iter()
             .map(|a| ProjectProgressReportDocumentation {
                 description: a.description.clone(),
                 extension: a.extension.clone(),
                 _id: ObjectId::new(),
+                cover: a.cover,
+                upload_date: None,
             })
             .collect();
         project_report.documentation = Some(docs);
     }
 
     match project_report.save().await {
-        Ok(report_id) => HttpResponse::Created().body(report_id.to_string()),
+        Ok(report_id) => {
+            let slot = project_report
+                .documentation
+                .iter()
+                .flatten()
+                .map(|a| ProjectProgressReportSlotResponse {
+                    _id: a._id.to_string(),
+                    url: format!(
+                        "{}/projects/{}/reports/{}/documentation/{}",
+                        std::env::var("BASE_PATH").unwrap(),
+                        project_id,
+                        report_id,
+                        a._id
+                    ),
+                })
+                .collect();
+            actix_web::rt::spawn(async move {
+                if let Err(error) = project_report.notify_owners().await {
+                    println!("Failed to send report notification: {}", error);
+                }
+            });
+            created_at(format!("/projects/{}/reports/{}", project_id, report_id)).json(
+                ProjectProgressReportCreateResponse {
+                    _id: report_id.to_string(),
+                    slot,
+                },
+            )
+        }
+        Err(error) if error == "INVALID_WEATHER_TIME" || error == "PROJECT_REPORT_TIME_INVALID" => {
+            HttpResponse::BadRequest().body(error)
+        }
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
 
 #[post("/projects/{project_id}/incidents")]
 pub async fn create_project_incident(
-    project_id: web::Path<String>,
+    PathObjectId(project_id): PathObjectId<ObjectId>,
     payload: web::Json<ProjectIncidentReportRequest>,
     query: web::Query<ProjectIncidentReportQueryParams>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -989,28 +3133,26 @@ pub async fn create_project_incident(
         _id: None,
         project_id,
         user_id: issuer_id,
+        task_id: payload.task_id,
         member_id: payload.member_id,
         kind: payload.kind,
         date: DateTime::from_millis(Utc::now().timestamp_millis()),
     };
 
     match project_incident.save(query.breakdown).await {
-        Ok(incident_id) => HttpResponse::Created().body(incident_id.to_string()),
+        Ok(incident_id) => created_at(format!("/projects/{}/reports", project_id))
+            .json(doc! { "_id": incident_id.to_string() }),
+        Err(error) if error == "PROJECT_TASK_NOT_FOUND" => HttpResponse::NotFound().body(error),
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
 
 #[put("/projects/{project_id}/status")]
 pub async fn update_project_status(
-    _id: web::Path<String>,
+    PathObjectId(project_id): PathObjectId<ObjectId>,
     query: web::Query<ProjectStatusQueryParams>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match _id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1030,14 +3172,25 @@ pub async fn update_project_status(
             return HttpResponse::BadRequest().body("INVALID_STATUS".to_string());
         }
 
-        if project.status.first().unwrap().kind != ProjectStatusKind::Breakdown
-            && project.status.first().unwrap().kind != ProjectStatusKind::Paused
-        {
-            return HttpResponse::BadRequest().body("PROJECT_STATUS_INVALID".to_string());
+        match project.current_status() {
+            Ok(status)
+                if status.kind != ProjectStatusKind::Breakdown
+                    && status.kind != ProjectStatusKind::Paused =>
+            {
+                return HttpResponse::BadRequest().body("PROJECT_STATUS_INVALID".to_string());
+            }
+            Err(error) => return HttpResponse::InternalServerError().body(error),
+            _ => (),
         }
 
         match project.update_status(query.status.clone(), None).await {
             Ok(project_id) => HttpResponse::Ok().body(project_id.to_string()),
+            Err(error) if error == "PROJECT_TASK_VALUE_SUM_MUST_BE_100" => {
+                match project.calculate_value_summary().await {
+                    Ok(summary) => HttpResponse::BadRequest().json(summary),
+                    Err(_) => HttpResponse::BadRequest().body(error),
+                }
+            }
             Err(error) => HttpResponse::InternalServerError().body(error),
         }
     } else {
@@ -1046,15 +3199,10 @@ pub async fn update_project_status(
 }
 #[put("/projects/{project_id}/tasks/{task_id}")] // FINISHED
 pub async fn update_project_task(
-    _id: web::Path<(String, String)>,
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
     payload: web::Json<ProjectTaskRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, task_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(task_id)) => (project_id, task_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1065,12 +3213,20 @@ pub async fn update_project_task(
 
     if let Ok(Some(mut task)) = ProjectTask::find_by_id(&task_id).await {
         if let Ok(Some(project)) = Project::find_by_id(&task.project_id).await {
-            if project.status.get(0).unwrap().kind != ProjectStatusKind::Pending {
-                return HttpResponse::BadRequest()
-                    .body("PROJECT_STATUS_MUST_BE_PENDING".to_string());
+            match project.current_status() {
+                Ok(status) if status.kind != ProjectStatusKind::Pending => {
+                    return HttpResponse::BadRequest()
+                        .body("PROJECT_STATUS_MUST_BE_PENDING".to_string());
+                }
+                Err(error) => return HttpResponse::InternalServerError().body(error),
+                _ => (),
             }
             let payload: ProjectTaskRequest = payload.into_inner();
 
+            task.cost = match payload.cost.map(ProjectTaskCost::from_request).transpose() {
+                Ok(cost) => cost,
+                Err(error) => return HttpResponse::BadRequest().body(error),
+            };
             task.name = payload.name;
             task.volume = payload.volume;
             task.description = payload.description;
@@ -1088,17 +3244,58 @@ pub async fn update_project_task(
         HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string())
     }
 }
+#[put("/projects/{project_id}/tasks/{task_id}/checklist/{checklist_id}")]
+pub async fn update_project_task_checklist(
+    PathObjectId((project_id, task_id, checklist_id)): PathObjectId<(ObjectId, ObjectId, ObjectId)>,
+    payload: web::Json<ProjectTaskChecklistUpdateRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::UpdateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut task = match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => task,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+    };
+
+    let payload = payload.into_inner();
+    let item = match task
+        .checklist
+        .as_mut()
+        .and_then(|checklist| checklist.iter_mut().find(|a| a._id == checklist_id))
+    {
+        Some(item) => item,
+        None => {
+            return HttpResponse::NotFound().body("PROJECT_TASK_CHECKLIST_NOT_FOUND".to_string())
+        }
+    };
+
+    if let Some(label) = payload.label {
+        if label.trim().is_empty() {
+            return HttpResponse::BadRequest().body("INVALID_LABEL".to_string());
+        }
+        item.label = label.trim().to_string();
+    }
+    if let Some(done) = payload.done {
+        item.done = done;
+    }
+
+    match task.update().await {
+        Ok(_) => HttpResponse::Ok().body(checklist_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
 #[put("/projects/{project_id}/tasks/{task_id}/status")]
 pub async fn update_project_task_status(
-    _id: web::Path<(String, String)>,
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
     payload: web::Json<ProjectTaskStatusRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, task_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(task_id)) => (project_id, task_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1110,8 +3307,27 @@ pub async fn update_project_task_status(
     if let Ok(Some(mut task)) = ProjectTask::find_by_id(&task_id).await {
         let payload: ProjectTaskStatusRequest = payload.into_inner();
 
+        if payload.kind == ProjectTaskStatusKind::Finished {
+            if let Ok(Some(_)) = ProjectTask::find_many(&ProjectTaskQuery {
+                _id: None,
+                project_id: Some(task.project_id),
+                task_id: Some(task_id),
+                area_id: None,
+                limit: Some(1),
+                kind: None,
+            })
+            .await
+            {
+                return HttpResponse::BadRequest()
+                    .body("CANNOT_FINISH_PARENT_DIRECTLY".to_string());
+            }
+        }
+
         match task.update_status(payload.kind, payload.message).await {
             Ok(task_id) => HttpResponse::Ok().body(task_id.to_string()),
+            Err(error) if error == "INVALID_TASK_TRANSITION" || error == "CHECKLIST_INCOMPLETE" => {
+                HttpResponse::BadRequest().body(error)
+            }
             Err(error) => HttpResponse::InternalServerError().body(error),
         }
     } else {
@@ -1120,15 +3336,10 @@ pub async fn update_project_task_status(
 }
 #[put("/projects/{project_id}/tasks/{task_id}/period")]
 pub async fn update_project_task_period(
-    _id: web::Path<(String, String)>,
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
     payload: web::Json<ProjectTaskPeriodRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, task_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(task_id)) => (project_id, task_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1140,30 +3351,187 @@ pub async fn update_project_task_period(
     if let Ok(Some(mut task)) = ProjectTask::find_by_id(&task_id).await {
         let payload: ProjectTaskPeriodRequest = payload.into_inner();
 
-        let period: ProjectTaskPeriod = ProjectTaskPeriod {
-            start: DateTime::from_millis(payload.start),
-            end: DateTime::from_millis(payload.end),
-        };
+        let period: ProjectTaskPeriod = ProjectTaskPeriod {
+            start: DateTime::from_millis(payload.start),
+            end: DateTime::from_millis(payload.end),
+        };
+
+        match task.update_period(period).await {
+            Ok(task_id) => HttpResponse::Ok().body(task_id.to_string()),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string())
+    }
+}
+#[put("/projects/{project_id}/reports/{report_id}")]
+pub async fn update_project_report(
+    PathObjectId((project_id, report_id)): PathObjectId<(ObjectId, ObjectId)>,
+    form: MultipartForm<ProjectProgressReportDocumentationMultipartRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::UpdateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut report = match ProjectProgressReport::find_by_id(&report_id).await {
+        Ok(Some(report)) if report.project_id == project_id => report,
+        _ => return HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
+    };
+
+    let save_dir = FileKind::ProjectDocumentation.path(format!("{}/", report_id));
+
+    if create_dir_all(&save_dir).is_err() {
+        return HttpResponse::InternalServerError().body("DIRECTORY_CREATION_FAILED".to_string());
+    }
+
+    let mut documentation = match report.documentation {
+        Some(documentation) => documentation,
+        None => {
+            return HttpResponse::BadRequest()
+                .body("PROJECT_REPORT_DOCUMENTATION_NOT_FOUND".to_string())
+        }
+    };
+
+    let slots = documentation
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| !a.is_uploaded(&report_id))
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+    if form.files.len() > slots.len() {
+        return HttpResponse::BadRequest()
+            .body("PROJECT_REPORT_DOCUMENTATION_INVALID_LENGTH".to_string());
+    }
+
+    let mut error: Option<HttpResponse> = None;
+    for (i, file) in slots.into_iter().zip(form.files.iter()) {
+        if let Some(image) = documentation.get_mut(i) {
+            let ext = match file
+                .file_name
+                .as_ref()
+                .and_then(|file_name| Path::new(file_name).extension().and_then(OsStr::to_str))
+            {
+                Some(ext) => ext.to_string(),
+                None => {
+                    error = Some(
+                        HttpResponse::BadRequest()
+                            .body("PROJECT_REPORT_DOCUMENTATION_ONLY_ACCEPTS_IMAGE".to_string()),
+                    );
+                    break;
+                }
+            };
+            let file_path_temp = file.file.path();
+            let file_path =
+                PathBuf::from(save_dir.to_owned() + &image._id.to_string() + "." + &ext);
+            if rename(file_path_temp, &file_path).is_err() {
+                error = Some(
+                    HttpResponse::InternalServerError()
+                        .body("PROJECT_REPORT_DOCUMENTATION_UPLOAD_FAILED".to_string()),
+                );
+                break;
+            }
+            image.extension = ext;
+            image.upload_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
+        }
+    }
+
+    report.documentation = Some(documentation);
+
+    if (report.update().await).is_err() {
+        return HttpResponse::InternalServerError()
+            .body("PROJECT_REPORT_UPDATE_FAILED".to_string());
+    }
+    if let Some(error) = error {
+        return error;
+    }
+
+    HttpResponse::Ok().body(report_id.to_string())
+}
+#[put("/projects/{project_id}/reports/{report_id}/documentation/{documentation_id}")]
+pub async fn update_project_report_documentation(
+    PathObjectId((project_id, report_id, documentation_id)): PathObjectId<(
+        ObjectId,
+        ObjectId,
+        ObjectId,
+    )>,
+    form: MultipartForm<ProjectProgressReportDocumentationMultipartRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::UpdateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut report = match ProjectProgressReport::find_by_id(&report_id).await {
+        Ok(Some(report)) if report.project_id == project_id => report,
+        _ => return HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
+    };
+
+    let file = match form.files.first() {
+        Some(file) if form.files.len() == 1 => file,
+        _ => {
+            return HttpResponse::BadRequest()
+                .body("PROJECT_REPORT_DOCUMENTATION_INVALID_LENGTH".to_string())
+        }
+    };
+    let ext = match file
+        .file_name
+        .as_ref()
+        .and_then(|file_name| Path::new(file_name).extension().and_then(OsStr::to_str))
+    {
+        Some(ext) => ext.to_string(),
+        None => {
+            return HttpResponse::BadRequest()
+                .body("PROJECT_REPORT_DOCUMENTATION_ONLY_ACCEPTS_IMAGE".to_string())
+        }
+    };
+
+    let mut documentation = report.documentation.unwrap_or_default();
+    let image = match documentation.iter_mut().find(|a| a._id == documentation_id) {
+        Some(image) => image,
+        None => {
+            return HttpResponse::NotFound()
+                .body("PROJECT_REPORT_DOCUMENTATION_NOT_FOUND".to_string())
+        }
+    };
+
+    let save_dir = FileKind::ProjectDocumentation.path(format!("{}/", report_id));
+    if create_dir_all(&save_dir).is_err() {
+        return HttpResponse::InternalServerError().body("DIRECTORY_CREATION_FAILED".to_string());
+    }
+
+    if image.is_uploaded(&report_id) {
+        let _ = fs::remove_file(format!("{}{}.{}", save_dir, image._id, image.extension));
+    }
+    let file_path = PathBuf::from(format!("{}{}.{}", save_dir, image._id, ext));
+    if rename(file.file.path(), &file_path).is_err() {
+        return HttpResponse::InternalServerError()
+            .body("PROJECT_REPORT_DOCUMENTATION_UPLOAD_FAILED".to_string());
+    }
+    image.extension = ext;
+    image.upload_date = Some(DateTime::from_millis(Utc::now().timestamp_millis()));
+
+    report.documentation = Some(documentation);
 
-        match task.update_period(period).await {
-            Ok(task_id) => HttpResponse::Ok().body(task_id.to_string()),
-            Err(error) => HttpResponse::InternalServerError().body(error),
-        }
-    } else {
-        HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string())
+    match report.update().await {
+        Ok(_) => HttpResponse::Ok().body(documentation_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
-#[put("/projects/{project_id}/reports/{report_id}")] // REDO ALL CHANGES WHEN FAILED
-pub async fn update_project_report(
-    _id: web::Path<(String, String)>,
-    form: MultipartForm<ProjectProgressReportDocumentationMultipartRequest>,
+#[put("/projects/{project_id}/reports/{report_id}/cover")]
+pub async fn update_project_report_cover(
+    PathObjectId((project_id, report_id)): PathObjectId<(ObjectId, ObjectId)>,
+    payload: web::Json<ProjectProgressReportCoverRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, report_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(report_id)) => (project_id, report_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1173,91 +3541,33 @@ pub async fn update_project_report(
     }
 
     let mut report = match ProjectProgressReport::find_by_id(&report_id).await {
-        Ok(Some(report)) => report,
+        Ok(Some(report)) if report.project_id == project_id => report,
         _ => return HttpResponse::NotFound().body("PROJECT_REPORT_NOT_FOUND".to_string()),
     };
 
-    let save_dir = format!("./files/reports/documentation/{}/", report_id);
-
-    if create_dir_all(&save_dir).is_err() {
-        return HttpResponse::InternalServerError().body("DIRECTORY_CREATION_FAILED".to_string());
+    let mut documentation = report.documentation.unwrap_or_default();
+    if !documentation
+        .iter()
+        .any(|a| a._id == payload.documentation_id)
+    {
+        return HttpResponse::NotFound().body("PROJECT_REPORT_DOCUMENTATION_NOT_FOUND".to_string());
     }
-
-    let mut documentation = match report.documentation {
-        Some(documentation) => {
-            if documentation.len() != form.files.len() {
-                ProjectProgressReport::delete_by_id(&report_id)
-                    .await
-                    .expect("PROJECT_REPORT_DELETION_FAILED");
-                return HttpResponse::BadRequest()
-                    .body("PROJECT_REPORT_DOCUMENTATION_INVALID_LENGTH".to_string());
-            }
-            documentation
-        }
-        None => {
-            return HttpResponse::BadRequest()
-                .body("PROJECT_REPORT_DOCUMENTATION_NOT_FOUND".to_string())
-        }
-    };
-
-    for (i, file) in form.files.iter().enumerate() {
-        if let Some(image) = documentation.get_mut(i) {
-            let mut ext = String::new();
-            if let Some(file_name) = &file.file_name {
-                if let Some(name) = Path::new(file_name).extension().and_then(OsStr::to_str) {
-                    ext = name.to_string();
-                }
-            } else {
-                ProjectProgressReport::delete_by_id(&report_id)
-                    .await
-                    .expect("PROJECT_REPORT_DELETION_FAILED");
-                return HttpResponse::BadRequest()
-                    .body("PROJECT_REPORT_DOCUMENTATION_ONLY_ACCEPTS_IMAGE".to_string());
-            }
-            let file_path_temp = file.file.path();
-            let file_path =
-                PathBuf::from(save_dir.to_owned() + &image._id.to_string() + "." + &ext);
-            if rename(file_path_temp, &file_path).is_err() {
-                if remove_dir_all(file_path).is_ok()
-                    && (ProjectProgressReport::delete_by_id(&report_id).await).is_err()
-                {
-                    return HttpResponse::InternalServerError()
-                        .body("PROJECT_REPORT_DELETION_FAILED".to_string());
-                }
-                break;
-            }
-            image.extension = ext.to_string();
-        } else {
-            ProjectProgressReport::delete_by_id(&report_id)
-                .await
-                .expect("PROJECT_REPORT_DELETION_FAILED");
-            return HttpResponse::InternalServerError()
-                .body("PROJECT_REPORT_DOCUMENTATION_MALFORMED".to_string());
-        }
+    for image in documentation.iter_mut() {
+        image.cover = Some(image._id == payload.documentation_id);
     }
-
     report.documentation = Some(documentation);
 
-    if (report.update().await).is_err() {
-        ProjectProgressReport::delete_by_id(&report_id)
-            .await
-            .expect("PROJECT_REPORT_DELETION_FAILED");
-        HttpResponse::InternalServerError().body("PROJECT_REPORT_UPDATE_FAILED".to_string());
+    match report.update().await {
+        Ok(report_id) => HttpResponse::Ok().body(report_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
     }
-
-    HttpResponse::Ok().body(report_id.to_string())
 }
 #[put("/projects/{project_id}/roles/{role_id}")] // REDO ALL CHANGES WHEN FAILED
 pub async fn update_project_role(
-    _id: web::Path<(String, String)>,
+    PathObjectId((project_id, role_id)): PathObjectId<(ObjectId, ObjectId)>,
     payload: web::Json<ProjectRoleRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, role_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(role_id)) => (project_id, role_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1282,17 +3592,38 @@ pub async fn update_project_role(
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
+#[put("/projects/{project_id}/budget")]
+pub async fn update_project_budget(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    payload: web::Json<ProjectBudgetRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::Owner).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(mut project)) = Project::find_by_id(&project_id).await {
+        match project.update_budget(payload.into_inner()).await {
+            Ok(project_id) => HttpResponse::Ok().body(project_id.to_string()),
+            Err(error) if error == "INVALID_BUDGET" || error == "INVALID_SPENT" => {
+                HttpResponse::BadRequest().body(error)
+            }
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
 #[put("/projects/{project_id}/members")]
 pub async fn add_project_member(
-    project_id: web::Path<String>,
+    PathObjectId(project_id): PathObjectId<ObjectId>,
     payload: web::Json<ProjectMemberRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1312,18 +3643,43 @@ pub async fn add_project_member(
         HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
     }
 }
+#[post("/projects/{project_id}/members/bulk")]
+pub async fn create_project_member_bulk(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    form: MultipartForm<ProjectMemberMultipartRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateRole).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        _ => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+    };
+
+    let bytes = match read_bulk_file(&form.file) {
+        Ok(bytes) => bytes,
+        Err(response) => return *response,
+    };
+    let (rows, errors) = parse_bulk_members(&String::from_utf8_lossy(&bytes));
+
+    match project.add_member_bulk(rows, errors).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
 //DIGANTI POST -> PATCH!!!!!
 #[put("/projects/{project_id}/areas")] // FINISHED
 pub async fn add_project_area(
-    project_id: web::Path<String>,
+    PathObjectId(project_id): PathObjectId<ObjectId>,
     payload: web::Json<ProjectAreaRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let project_id = match project_id.parse() {
-        Ok(project_id) => project_id,
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
-    };
-
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1343,16 +3699,73 @@ pub async fn add_project_area(
         HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
     }
 }
-#[delete("/projects/{project_id}/areas/{area_id}")]
-pub async fn delete_project_area(
-    _id: web::Path<(String, String)>,
+#[put("/projects/{project_id}/areas/{area_id}")]
+pub async fn update_project_area(
+    PathObjectId((project_id, area_id)): PathObjectId<(ObjectId, ObjectId)>,
+    payload: web::Json<ProjectAreaRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::CreateRole).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    if let Ok(Some(mut project)) = Project::find_by_id(&project_id).await {
+        match project.update_area(&area_id, payload.into_inner()).await {
+            Ok(project_id) => HttpResponse::Ok().body(project_id.to_string()),
+            Err(error) if error == "PROJECT_AREA_NOT_FOUND" => HttpResponse::NotFound().body(error),
+            Err(error) => HttpResponse::InternalServerError().body(error),
+        }
+    } else {
+        HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
+    }
+}
+#[delete("/projects/{project_id}/areas/empty")]
+pub async fn delete_project_areas_empty(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, area_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(area_id)) => (project_id, area_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::DeleteTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut project = match Project::find_by_id(&project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string()),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    };
+
+    let empty_id = match project.find_empty_areas().await {
+        Ok(areas) => areas.into_iter().map(|a| a._id).collect::<Vec<ObjectId>>(),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
     };
+    if empty_id.is_empty() {
+        return HttpResponse::Ok().json(Vec::<String>::new());
+    }
 
+    match project.remove_many_area(&empty_id).await {
+        Ok(_) => HttpResponse::Ok().json(
+            empty_id
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<String>>(),
+        ),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[delete("/projects/{project_id}/areas/{area_id}")]
+pub async fn delete_project_area(
+    PathObjectId((project_id, area_id)): PathObjectId<(ObjectId, ObjectId)>,
+    query: web::Query<ProjectAreaDeleteQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1362,7 +3775,21 @@ pub async fn delete_project_area(
     }
 
     if let Ok(Some(mut project)) = Project::find_by_id(&project_id).await {
-        if ProjectTask::delete_many_by_area_id(&area_id).await.is_ok() {
+        let area = project.area.clone().unwrap_or_default();
+        if !area.iter().any(|a| a._id == area_id) {
+            return HttpResponse::NotFound().body("PROJECT_AREA_NOT_FOUND".to_string());
+        }
+
+        let result = if let Some(move_to) = query.move_to {
+            if move_to == area_id || !area.iter().any(|a| a._id == move_to) {
+                return HttpResponse::BadRequest().body("INVALID_TARGET_AREA".to_string());
+            }
+            ProjectTask::update_many_area_id(&project_id, &area_id, &move_to).await
+        } else {
+            ProjectTask::delete_many_by_area_id(&area_id).await
+        };
+
+        if result.is_ok() {
             match project.remove_area(&area_id).await {
                 Ok(_id) => HttpResponse::Ok().body(_id.to_string()),
                 Err(error) => HttpResponse::InternalServerError().body(error),
@@ -1374,16 +3801,73 @@ pub async fn delete_project_area(
         HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
     }
 }
-#[delete("/projects/{project_id}/tasks/{task_id}")]
-pub async fn delete_project_task(
-    _id: web::Path<(String, String)>,
+#[delete("/projects/{project_id}/tasks")]
+pub async fn delete_project_tasks(
+    PathObjectId(project_id): PathObjectId<ObjectId>,
+    payload: web::Json<ProjectTaskDeleteRequest>,
     req: HttpRequest,
 ) -> HttpResponse {
-    let (project_id, task_id) = match (_id.0.parse(), _id.1.parse()) {
-        (Ok(project_id), Ok(task_id)) => (project_id, task_id),
-        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::DeleteTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let payload = payload.into_inner();
+
+    let tasks = match ProjectTask::find_many(&ProjectTaskQuery {
+        _id: None,
+        project_id: Some(project_id),
+        task_id: None,
+        area_id: None,
+        limit: None,
+        kind: None,
+    })
+    .await
+    {
+        Ok(Some(tasks)) => tasks,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
     };
 
+    for _id in payload.task_ids.iter() {
+        if !tasks.iter().any(|a| a._id == Some(*_id)) {
+            return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string());
+        }
+        if !payload.cascade && tasks.iter().any(|a| a.task_id == Some(*_id)) {
+            return HttpResponse::BadRequest().body("TASK_HAS_SUBTASKS".to_string());
+        }
+    }
+
+    let mut deleted = BTreeMap::<String, u64>::new();
+    let mut deleted_id = Vec::<ObjectId>::new();
+    for _id in payload.task_ids.iter() {
+        if deleted_id.contains(_id) {
+            deleted.insert(_id.to_string(), 0);
+            continue;
+        }
+        match ProjectTask::delete_tree(_id).await {
+            Ok(task_id) => {
+                deleted.insert(_id.to_string(), task_id.len() as u64);
+                deleted_id.extend(task_id);
+            }
+            Err(error) => return HttpResponse::InternalServerError().body(error),
+        }
+    }
+
+    if let Err(error) = ProjectProgressReport::delete_many_task_entry(&deleted_id).await {
+        return HttpResponse::InternalServerError().body(error);
+    }
+
+    HttpResponse::Ok().json(deleted)
+}
+#[delete("/projects/{project_id}/tasks/{task_id}")]
+pub async fn delete_project_task(
+    PathObjectId((project_id, task_id)): PathObjectId<(ObjectId, ObjectId)>,
+    query: web::Query<ProjectTaskDeleteQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
     let issuer_id = match req.extensions().get::<UserAuthentication>() {
         Some(issuer) => issuer._id.unwrap(),
         None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
@@ -1393,11 +3877,192 @@ pub async fn delete_project_task(
     }
 
     if let Ok(Some(_)) = Project::find_by_id(&project_id).await {
-        match ProjectTask::delete_by_id(&task_id).await {
-            Ok(result) => HttpResponse::NoContent().body(result.to_string()),
+        match ProjectTask::find_by_id(&task_id).await {
+            Ok(Some(task)) if task.project_id == project_id => (),
+            _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+        }
+
+        if !query.cascade.unwrap_or(false) {
+            if let Ok(Some(_)) = ProjectTask::find_many(&ProjectTaskQuery {
+                _id: None,
+                project_id: Some(project_id),
+                task_id: Some(task_id),
+                area_id: None,
+                limit: Some(1),
+                kind: None,
+            })
+            .await
+            {
+                return HttpResponse::BadRequest().body("TASK_HAS_SUBTASKS".to_string());
+            }
+        }
+
+        match ProjectTask::delete_tree(&task_id).await {
+            Ok(result) => {
+                if let Err(error) = ProjectProgressReport::delete_many_task_entry(&result).await {
+                    return HttpResponse::InternalServerError().body(error);
+                }
+                HttpResponse::NoContent().body(result.len().to_string())
+            }
             Err(_) => HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
         }
     } else {
         HttpResponse::NotFound().body("PROJECT_NOT_FOUND".to_string())
     }
 }
+#[delete("/projects/{project_id}/tasks/{task_id}/attachments/{attachment_id}")]
+pub async fn delete_project_task_attachment(
+    PathObjectId((project_id, task_id, attachment_id)): PathObjectId<(
+        ObjectId,
+        ObjectId,
+        ObjectId,
+    )>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::UpdateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut task = match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => task,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+    };
+
+    let mut attachment = task.attachment.clone().unwrap_or_default();
+    let index = match attachment.iter().position(|a| a._id == attachment_id) {
+        Some(index) => index,
+        None => {
+            return HttpResponse::NotFound().body("PROJECT_TASK_ATTACHMENT_NOT_FOUND".to_string())
+        }
+    };
+    let removed = attachment.remove(index);
+
+    task.attachment = if attachment.is_empty() {
+        None
+    } else {
+        Some(attachment)
+    };
+
+    match task.update().await {
+        Ok(task_id) => {
+            let _ = fs::remove_file(
+                FileKind::TaskAttachment
+                    .path(format!("{}/{}.{}", task_id, removed._id, removed.extension)),
+            );
+            HttpResponse::Ok().body(task_id.to_string())
+        }
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[delete("/projects/{project_id}/tasks/{task_id}/checklist/{checklist_id}")]
+pub async fn delete_project_task_checklist(
+    PathObjectId((project_id, task_id, checklist_id)): PathObjectId<(ObjectId, ObjectId, ObjectId)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+    if !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::UpdateTask).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    let mut task = match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => task,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+    };
+
+    let mut checklist = task.checklist.take().unwrap_or_default();
+    match checklist.iter().position(|a| a._id == checklist_id) {
+        Some(index) => {
+            checklist.remove(index);
+        }
+        None => {
+            return HttpResponse::NotFound().body("PROJECT_TASK_CHECKLIST_NOT_FOUND".to_string())
+        }
+    }
+    task.checklist = if checklist.is_empty() {
+        None
+    } else {
+        Some(checklist)
+    };
+
+    match task.update().await {
+        Ok(_) => HttpResponse::Ok().body(checklist_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[delete("/projects/{project_id}/tasks/{task_id}/comments/{comment_id}")]
+pub async fn delete_project_task_comment(
+    PathObjectId((project_id, task_id, comment_id)): PathObjectId<(ObjectId, ObjectId, ObjectId)>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+
+    match ProjectTask::find_by_id(&task_id).await {
+        Ok(Some(task)) if task.project_id == project_id => (),
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_NOT_FOUND".to_string()),
+    }
+    let comment = match ProjectTaskComment::find_by_id(&comment_id).await {
+        Ok(Some(comment)) if comment.task_id == task_id => comment,
+        _ => return HttpResponse::NotFound().body("PROJECT_TASK_COMMENT_NOT_FOUND".to_string()),
+    };
+    if comment.user_id != issuer_id
+        && !ProjectRole::validate(&project_id, &issuer_id, &ProjectRolePermission::Owner).await
+    {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string());
+    }
+
+    match ProjectTaskComment::delete_by_id(&comment_id).await {
+        Ok(_) => HttpResponse::Ok().body(comment_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_bulk_members, split_bulk_line};
+    use crate::models::project::ProjectMemberKind;
+
+    #[test]
+    fn bulk_line_keeps_quoted_separators() {
+        assert_eq!(
+            split_bulk_line(r#"a@b.com, "Manager, Site|Owner" ;indirect"#),
+            vec!["a@b.com", "Manager, Site|Owner", "indirect"]
+        );
+        assert_eq!(
+            split_bulk_line(r#""say ""hi""",x"#),
+            vec![r#"say "hi""#, "x"]
+        );
+    }
+
+    #[test]
+    fn bulk_members_are_parsed_per_row() {
+        let csv = "email,roles,kind\n\
+                   a@b.com,\"Manager, Site|Owner\",indirect\n\
+                   \n\
+                   c@d.com,Owner\n\
+                   e@f.com,Owner,external\n";
+        let (rows, errors) = parse_bulk_members(csv);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].row, 2);
+        assert_eq!(rows[0].email, "a@b.com");
+        assert_eq!(rows[0].role, vec!["Manager, Site", "Owner"]);
+        assert!(matches!(rows[0].kind, ProjectMemberKind::Indirect));
+        assert_eq!(rows[1].row, 4);
+        assert!(matches!(rows[1].kind, ProjectMemberKind::Direct));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].row, 5);
+        assert_eq!(errors[0].value, "external");
+        assert_eq!(errors[0].error, "INVALID_MEMBER_KIND");
+    }
+}
@@ -1,5 +1,7 @@
 use actix_web::{delete, get, post, put, web, HttpMessage, HttpRequest, HttpResponse};
+use mongodb::bson::doc;
 
+use super::created_at;
 use crate::models::{
     role::{Role, RolePermission, RoleQuery, RoleRequest},
     user::UserAuthentication,
@@ -40,7 +42,7 @@ pub async fn create_role(payload: web::Json<RoleRequest>, req: HttpRequest) -> H
     }
 
     match role.save().await {
-        Ok(_id) => HttpResponse::Created().body(_id.to_string()),
+        Ok(_id) => created_at(format!("/roles/{}", _id)).json(doc! { "_id": _id.to_string() }),
         Err(error) => HttpResponse::InternalServerError().body(error),
     }
 }
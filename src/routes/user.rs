@@ -7,16 +7,28 @@ use actix_multipart::form::MultipartForm;
 use actix_web::{get, post, put, web, HttpMessage, HttpRequest, HttpResponse};
 use mime_guess::get_mime_extensions_str;
 use mongodb::bson::{doc, oid::ObjectId, to_bson};
-use regex::Regex;
+use serde::Deserialize;
 
+use super::created_at;
 use crate::models::{
+    is_valid_email,
+    notification::{Notification, NotificationQuery},
+    page_limit,
     role::{Role, RolePermission},
     user::{
-        User, UserAuthentication, UserCredential, UserImage, UserImageMultipartRequest, UserQuery,
-        UserRefreshRequest, UserRequest, UserResponse,
+        User, UserAuthentication, UserCredential, UserImage, UserImageMultipartRequest,
+        UserPreference, UserQuery, UserRefreshRequest, UserRequest, UserResponse,
+        UserRoleAssignRequest, UserRoleAssignResponse,
     },
+    FileKind,
 };
 
+#[derive(Deserialize)]
+pub struct UserNotificationQueryParams {
+    pub read: Option<bool>,
+    pub limit: Option<usize>,
+}
+
 #[get("/users")]
 pub async fn get_users() -> HttpResponse {
     let query: UserQuery = UserQuery {
@@ -31,6 +43,27 @@ pub async fn get_users() -> HttpResponse {
         Err(error) => HttpResponse::BadRequest().body(error),
     }
 }
+#[get("/users/me/notifications")]
+pub async fn get_user_notifications(
+    query: web::Query<UserNotificationQueryParams>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+
+    let query = NotificationQuery {
+        user_id: issuer_id,
+        read: query.read,
+        limit: Some(page_limit(query.limit)),
+    };
+
+    match Notification::find_many(&query).await {
+        Ok(notifications) => HttpResponse::Ok().json(notifications),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
 #[get("/users/{user_id}")]
 pub async fn get_user(user_id: web::Path<String>) -> HttpResponse {
     let user_id = match user_id.parse() {
@@ -46,18 +79,15 @@ pub async fn get_user(user_id: web::Path<String>) -> HttpResponse {
 }
 #[post("/users")]
 pub async fn create_user(payload: web::Json<UserRequest>, req: HttpRequest) -> HttpResponse {
-    let payload: UserRequest = payload.into_inner();
-    let email_regex: Regex = Regex::new(
-        r"^([a-z0-9_+]([a-z0-9_+.]*[a-z0-9_+])?)@([a-z0-9]+([\-\.]{1}[a-z0-9]+)*\.[a-z]{2,6})",
-    )
-    .unwrap();
+    let mut payload: UserRequest = payload.into_inner();
 
     if payload.password.len() < 8 {
         return HttpResponse::BadRequest().body("USER_MUST_HAVE_VALID_PASSWORD");
     }
-    if !email_regex.is_match(&payload.email) {
-        return HttpResponse::BadRequest().body("USER_MUST_HAVE_VALID_EMAIL");
+    if !is_valid_email(&payload.email) {
+        return HttpResponse::BadRequest().body("INVALID_EMAIL");
     }
+    payload.email = payload.email.trim().to_string();
 
     let mut user: User = User {
         _id: None,
@@ -66,6 +96,7 @@ pub async fn create_user(payload: web::Json<UserRequest>, req: HttpRequest) -> H
         email: payload.email,
         password: payload.password,
         image: None,
+        preference: None,
     };
 
     if (User::find_many(&UserQuery {
@@ -121,11 +152,96 @@ pub async fn create_user(payload: web::Json<UserRequest>, req: HttpRequest) -> H
         HttpResponse::BadRequest().body("USER_ALREADY_EXIST")
     } else {
         match user.save().await {
-            Ok(id) => HttpResponse::Created().body(id.to_string()),
+            Ok(id) => created_at(format!("/users/{}", id)).json(doc! { "_id": id.to_string() }),
             Err(error) => HttpResponse::InternalServerError().body(error),
         }
     }
 }
+#[post("/users/roles/assign")]
+pub async fn create_user_role_assignment(
+    payload: web::Json<UserRoleAssignRequest>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_role = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer.role_id.clone(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+    if issuer_role.is_empty() || !Role::validate(&issuer_role, &RolePermission::UpdateUser).await {
+        return HttpResponse::Unauthorized().body("UNAUTHORIZED");
+    }
+
+    let payload: UserRoleAssignRequest = payload.into_inner();
+
+    match Role::find_by_id(&payload.role_id).await {
+        Ok(Some(_)) => (),
+        Ok(None) => return HttpResponse::NotFound().body("ROLE_NOT_FOUND"),
+        Err(error) => return HttpResponse::InternalServerError().body(error),
+    }
+
+    let mut results = Vec::<UserRoleAssignResponse>::new();
+    for user_id in payload.user_ids.iter() {
+        let result = match User::update_role(user_id, &payload.role_id, &payload.mode).await {
+            Ok(true) => "UPDATED".to_string(),
+            Ok(false) => "UNCHANGED".to_string(),
+            Err(error) => error,
+        };
+        results.push(UserRoleAssignResponse {
+            _id: user_id.to_string(),
+            result,
+        });
+    }
+
+    HttpResponse::Ok().json(results)
+}
+#[put("/users/me/notifications/{notification_id}/read")]
+pub async fn update_user_notification_read(
+    notification_id: web::Path<String>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let notification_id: ObjectId = match notification_id.parse() {
+        Ok(notification_id) => notification_id,
+        _ => return HttpResponse::BadRequest().body("INVALID_ID".to_string()),
+    };
+
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED".to_string()),
+    };
+
+    match Notification::update_read(&notification_id, &issuer_id).await {
+        Ok(0) => HttpResponse::NotFound().body("NOTIFICATION_NOT_FOUND".to_string()),
+        Ok(_) => HttpResponse::Ok().body(notification_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
+#[put("/users/me/preference")]
+pub async fn update_user_preference(
+    payload: web::Json<UserPreference>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let issuer_id = match req.extensions().get::<UserAuthentication>() {
+        Some(issuer) => issuer._id.unwrap(),
+        None => return HttpResponse::Unauthorized().body("UNAUTHORIZED"),
+    };
+
+    let mut user = match User::find_by_id(&issuer_id).await {
+        Ok(Some(user)) => user,
+        _ => return HttpResponse::NotFound().body("USER_NOT_FOUND"),
+    };
+    let payload = payload.into_inner();
+    user.preference = Some(match user.preference {
+        Some(preference) => UserPreference {
+            report_email: payload.report_email.or(preference.report_email),
+            digest_email: payload.digest_email.or(preference.digest_email),
+        },
+        None => payload,
+    });
+
+    match user.update(false).await {
+        Ok(user_id) => HttpResponse::Ok().body(user_id.to_string()),
+        Err(error) => HttpResponse::InternalServerError().body(error),
+    }
+}
 #[put("/users/{user_id}")]
 pub async fn update_user(
     user_id: web::Path<String>,
@@ -146,11 +262,16 @@ pub async fn update_user(
     };
 
     if let Ok(Some(user)) = User::find_by_id(&user_id).await {
-        let payload = payload.into_inner();
+        let mut payload = payload.into_inner();
         let mut update_hash = false;
 
+        if !is_valid_email(&payload.email) {
+            return HttpResponse::BadRequest().body("INVALID_EMAIL");
+        }
+        payload.email = payload.email.trim().to_string();
+
         if user.image.is_some() {
-            let old_path = format!("./files/users/{user_id}",);
+            let old_path = FileKind::UserImage.path(user_id);
             match remove_dir_all(old_path) {
                 _ => (),
             };
@@ -163,6 +284,7 @@ pub async fn update_user(
             email: payload.email,
             password: user.password,
             image: None,
+            preference: user.preference,
         };
 
         if payload.password != *"*" {
@@ -210,7 +332,7 @@ pub async fn update_user_image(
             None => return HttpResponse::BadRequest().body("USER_IMAGE_NOT_FOUND"),
         };
 
-        let save_dir = format!("./files/users/{}/", user_id);
+        let save_dir = FileKind::UserImage.path(format!("{}/", user_id));
 
         if create_dir_all(&save_dir).is_err() {
             return HttpResponse::InternalServerError()
@@ -0,0 +1,139 @@
+use actix_web::rt::time::interval;
+use chrono::{Local, NaiveTime, Utc};
+use mongodb::{
+    bson::{doc, Document},
+    options::UpdateOptions,
+    Collection, Database,
+};
+use std::time::Duration;
+
+use crate::{
+    database::get_db,
+    mailer,
+    models::{
+        project::{Project, ProjectStatusKind},
+        project_task::{
+            ProjectTask, ProjectTaskQuery, ProjectTaskQueryKind, ProjectTaskStatusKind,
+        },
+    },
+};
+
+async fn claim(name: &str, date: &str) -> bool {
+    let db: Database = get_db();
+    let collection: Collection<Document> = db.collection::<Document>("jobs");
+
+    let options = UpdateOptions::builder().upsert(true).build();
+
+    collection
+        .update_one(
+            doc! { "_id": name, "date": { "$ne": date } },
+            doc! { "$set": { "date": date } },
+            options,
+        )
+        .await
+        .is_ok_and(|result| result.modified_count > 0 || result.upserted_id.is_some())
+}
+
+async fn run_overdue_digest() -> Result<usize, String> {
+    let now = Utc::now().timestamp_millis();
+    let mut sent = 0;
+
+    for project in Project::find_many_by_status(&ProjectStatusKind::Running).await? {
+        let tasks = ProjectTask::find_many(&ProjectTaskQuery {
+            _id: None,
+            project_id: project._id,
+            task_id: None,
+            area_id: None,
+            limit: None,
+            kind: Some(ProjectTaskQueryKind::Base),
+        })
+        .await?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|a| {
+            a.period
+                .as_ref()
+                .is_some_and(|period| period.end.timestamp_millis() < now)
+                && !a
+                    .status
+                    .first()
+                    .is_some_and(|status| status.kind == ProjectTaskStatusKind::Finished)
+        })
+        .collect::<Vec<ProjectTask>>();
+
+        if tasks.is_empty() {
+            continue;
+        }
+
+        let emails = project
+            .find_owners()
+            .await
+            .into_iter()
+            .filter(|a| {
+                a.preference
+                    .as_ref()
+                    .and_then(|preference| preference.digest_email)
+                    .unwrap_or(true)
+            })
+            .map(|a| a.email)
+            .collect::<Vec<String>>();
+
+        if emails.is_empty() {
+            continue;
+        }
+
+        let mut body = format!("{} has {} overdue task(s):\n\n", project.name, tasks.len());
+        for task in tasks.iter() {
+            let end = task.period.as_ref().unwrap().end.timestamp_millis();
+            body.push_str(&format!(
+                "- {} (due {}, {} day(s) overdue)\n",
+                task.name,
+                task.period
+                    .as_ref()
+                    .unwrap()
+                    .end
+                    .try_to_rfc3339_string()
+                    .unwrap_or_default(),
+                (now - end) / 86400000
+            ));
+        }
+        body.push_str(
+            "\nTo stop receiving these emails, turn off digest emails in your preferences.",
+        );
+
+        match mailer::send(&emails, &format!("[{}] Overdue tasks", project.name), &body).await {
+            Ok(_) => sent += 1,
+            Err(error) => println!(
+                "Failed to send overdue digest for {}: {}",
+                project.name, error
+            ),
+        }
+    }
+
+    Ok(sent)
+}
+
+pub fn start() {
+    let time = NaiveTime::parse_from_str(&std::env::var("DIGEST_TIME").unwrap(), "%H:%M")
+        .expect("INVALID_DIGEST_TIME");
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(60));
+        loop {
+            ticker.tick().await;
+
+            let now = Local::now();
+            if !mailer::is_enabled() || now.time() < time {
+                continue;
+            }
+            if !claim("overdue_digest", &now.date_naive().to_string()).await {
+                continue;
+            }
+
+            match run_overdue_digest().await {
+                Ok(sent) => println!("Sent overdue digest for {} project(s)", sent),
+                Err(error) => println!("Failed to run overdue digest: {}", error),
+            }
+        }
+    });
+}